@@ -0,0 +1,638 @@
+// Combat math against typical player characters: the chances of a creature's
+// Strikes and DCs, and how fast its damage takes down an average PC.
+
+use crate::damage::DamageRoll;
+use crate::party::Pc;
+use crate::strike::{self, CriticalTrait};
+
+pub const MIN_PC_LEVEL: i32 = 1;
+pub const MAX_PC_LEVEL: i32 = 20;
+
+// Multiple attack penalty of the second Strike in a round.
+const MULTIPLE_ATTACK_PENALTY: f64 = 5.0;
+const AGILE_MULTIPLE_ATTACK_PENALTY: f64 = 4.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PcBenchmark {
+    pub level: i32,
+    pub ac: f64,
+    // A PC's middle saving throw, neither its best nor its worst.
+    pub save: f64,
+    pub hp: f64,
+}
+
+// AC, save and HP of a typical PC at levels 1 to 20. AC assumes armor with 5
+// from Dexterity and item bonus, trained until 13th level, expert until 19th
+// and master after, plus potency runes. Saves assume expert until 11th level
+// and master after, plus resilient runes. HP assume an 8 HP ancestry and
+// 10 HP class with a Constitution modifier from +2 to +4.
+fn pc_benchmarks() -> Vec<Vec<i32>> {
+    vec![
+        vec![18, 7, 20],
+        vec![19, 8, 32],
+        vec![20, 9, 44],
+        vec![21, 10, 56],
+        vec![23, 12, 68],
+        vec![24, 13, 80],
+        vec![25, 14, 92],
+        vec![26, 16, 104],
+        vec![27, 17, 116],
+        vec![28, 18, 138],
+        vec![30, 21, 151],
+        vec![31, 22, 164],
+        vec![34, 23, 177],
+        vec![35, 25, 190],
+        vec![36, 27, 203],
+        vec![37, 28, 216],
+        vec![38, 29, 229],
+        vec![40, 30, 242],
+        vec![43, 31, 255],
+        vec![45, 33, 288],
+    ]
+}
+
+// The benchmark for a PC of the given level, clamped to the PC level range.
+pub fn pc_benchmark(level: i32) -> PcBenchmark {
+    let level = level.clamp(MIN_PC_LEVEL, MAX_PC_LEVEL);
+    let i: usize = (level - MIN_PC_LEVEL)
+        .try_into()
+        .expect("level should be in range");
+    let row = &pc_benchmarks()[i];
+
+    PcBenchmark {
+        level,
+        ac: row[0].into(),
+        save: row[1].into(),
+        hp: row[2].into(),
+    }
+}
+
+// The chance of each degree of success of a d20 check.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Chances {
+    pub critical_failure: f64,
+    pub failure: f64,
+    pub success: f64,
+    pub critical_success: f64,
+}
+
+// The degree of success of a d20 roll, from 0 for a critical failure to 3 for
+// a critical success. A natural 20 or 1 moves the degree up or down one step.
+pub fn degree_of_success(roll: i32, modifier: f64, dc: f64) -> usize {
+    let total = f64::from(roll) + modifier;
+
+    let degree: usize = if total >= dc + 10.0 {
+        3
+    } else if total >= dc {
+        2
+    } else if total <= dc - 10.0 {
+        0
+    } else {
+        1
+    };
+
+    if roll == 20 {
+        (degree + 1).min(3)
+    } else if roll == 1 {
+        degree.saturating_sub(1)
+    } else {
+        degree
+    }
+}
+
+pub fn check_chances(modifier: f64, dc: f64) -> Chances {
+    // Number of d20 rolls giving each degree, from critical failure to critical success.
+    let mut rolls = [0; 4];
+
+    for roll in 1..=20 {
+        rolls[degree_of_success(roll, modifier, dc)] += 1;
+    }
+
+    let chance = |degree: usize| f64::from(rolls[degree]) / 20.0;
+
+    Chances {
+        critical_failure: chance(0),
+        failure: chance(1),
+        success: chance(2),
+        critical_success: chance(3),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrikeStats {
+    pub name: String,
+    pub attack_bonus: f64,
+    pub damage: f64,
+    // The dice of the damage, for simulations. Empty if they aren't known.
+    pub rolls: Vec<DamageRoll>,
+    pub agile: bool,
+    // Deadly and fatal traits.
+    pub critical_traits: Vec<CriticalTrait>,
+}
+
+impl StrikeStats {
+    // The penalty of the second Strike in a round.
+    pub fn multiple_attack_penalty(&self) -> f64 {
+        if self.agile {
+            AGILE_MULTIPLE_ATTACK_PENALTY
+        } else {
+            MULTIPLE_ATTACK_PENALTY
+        }
+    }
+
+    pub fn critical_damage(&self) -> f64 {
+        critical_damage(self.damage, &self.rolls, &self.critical_traits)
+    }
+
+    // The average damage of a critical hit, only for Strikes whose traits change it.
+    fn reported_critical_damage(&self) -> Option<f64> {
+        if self.critical_traits.is_empty() {
+            None
+        } else {
+            Some(self.critical_damage())
+        }
+    }
+}
+
+// Average damage of a critical hit with the given average damage of a hit.
+// Fatal adds the difference its bigger dice make to the doubled damage, and
+// extra dice aren't doubled.
+pub fn critical_damage(damage: f64, rolls: &[DamageRoll], traits: &[CriticalTrait]) -> f64 {
+    let (doubled, extra) = strike::critical_rolls(rolls, traits);
+    let fatal: f64 = doubled
+        .iter()
+        .zip(rolls)
+        .map(|(d, r)| d.average_value() - r.average_value())
+        .sum();
+
+    2.0 * (damage + fatal) + extra.average_value()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DcStats {
+    pub label: String,
+    pub dc: f64,
+}
+
+// The statistics of a creature that matter for its offense.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CombatStats {
+    pub strikes: Vec<StrikeStats>,
+    pub dcs: Vec<DcStats>,
+    pub area_damage: Vec<f64>,
+    // Separate strike-attack and strike-damage lines, paired into a Strike
+    // once both are known.
+    unpaired_attack: Option<f64>,
+    unpaired_damage: Option<(f64, Vec<DamageRoll>)>,
+}
+
+impl CombatStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn pair_strike(&mut self) {
+        if let (Some(attack_bonus), Some((damage, rolls))) =
+            (self.unpaired_attack, self.unpaired_damage.clone())
+        {
+            // These Strikes have no name.
+            self.strikes.push(StrikeStats {
+                name: String::new(),
+                attack_bonus,
+                damage,
+                rolls,
+                agile: false,
+                critical_traits: Vec::new(),
+            });
+            self.unpaired_attack = None;
+            self.unpaired_damage = None;
+        }
+    }
+
+    pub fn add_strike_attack(&mut self, attack_bonus: f64) {
+        self.unpaired_attack = Some(attack_bonus);
+        self.pair_strike();
+    }
+
+    pub fn add_strike_damage(&mut self, damage: f64, rolls: Vec<DamageRoll>) {
+        self.unpaired_damage = Some((damage, rolls));
+        self.pair_strike();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strikes.is_empty() && self.dcs.is_empty() && self.area_damage.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrikeReport {
+    pub name: String,
+    pub attack_bonus: f64,
+    pub chances: Chances,
+    // Average damage of a critical hit, if deadly or fatal change it.
+    pub critical_damage: Option<f64>,
+    // Two Strikes, the second with the multiple attack penalty.
+    pub damage_per_round: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DcReport {
+    pub label: String,
+    pub dc: f64,
+    // The chances of the PC's saving throw.
+    pub chances: Chances,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CombatReport {
+    pub pc: PcBenchmark,
+    pub strikes: Vec<StrikeReport>,
+    pub dcs: Vec<DcReport>,
+    // Expected damage of each area ability against a basic save with the highest DC.
+    pub area_damage: Vec<(f64, f64)>,
+    pub damage_per_round: f64,
+}
+
+impl CombatReport {
+    // Rounds of the best damage per round to reduce a typical PC to 0 HP.
+    pub fn rounds_to_down(&self) -> Option<f64> {
+        if self.damage_per_round > 0.0 {
+            Some(self.pc.hp / self.damage_per_round)
+        } else {
+            None
+        }
+    }
+}
+
+fn expected_strike_damage(strike: &StrikeStats, attack_bonus: f64, ac: f64) -> f64 {
+    let chances = check_chances(attack_bonus, ac);
+
+    strike.damage * chances.success + strike.critical_damage() * chances.critical_success
+}
+
+fn expected_basic_save_damage(damage: f64, save: f64, dc: f64) -> f64 {
+    let chances = check_chances(save, dc);
+
+    damage * (0.5 * chances.success + chances.failure + 2.0 * chances.critical_failure)
+}
+
+// Two Strikes, the second with the multiple attack penalty.
+fn strike_damage_per_round(strike: &StrikeStats, ac: f64) -> f64 {
+    let penalty = strike.multiple_attack_penalty();
+
+    expected_strike_damage(strike, strike.attack_bonus, ac)
+        + expected_strike_damage(strike, strike.attack_bonus - penalty, ac)
+}
+
+fn expected_area_damage<F: Fn(f64, f64) -> f64>(
+    stats: &CombatStats,
+    expected: F,
+) -> Vec<(f64, f64)> {
+    let best_dc = stats.dcs.iter().map(|d| d.dc).reduce(f64::max);
+
+    match best_dc {
+        Some(dc) => stats
+            .area_damage
+            .iter()
+            .map(|d| (*d, expected(*d, dc)))
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+fn best_damage_per_round(strikes: &[f64], area_damage: &[(f64, f64)]) -> f64 {
+    strikes
+        .iter()
+        .copied()
+        .chain(area_damage.iter().map(|(_, expected)| *expected))
+        .fold(0.0, f64::max)
+}
+
+pub fn combat_report(stats: &CombatStats, level: i32) -> CombatReport {
+    let pc = pc_benchmark(level);
+
+    let strikes: Vec<StrikeReport> = stats
+        .strikes
+        .iter()
+        .map(|s| StrikeReport {
+            name: s.name.clone(),
+            attack_bonus: s.attack_bonus,
+            chances: check_chances(s.attack_bonus, pc.ac),
+            critical_damage: s.reported_critical_damage(),
+            damage_per_round: strike_damage_per_round(s, pc.ac),
+        })
+        .collect();
+
+    let dcs: Vec<DcReport> = stats
+        .dcs
+        .iter()
+        .map(|d| DcReport {
+            label: d.label.clone(),
+            dc: d.dc,
+            chances: check_chances(pc.save, d.dc),
+        })
+        .collect();
+
+    let area_damage = expected_area_damage(stats, |damage, dc| {
+        expected_basic_save_damage(damage, pc.save, dc)
+    });
+
+    let strike_damage: Vec<f64> = strikes.iter().map(|s| s.damage_per_round).collect();
+    let damage_per_round = best_damage_per_round(&strike_damage, &area_damage);
+
+    CombatReport {
+        pc,
+        strikes,
+        dcs,
+        area_damage,
+        damage_per_round,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PcStrikeReport {
+    pub name: String,
+    pub attack_bonus: f64,
+    pub chances: Chances,
+    // Fraction of the PC's HP removed by an average hit.
+    pub hp_per_hit: f64,
+    // Average damage of a critical hit, if deadly or fatal change it.
+    pub critical_damage: Option<f64>,
+    pub damage_per_round: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PcDcReport {
+    pub label: String,
+    pub dc: f64,
+    // The chances of each of the PC's saving throws, since the stat block
+    // doesn't say which one a DC targets, and of Perception, which some
+    // abilities like illusions and Feints target.
+    pub fortitude: Chances,
+    pub reflex: Chances,
+    pub will: Chances,
+    pub perception: Chances,
+}
+
+// Combat math against a specific PC from a party file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PcReport {
+    pub pc: Pc,
+    pub strikes: Vec<PcStrikeReport>,
+    pub dcs: Vec<PcDcReport>,
+    // Expected damage of each area ability against a basic Reflex save with
+    // the highest DC.
+    pub area_damage: Vec<(f64, f64)>,
+    pub damage_per_round: f64,
+}
+
+impl PcReport {
+    // Rounds of the best damage per round to reduce the PC to 0 HP.
+    pub fn rounds_to_down(&self) -> Option<f64> {
+        if self.damage_per_round > 0.0 {
+            Some(self.pc.hp / self.damage_per_round)
+        } else {
+            None
+        }
+    }
+}
+
+pub fn pc_report(stats: &CombatStats, pc: &Pc) -> PcReport {
+    let strikes: Vec<PcStrikeReport> = stats
+        .strikes
+        .iter()
+        .map(|s| PcStrikeReport {
+            name: s.name.clone(),
+            attack_bonus: s.attack_bonus,
+            chances: check_chances(s.attack_bonus, pc.ac),
+            hp_per_hit: if pc.hp > 0.0 { s.damage / pc.hp } else { 1.0 },
+            critical_damage: s.reported_critical_damage(),
+            damage_per_round: strike_damage_per_round(s, pc.ac),
+        })
+        .collect();
+
+    let dcs: Vec<PcDcReport> = stats
+        .dcs
+        .iter()
+        .map(|d| PcDcReport {
+            label: d.label.clone(),
+            dc: d.dc,
+            fortitude: check_chances(pc.fortitude, d.dc),
+            reflex: check_chances(pc.reflex, d.dc),
+            will: check_chances(pc.will, d.dc),
+            perception: check_chances(pc.perception, d.dc),
+        })
+        .collect();
+
+    let area_damage = expected_area_damage(stats, |damage, dc| {
+        expected_basic_save_damage(damage, pc.reflex, dc)
+    });
+
+    let strike_damage: Vec<f64> = strikes.iter().map(|s| s.damage_per_round).collect();
+    let damage_per_round = best_damage_per_round(&strike_damage, &area_damage);
+
+    PcReport {
+        pc: pc.clone(),
+        strikes,
+        dcs,
+        area_damage,
+        damage_per_round,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::float_eq;
+
+    fn assert_chances(chances: Chances, expected: [f64; 4]) {
+        assert!(float_eq(chances.critical_failure, expected[0]));
+        assert!(float_eq(chances.failure, expected[1]));
+        assert!(float_eq(chances.success, expected[2]));
+        assert!(float_eq(chances.critical_success, expected[3]));
+    }
+
+    #[test]
+    fn validate_pc_benchmarks() {
+        let benchmarks = pc_benchmarks();
+        assert_eq!(benchmarks.len(), (MAX_PC_LEVEL - MIN_PC_LEVEL + 1) as usize);
+
+        for pair in benchmarks.windows(2) {
+            for (previous, next) in pair[0].iter().zip(&pair[1]) {
+                assert!(previous < next);
+            }
+        }
+    }
+
+    #[test]
+    fn clamp_pc_level() {
+        assert_eq!(pc_benchmark(-1), pc_benchmark(1));
+        assert_eq!(pc_benchmark(25), pc_benchmark(20));
+        assert_eq!(pc_benchmark(25).level, 20);
+        assert!(float_eq(pc_benchmark(1).ac, 18.0));
+        assert!(float_eq(pc_benchmark(20).hp, 288.0));
+    }
+
+    #[test]
+    fn compute_check_chances() {
+        // Need 10 or more: 1 is a critical failure, 20 a critical success.
+        assert_chances(check_chances(10.0, 20.0), [0.05, 0.4, 0.5, 0.05]);
+
+        // Need 1 or more: only 1 fails, and 11 or more critically succeeds.
+        assert_chances(check_chances(19.0, 20.0), [0.0, 0.05, 0.45, 0.5]);
+
+        // Need 21 or more: 20 succeeds, and 11 or less critically fails.
+        assert_chances(check_chances(0.0, 21.0), [0.55, 0.4, 0.05, 0.0]);
+
+        // Hopeless: even a 20 only fails.
+        assert_chances(check_chances(0.0, 40.0), [0.95, 0.05, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn pair_strike_lines() {
+        let mut stats = CombatStats::new();
+        assert!(stats.is_empty());
+
+        stats.add_strike_attack(15.0);
+        assert!(stats.strikes.is_empty());
+
+        stats.add_strike_damage(20.0, Vec::new());
+        assert_eq!(stats.strikes.len(), 1);
+        assert!(float_eq(stats.strikes[0].attack_bonus, 15.0));
+        assert!(float_eq(stats.strikes[0].damage, 20.0));
+    }
+
+    #[test]
+    fn compute_combat_report() {
+        let mut stats = CombatStats::new();
+        stats.strikes.push(StrikeStats {
+            name: String::from("jaws"),
+            attack_bonus: 16.0,
+            damage: 20.0,
+            rolls: Vec::new(),
+            agile: false,
+            critical_traits: Vec::new(),
+        });
+        stats.dcs.push(DcStats {
+            label: String::from("spell-DC"),
+            dc: 22.0,
+        });
+        stats.area_damage.push(20.0);
+
+        // PC level 5: AC 23, save +12, 68 HP.
+        let report = combat_report(&stats, 5);
+        assert_eq!(report.pc.level, 5);
+
+        // Needs 7 to hit and 17 to crit, then 12 to hit and 22 to crit.
+        let strike = &report.strikes[0];
+        assert_chances(strike.chances, [0.05, 0.25, 0.5, 0.2]);
+        assert!(float_eq(strike.damage_per_round, 20.0 * 0.9 + 20.0 * 0.5));
+
+        // The PC needs 10 to save, 20 to critically succeed.
+        let dc = &report.dcs[0];
+        assert_chances(dc.chances, [0.05, 0.4, 0.5, 0.05]);
+        assert!(float_eq(report.area_damage[0].1, 20.0 * (0.25 + 0.4 + 0.1)));
+
+        assert!(float_eq(report.damage_per_round, 28.0));
+        assert!(float_eq(report.rounds_to_down().unwrap(), 68.0 / 28.0));
+
+        // Agile Strikes have a smaller multiple attack penalty.
+        stats.strikes[0].agile = true;
+        let report = combat_report(&stats, 5);
+        assert!(float_eq(
+            report.strikes[0].damage_per_round,
+            20.0 * 0.9 + 20.0 * 0.55
+        ));
+
+        // Without a DC, area damage can't be estimated.
+        let mut stats = CombatStats::new();
+        stats.area_damage.push(20.0);
+        let report = combat_report(&stats, 5);
+        assert!(report.area_damage.is_empty());
+        assert_eq!(report.rounds_to_down(), None);
+    }
+
+    #[test]
+    fn critical_traits_change_critical_damage() {
+        let mut jaws = StrikeStats {
+            name: String::from("jaws"),
+            attack_bonus: 16.0,
+            damage: 20.0,
+            rolls: vec![crate::damage::parse_damage_roll("2d10+9").unwrap()],
+            agile: false,
+            critical_traits: Vec::new(),
+        };
+        assert!(float_eq(jaws.critical_damage(), 40.0));
+
+        let mut stats = CombatStats::new();
+        stats.strikes.push(jaws.clone());
+        assert_eq!(combat_report(&stats, 5).strikes[0].critical_damage, None);
+
+        // Deadly dice aren't doubled.
+        jaws.critical_traits = vec![CriticalTrait::Deadly(crate::damage::Dice {
+            count: 1,
+            size: 10,
+        })];
+        assert!(float_eq(jaws.critical_damage(), 45.5));
+
+        // Critical hits are 20% of the first Strikes and 5% of the second.
+        stats.strikes[0] = jaws.clone();
+        let strike = &combat_report(&stats, 5).strikes[0];
+        assert!(float_eq(strike.critical_damage.unwrap(), 45.5));
+        assert!(float_eq(strike.damage_per_round, 20.0 * 0.9 + 45.5 * 0.25));
+
+        // Fatal makes the weapon dice d12s, plus one more d12.
+        jaws.critical_traits = vec![CriticalTrait::Fatal(12)];
+        assert!(float_eq(jaws.critical_damage(), 2.0 * 22.0 + 6.5));
+
+        // Without dice, fatal only adds its extra die.
+        jaws.rolls = Vec::new();
+        assert!(float_eq(jaws.critical_damage(), 46.5));
+    }
+
+    #[test]
+    fn compute_pc_report() {
+        let mut stats = CombatStats::new();
+        stats.strikes.push(StrikeStats {
+            name: String::from("jaws"),
+            attack_bonus: 16.0,
+            damage: 20.0,
+            rolls: Vec::new(),
+            agile: false,
+            critical_traits: Vec::new(),
+        });
+        stats.dcs.push(DcStats {
+            label: String::from("spell-DC"),
+            dc: 22.0,
+        });
+        stats.area_damage.push(20.0);
+
+        let pc = Pc {
+            name: String::from("Valeros"),
+            level: 5,
+            ac: 23.0,
+            fortitude: 14.0,
+            reflex: 12.0,
+            will: 7.0,
+            perception: 11.0,
+            hp: 80.0,
+        };
+        let report = pc_report(&stats, &pc);
+
+        let strike = &report.strikes[0];
+        assert_chances(strike.chances, [0.05, 0.25, 0.5, 0.2]);
+        assert!(float_eq(strike.hp_per_hit, 0.25));
+        assert!(float_eq(strike.damage_per_round, 28.0));
+
+        // Each save and Perception is reported, and area damage uses Reflex.
+        let dc = &report.dcs[0];
+        assert_chances(dc.fortitude, [0.05, 0.3, 0.5, 0.15]);
+        assert_chances(dc.reflex, [0.05, 0.4, 0.5, 0.05]);
+        assert_chances(dc.will, [0.25, 0.45, 0.25, 0.05]);
+        assert_chances(dc.perception, [0.05, 0.45, 0.45, 0.05]);
+        assert!(float_eq(report.area_damage[0].1, 20.0 * (0.25 + 0.4 + 0.1)));
+
+        assert!(float_eq(report.damage_per_round, 28.0));
+        assert!(float_eq(report.rounds_to_down().unwrap(), 80.0 / 28.0));
+    }
+}
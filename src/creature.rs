@@ -0,0 +1,138 @@
+use crate::logging::{self, LogLevel};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CreatureHeader {
+    pub name: String,
+    pub level: i32,
+}
+
+impl CreatureHeader {
+    pub fn new(name: &str, level: i32) -> Self {
+        Self {
+            name: String::from(name),
+            level,
+        }
+    }
+}
+
+pub fn is_creature_header(line: &str) -> bool {
+    line.trim_start().starts_with('[')
+}
+
+fn log_invalid_header(line: &str) {
+    logging::log(
+        LogLevel::Error,
+        format!("invalid creature header: {}", line.trim()),
+    );
+    logging::log(LogLevel::Info, "usage: [creature <name> level <level>]");
+}
+
+// Parse a section header of the form [creature <name> level <level>].
+// The name may contain spaces and keeps its original case.
+pub fn parse_creature_header(line: &str) -> Option<CreatureHeader> {
+    let inner = line
+        .trim()
+        .strip_prefix('[')
+        .and_then(|l| l.strip_suffix(']'))
+        .map(|l| l.trim());
+
+    let inner = match inner {
+        Some(i) => i,
+        None => {
+            log_invalid_header(line);
+            return None;
+        }
+    };
+
+    let (keyword, rest) = match inner.split_once(' ') {
+        Some(p) => p,
+        None => {
+            log_invalid_header(line);
+            return None;
+        }
+    };
+
+    let (rest, level) = match rest.trim().rsplit_once(' ') {
+        Some(p) => p,
+        None => {
+            log_invalid_header(line);
+            return None;
+        }
+    };
+
+    let (name, level_keyword) = match rest.trim().rsplit_once(' ') {
+        Some(p) => p,
+        None => {
+            log_invalid_header(line);
+            return None;
+        }
+    };
+
+    let name = name.trim();
+
+    if !keyword.eq_ignore_ascii_case("creature")
+        || !level_keyword.eq_ignore_ascii_case("level")
+        || name.is_empty()
+    {
+        log_invalid_header(line);
+        return None;
+    }
+
+    let level: i32 = match level.parse() {
+        Ok(l) => l,
+        Err(_) => {
+            logging::log(
+                LogLevel::Error,
+                format!("creature level is not a valid integer: {}", level),
+            );
+            return None;
+        }
+    };
+
+    Some(CreatureHeader::new(name, level))
+}
+
+pub fn format_creature_header(name: &str, level: i32) -> String {
+    format!("[creature {} level {}]", name, level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accept_valid_creature_header() {
+        let header = parse_creature_header("[creature Goblin Warrior level -1]").unwrap();
+        assert_eq!(header, CreatureHeader::new("Goblin Warrior", -1));
+
+        let header = parse_creature_header("  [creature Ogre level 3]  ").unwrap();
+        assert_eq!(header, CreatureHeader::new("Ogre", 3));
+
+        let header = parse_creature_header("[CREATURE Young Red Dragon LEVEL 10]").unwrap();
+        assert_eq!(header, CreatureHeader::new("Young Red Dragon", 10));
+
+        let header = parse_creature_header("[ creature   Ghoul   level 1 ]").unwrap();
+        assert_eq!(header, CreatureHeader::new("Ghoul", 1));
+    }
+
+    #[test]
+    fn reject_invalid_creature_header() {
+        assert_eq!(parse_creature_header("[]"), None);
+        assert_eq!(parse_creature_header("[creature]"), None);
+        assert_eq!(parse_creature_header("[creature Ogre]"), None);
+        assert_eq!(parse_creature_header("[creature level 3]"), None);
+        assert_eq!(parse_creature_header("[creature Ogre level]"), None);
+        assert_eq!(parse_creature_header("[creature Ogre level x]"), None);
+        assert_eq!(parse_creature_header("[creature Ogre 3]"), None);
+        assert_eq!(parse_creature_header("[monster Ogre level 3]"), None);
+        assert_eq!(parse_creature_header("[creature Ogre level 3"), None);
+        assert_eq!(parse_creature_header("creature Ogre level 3]"), None);
+    }
+
+    #[test]
+    fn header_round_trip() {
+        let line = format_creature_header("Goblin Warrior", 4);
+        let header = parse_creature_header(&line).unwrap();
+        assert_eq!(header, CreatureHeader::new("Goblin Warrior", 4));
+    }
+}
@@ -1,11 +1,92 @@
+use crate::damage_types;
+use crate::diagnostic::{self, Diagnostic, Span};
 use crate::logging::{self, LogLevel};
 use crate::tables::Proficiency;
 use std::cmp::Ordering;
+use std::fmt;
+
+// A number of dice of one size, such as 2d6.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Dice {
+    pub count: i32,
+    pub size: i32,
+}
+
+impl Dice {
+    pub fn average_value(&self) -> f64 {
+        f64::from(self.count) * f64::from(self.size + 1) / 2.0
+    }
+}
+
+// The dice and flat modifier of a damage expression, kept for rolling it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DamageRoll {
+    pub dice: Vec<Dice>,
+    pub modifier: f64,
+}
+
+impl DamageRoll {
+    pub fn average_value(&self) -> f64 {
+        self.dice.iter().map(|d| d.average_value()).sum::<f64>() + self.modifier
+    }
+}
+
+impl fmt::Display for DamageRoll {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts: Vec<String> = self
+            .dice
+            .iter()
+            .map(|d| format!("{}d{}", d.count, d.size))
+            .collect();
+
+        if self.modifier != 0.0 || parts.is_empty() {
+            parts.push(format!("{}", self.modifier));
+        }
+
+        write!(f, "{}", parts.join("+"))
+    }
+}
+
+// Extra damage that only applies some of the time, which the damage tables
+// don't include.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DamageCondition {
+    // Precision damage, like a sneak attack.
+    Precision,
+    // Damage written "on a critical hit".
+    CriticalHit,
+    // Damage against creatures in a condition, with the words that say so,
+    // like "to off-guard creatures" or "against off-guard creatures".
+    Against(String),
+}
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct DamageComponent {
     pub average_value: f64,
     pub damage_type: String,
+    // The expression as written. Scaled components only have an average,
+    // except conditional ones, which keep their dice.
+    pub roll: Option<DamageRoll>,
+    pub condition: Option<DamageCondition>,
+}
+
+impl DamageComponent {
+    pub fn is_conditional(&self) -> bool {
+        self.condition.is_some()
+    }
+
+    // The damage type with its condition, as written after the damage.
+    pub fn description(&self) -> String {
+        match &self.condition {
+            Some(DamageCondition::CriticalHit) => {
+                format!("{} {}", self.damage_type, CRITICAL_HIT)
+            }
+            Some(DamageCondition::Against(condition)) => {
+                format!("{} {}", self.damage_type, condition)
+            }
+            Some(DamageCondition::Precision) | None => self.damage_type.clone(),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -20,15 +101,29 @@ impl Damage {
         }
     }
 
+    // Conditional components aren't part of the damage the tables benchmark.
+    pub fn is_baseline(&self, component: &DamageComponent) -> bool {
+        !component.is_conditional()
+    }
+
+    // The average of the baseline components, which is looked up in the tables.
     pub fn total_average_value(&self) -> f64 {
         let mut total: f64 = 0.0;
 
-        for component in &self.components {
+        for component in self.components.iter().filter(|c| self.is_baseline(c)) {
             total += component.average_value;
         }
 
         total
     }
+
+    pub fn conditional_average_value(&self) -> f64 {
+        self.components
+            .iter()
+            .filter(|c| !self.is_baseline(c))
+            .map(|c| c.average_value)
+            .sum()
+    }
 }
 
 impl Default for Damage {
@@ -37,133 +132,299 @@ impl Default for Damage {
     }
 }
 
-fn parse_dice_expression(dice: &str) -> Option<f64> {
-    let parts: Vec<&str> = dice.split('d').collect();
+// Warn about unknown damage types, suggesting a known type for likely typos.
+// Unknown types are still accepted as they are.
+fn check_damage_type(damage_type: &str) {
+    if damage_types::is_known(damage_type) {
+        return;
+    }
+
+    warn_unknown_type(damage_type, damage_types::suggest(damage_type));
+}
+
+// Like check_damage_type, for the type of a resistance or weakness, which may
+// also be a material like "cold iron" or a broad type like "physical".
+pub fn check_resistance_type(resistance: &str) {
+    if damage_types::is_known_resistance(resistance) {
+        return;
+    }
+
+    warn_unknown_type(resistance, damage_types::suggest_resistance(resistance));
+}
+
+fn warn_unknown_type(damage_type: &str, suggestion: Option<&str>) {
+    let message = match suggestion {
+        Some(suggestion) => format!(
+            "unknown damage type: {}, did you mean {}?",
+            damage_type, suggestion
+        ),
+        None => format!("unknown damage type: {}", damage_type),
+    };
+
+    logging::log(LogLevel::Warning, message);
+}
+
+const DICE_HELP: &str = "dice are written as <count>d<size>, such as 2d6";
+
+// The trimmed text and its offset, given the text's own offset.
+fn trimmed_at(text: &str, start: usize) -> (&str, usize) {
+    (text.trim(), start + text.len() - text.trim_start().len())
+}
+
+// Parsers that take the offset of their text in the line, so errors can point
+// at the offending part of the line.
+fn parse_dice_at(dice: &str, start: usize) -> Result<Dice, Diagnostic> {
+    let parts: Vec<&str> = dice.split(['d', 'D']).collect();
 
     if parts.len() != 2 {
-        logging::log(
-            LogLevel::Error,
+        return Err(Diagnostic::new(
             format!("invalid dice expression: {}", dice),
-        );
-        return None;
+            Span::at(start, dice),
+        )
+        .with_help(DICE_HELP));
     }
 
     let num_dice: i32 = match parts[0].parse() {
         Ok(n) => n,
         Err(_) => {
-            logging::log(
-                LogLevel::Error,
+            return Err(Diagnostic::new(
                 format!("number of dice is not a valid integer: {}", parts[0]),
-            );
-            return None;
+                Span::at(start, parts[0]),
+            )
+            .with_help(DICE_HELP));
         }
     };
-    let num_dice: f64 = num_dice.into();
-
-    let avg_die_damage: f64 = match parts[1] {
-        "4" => 2.5,
-        "6" => 3.5,
-        "8" => 4.5,
-        "10" => 5.5,
-        "12" => 6.5,
+
+    let die_size: i32 = match parts[1] {
+        "4" => 4,
+        "6" => 6,
+        "8" => 8,
+        "10" => 10,
+        "12" => 12,
         _ => {
-            logging::log(LogLevel::Error, format!("unknown die size: {}", parts[1]));
-            return None;
+            return Err(Diagnostic::new(
+                format!("unknown die size: {}", parts[1]),
+                Span::at(start + parts[0].len() + 1, parts[1]),
+            )
+            .with_help("die sizes are d4, d6, d8, d10 and d12"));
         }
     };
 
-    Some(num_dice * avg_die_damage)
+    Ok(Dice {
+        count: num_dice,
+        size: die_size,
+    })
 }
 
-fn parse_flat_modifier(modifier: &str) -> Option<f64> {
-    let parsed = match modifier.parse() {
+fn parse_flat_modifier_at(modifier: &str, start: usize) -> Result<f64, Diagnostic> {
+    let parsed: f64 = match modifier.parse() {
         Ok(m) => m,
         Err(_) => {
-            logging::log(
-                LogLevel::Error,
+            return Err(Diagnostic::new(
                 format!("flat modifier is not a valid integer: {}", modifier),
-            );
-            return None;
+                Span::at(start, modifier),
+            )
+            .with_help("a flat modifier is a whole number, such as the 8 in 2d6+8"));
         }
     };
 
     // Reject negative modifiers entirely for now, our parser splits on "+"
     // and we would have to input 1d4 + -1 for negative flat modifiers.
     if parsed < 0.0 {
-        return None;
+        return Err(Diagnostic::new(
+            format!("negative flat modifiers are not supported: {}", modifier),
+            Span::at(start, modifier),
+        ));
     }
 
-    Some(parsed)
+    Ok(parsed)
 }
 
-pub fn parse_damage_expression(expression: &str) -> Option<f64> {
-    let parts: Vec<&str> = expression.split('+').collect();
-
-    let mut total_avg_damage = 0.0;
+fn parse_damage_roll_at(expression: &str, start: usize) -> Result<DamageRoll, Diagnostic> {
+    let mut roll = DamageRoll::default();
+    let mut offset = start;
 
-    for part in parts {
-        let part = part.trim();
+    for part in expression.split('+') {
+        let (trimmed, part_start) = trimmed_at(part, offset);
 
-        let avg_damage = if part.contains('d') {
-            parse_dice_expression(part)
+        if trimmed.contains(['d', 'D']) {
+            roll.dice.push(parse_dice_at(trimmed, part_start)?);
         } else {
-            parse_flat_modifier(part)
-        };
+            roll.modifier += parse_flat_modifier_at(trimmed, part_start)?;
+        }
+
+        offset += part.len() + 1;
+    }
+
+    Ok(roll)
+}
+
+// Reject damage types that are really several components without "plus"
+// between them, like "fire 1d6 cold" or "fire cold".
+fn check_damage_type_words(damage_type: &str, start: usize) -> Result<(), Diagnostic> {
+    let (trimmed, start) = trimmed_at(damage_type, start);
+    let words: Vec<String> = trimmed
+        .split_whitespace()
+        .map(|w| w.to_lowercase())
+        .collect();
+
+    let has_damage = trimmed.contains(|c: char| c.is_ascii_digit() || c == '+');
+    let is_several_types = words.len() > 1 && words.iter().all(|w| damage_types::is_known(w));
+
+    if has_damage || is_several_types {
+        return Err(Diagnostic::new(
+            format!("invalid damage type: {}", trimmed),
+            Span::at(start, trimmed),
+        )
+        .with_help("join damage components with plus, such as 2d6 fire plus 1d6 cold"));
+    }
+
+    Ok(())
+}
+
+// The length of the damage roll at the start of a component. The roll goes on
+// while tokens start with a digit or '+', or follow a token that ends with '+',
+// so "2d6 + 4 persistent fire" splits after the 4.
+fn damage_roll_length(component: &str) -> usize {
+    let mut length = 0;
+    let mut continues = false;
 
-        let avg_damage = match avg_damage {
-            Some(d) => d,
-            None => return None,
+    for (i, token) in component.split_whitespace().enumerate() {
+        let is_roll = if i == 0 {
+            token.contains(|c: char| c.is_ascii_digit())
+        } else {
+            continues || token.starts_with(|c: char| c.is_ascii_digit() || c == '+')
         };
+        if !is_roll {
+            break;
+        }
 
-        total_avg_damage += avg_damage;
+        let token_start = component[length..]
+            .find(token)
+            .expect("token should be in the component")
+            + length;
+        length = token_start + token.len();
+        continues = token.ends_with('+');
     }
 
-    Some(total_avg_damage)
+    length
 }
 
-fn parse_damage_component(component: &str) -> Option<DamageComponent> {
-    let (damage, damage_type) = match component.rsplit_once(' ') {
-        Some((d, dt)) => (d, dt),
-        None => {
-            logging::log(
-                LogLevel::Error,
-                format!(
-                    "failed to parse damage component: expected <dice_expression> <damage_type>, got {}",
-                    component
-            ));
-            return None;
+const CRITICAL_HIT: &str = "on a critical hit";
+
+// Split the condition off the end of a damage type, like the "on a critical
+// hit" of "1d6 fire on a critical hit". Precision damage is always conditional.
+fn split_condition(damage_type: &str) -> (&str, Option<DamageCondition>) {
+    if let Some(t) = damage_type.strip_suffix(CRITICAL_HIT) {
+        return (t.trim_end(), Some(DamageCondition::CriticalHit));
+    }
+
+    for word in ["against", "to"] {
+        if let Some(i) = damage_type.find(&format!(" {} ", word)) {
+            let condition = String::from(&damage_type[i + 1..]);
+            return (&damage_type[..i], Some(DamageCondition::Against(condition)));
         }
-    };
+    }
 
-    let average_damage = match parse_damage_expression(damage) {
-        Some(d) => d,
-        None => return None,
-    };
+    if damage_types::find(damage_type).is_some_and(|t| t.name == "precision") {
+        return (damage_type, Some(DamageCondition::Precision));
+    }
+
+    (damage_type, None)
+}
+
+// Damage types may be several words, like "persistent fire" or "spirit (good)".
+fn parse_damage_component_at(component: &str, start: usize) -> Result<DamageComponent, Diagnostic> {
+    let roll_length = damage_roll_length(component);
+    let (damage, damage_type) = component.split_at(roll_length);
+
+    if damage.is_empty() || damage_type.trim().is_empty() {
+        return Err(Diagnostic::new(
+            format!(
+                "failed to parse damage component: expected <dice_expression> <damage_type>, got {}",
+                component
+            ),
+            Span::at(start, component),
+        )
+        .with_help("give the damage type after the damage, such as 2d6+8 fire"));
+    }
+
+    let roll = parse_damage_roll_at(damage, start)?;
+    check_damage_type_words(damage_type, start + roll_length)?;
+    let damage_type = damage_type
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .to_lowercase();
+
+    let (damage_type, condition) = split_condition(&damage_type);
+    if damage_type.is_empty() {
+        return Err(Diagnostic::new(
+            format!("missing damage type: {}", component),
+            Span::at(start, component),
+        )
+        .with_help(
+            "give the damage type before the condition, such as 1d6 fire on a critical hit",
+        ));
+    }
+    check_damage_type(damage_type);
 
     let result = DamageComponent {
-        average_value: average_damage,
+        average_value: roll.average_value(),
         damage_type: String::from(damage_type),
+        roll: Some(roll),
+        condition,
     };
 
-    Some(result)
+    Ok(result)
 }
 
-pub fn parse_damage(expression: &str) -> Option<Damage> {
-    let expression: String = expression.trim().to_lowercase();
-    let components: Vec<&str> = expression.split("plus").collect();
+// Parse a damage expression that starts at the given offset in a line.
+pub fn parse_damage_at(expression: &str, start: usize) -> Result<Damage, Diagnostic> {
+    let (expression, start) = trimmed_at(expression, start);
+
+    // Find the separators case-insensitively without changing byte offsets.
+    let lowercase = expression.to_ascii_lowercase();
+    let mut bounds: Vec<(usize, usize)> = Vec::new();
+    let mut component_start = 0;
+    for (i, separator) in lowercase.match_indices("plus") {
+        bounds.push((component_start, i));
+        component_start = i + separator.len();
+    }
+    bounds.push((component_start, expression.len()));
 
     let mut result = Damage::new();
 
-    for component in components {
-        let component = component.trim();
-        let parsed = parse_damage_component(component);
-        match parsed {
-            Some(c) => result.components.push(c),
-            None => return None,
-        }
+    for (from, to) in bounds {
+        let (component, offset) = trimmed_at(&expression[from..to], start + from);
+        result
+            .components
+            .push(parse_damage_component_at(component, offset)?);
+    }
+
+    // The tables only benchmark damage that always applies, so there has to
+    // be some to look up.
+    if result.components.iter().all(|c| c.is_conditional()) {
+        return Err(Diagnostic::new(
+            format!("damage is only conditional: {}", expression),
+            Span::at(start, expression),
+        )
+        .with_help("give the damage it adds to first, such as 1d8+4 piercing plus 1d6 precision"));
     }
 
-    Some(result)
+    Ok(result)
+}
+
+pub fn parse_damage_expression(expression: &str) -> Option<f64> {
+    parse_damage_roll(expression).map(|r| r.average_value())
+}
+
+pub fn parse_damage_roll(expression: &str) -> Option<DamageRoll> {
+    diagnostic::log_message(parse_damage_roll_at(expression, 0))
+}
+
+pub fn parse_damage(expression: &str) -> Option<Damage> {
+    diagnostic::log_message(parse_damage_at(expression, 0))
 }
 
 struct ExpressionCandidate {
@@ -272,6 +533,18 @@ mod tests {
     use super::*;
     use crate::utils::float_eq;
 
+    fn parse_dice_expression(dice: &str) -> Option<f64> {
+        parse_dice_at(dice, 0).ok().map(|d| d.average_value())
+    }
+
+    fn parse_flat_modifier(modifier: &str) -> Option<f64> {
+        parse_flat_modifier_at(modifier, 0).ok()
+    }
+
+    fn parse_damage_component(component: &str) -> Option<DamageComponent> {
+        parse_damage_component_at(component, 0).ok()
+    }
+
     #[test]
     fn accept_valid_flat_modifier() {
         assert!(float_eq(parse_flat_modifier("1").unwrap(), 1.0));
@@ -303,6 +576,10 @@ mod tests {
         assert!(float_eq(parse_dice_expression("12d8").unwrap(), 54.0));
         assert!(float_eq(parse_dice_expression("10d10").unwrap(), 55.0));
         assert!(float_eq(parse_dice_expression("1234d12").unwrap(), 8021.0));
+
+        assert!(float_eq(parse_dice_expression("2D6").unwrap(), 7.0));
+        assert!(float_eq(parse_damage_expression("2D6+4").unwrap(), 11.0));
+        assert!(float_eq(parse_damage("2D6+4 fire").unwrap().total_average_value(), 11.0));
     }
 
     #[test]
@@ -342,6 +619,25 @@ mod tests {
         assert!(float_eq(parse_damage_expression("2+1d4").unwrap(), 4.5));
     }
 
+    #[test]
+    fn accept_valid_damage_roll() {
+        let roll = parse_damage_roll("3d8 + 1d4 + 15").unwrap();
+        assert_eq!(roll.dice, vec![Dice { count: 3, size: 8 }, Dice { count: 1, size: 4 }]);
+        assert!(float_eq(roll.modifier, 15.0));
+        assert!(float_eq(roll.average_value(), 31.0));
+
+        let component = parse_damage_component("2d6+7 fire").unwrap();
+        assert_eq!(component.roll.unwrap().dice, vec![Dice { count: 2, size: 6 }]);
+    }
+
+    #[test]
+    fn format_damage_roll() {
+        assert_eq!(parse_damage_roll("3d8 + 1d4 + 15").unwrap().to_string(), "3d8+1d4+15");
+        assert_eq!(parse_damage_roll("2+1d4").unwrap().to_string(), "1d4+2");
+        assert_eq!(parse_damage_roll("2d6").unwrap().to_string(), "2d6");
+        assert_eq!(parse_damage_roll("0").unwrap().to_string(), "0");
+    }
+
     #[test]
     fn reject_invalid_damage_expression() {
         assert_eq!(parse_damage_expression("2d6+"), None);
@@ -370,8 +666,6 @@ mod tests {
         assert_eq!(parse_damage_component("1d4 + 1 fire cold"), None);
         assert_eq!(parse_damage_component("fire 1d4 + 1"), None);
         assert_eq!(parse_damage_component("fire 1d4 + 1 cold"), None);
-
-        assert_eq!(parse_damage_component("3d6 + 2 persistent fire"), None);
     }
 
     #[test]
@@ -407,10 +701,103 @@ mod tests {
     fn reject_invalid_damage() {
         assert_eq!(parse_damage("1d4 piercing plus"), None);
         assert_eq!(parse_damage("plus 1d4 piercing"), None);
-        assert_eq!(parse_damage("2d6+5 piercing plus 1d6 persistent fire"), None);
         assert_eq!(parse_damage("2d8+9 piercing + 1d10 cold"), None);
     }
 
+    #[test]
+    fn point_at_damage_errors() {
+        let span = |expression: &str| parse_damage_at(expression, 14).unwrap_err().span;
+
+        // strike-damage 2dd6 fire
+        assert_eq!(span("2dd6 fire"), Span::new(14, 18));
+        // strike-damage 2d7+4 fire
+        assert_eq!(span("2d7+4 fire"), Span::new(16, 17));
+        // strike-damage 2D7+4 fire
+        assert_eq!(span("2D7+4 fire"), Span::new(16, 17));
+        // strike-damage 2d6 + x fire
+        assert_eq!(span("2d6 + x fire"), Span::new(20, 21));
+        // strike-damage  2d6+4 fire plus 1d6
+        assert_eq!(span(" 2d6+4 fire plus 1d6"), Span::new(31, 34));
+        // strike-damage 2d6+4 fire PLUS 1d5 cold
+        assert_eq!(span("2d6+4 fire PLUS 1d5 cold"), Span::new(32, 33));
+
+        // strike-damage 2d8+9 piercing + 1d10 cold
+        assert_eq!(span("2d8+9 piercing + 1d10 cold"), Span::new(20, 40));
+
+        let error = parse_damage_at("2d6+4", 0).unwrap_err();
+        assert!(error.message.starts_with("failed to parse damage component"));
+        assert!(error.help.is_some());
+
+        // Damage types are lowercased, separators are found in any case.
+        let damage = parse_damage("2d6 Fire PLUS 1d4 Cold").unwrap();
+        assert_eq!(damage.components[0].damage_type, "fire");
+        assert_eq!(damage.components[1].damage_type, "cold");
+    }
+
+    #[test]
+    fn accept_multi_word_damage_types() {
+        let damage = parse_damage("2d6 cold iron plus 1d6 spirit (good) plus 1d4 Persistent  Fire").unwrap();
+        assert_eq!(damage.components[0].damage_type, "cold iron");
+        assert_eq!(damage.components[1].damage_type, "spirit (good)");
+        assert_eq!(damage.components[2].damage_type, "persistent fire");
+
+        let damage = parse_damage("2d6 + 4 persistent bleed").unwrap();
+        assert!(float_eq(damage.total_average_value(), 11.0));
+        assert_eq!(damage.components[0].damage_type, "persistent bleed");
+
+        let component = parse_damage_component("3d6 + 2 persistent fire").unwrap();
+        assert!(float_eq(component.average_value, 12.5));
+
+        assert_eq!(damage_roll_length("2d6+ 4 fire"), 6);
+        assert_eq!(damage_roll_length("fire"), 0);
+    }
+
+    #[test]
+    fn accept_conditional_damage() {
+        let damage = parse_damage(
+            "2d8+9 piercing plus 1d6 precision plus 1d6 fire on a critical hit plus 1d6 spirit to off-guard creatures",
+        )
+        .unwrap();
+        let conditions: Vec<Option<DamageCondition>> =
+            damage.components.iter().map(|c| c.condition.clone()).collect();
+        assert_eq!(
+            conditions,
+            vec![
+                None,
+                Some(DamageCondition::Precision),
+                Some(DamageCondition::CriticalHit),
+                Some(DamageCondition::Against(String::from("to off-guard creatures")))
+            ]
+        );
+        assert_eq!(damage.components[2].damage_type, "fire");
+        assert_eq!(damage.components[2].description(), "fire on a critical hit");
+        assert_eq!(damage.components[3].damage_type, "spirit");
+        assert_eq!(
+            damage.components[3].description(),
+            "spirit to off-guard creatures"
+        );
+
+        // Only the baseline damage is looked up in the tables.
+        assert!(float_eq(damage.total_average_value(), 18.0));
+        assert!(float_eq(damage.conditional_average_value(), 10.5));
+
+        let damage =
+            parse_damage("2d6 fire plus 1d6 cold against creatures that are off-guard").unwrap();
+        assert_eq!(
+            damage.components[1].condition,
+            Some(DamageCondition::Against(String::from(
+                "against creatures that are off-guard"
+            )))
+        );
+
+        // Damage that is all conditional has nothing to look up in the tables.
+        let error = parse_damage_at("2d6 precision", 0).err().unwrap();
+        assert_eq!(error.span, Span::new(0, 13));
+        assert!(parse_damage_at("1d6 fire on a critical hit", 0).is_err());
+
+        assert_eq!(parse_damage("1d6 on a critical hit"), None);
+    }
+
     #[test]
     fn test_damage_expression_builder_correctness() {
         let proficiencies = vec![
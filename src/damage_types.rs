@@ -0,0 +1,228 @@
+use crate::suggest;
+
+// The damage types of the remaster, and the legacy types of older books with
+// the remaster types that replaced them.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DamageCategory {
+    Physical,
+    Energy,
+    // Mental, poison, spirit, precision and untyped damage.
+    Other,
+    // Alignment damage and positive and negative damage from before the remaster.
+    Legacy,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KnownDamageType {
+    pub name: &'static str,
+    pub category: DamageCategory,
+    // The remaster type that replaces a legacy type.
+    pub remaster: Option<&'static str>,
+}
+
+const fn known(name: &'static str, category: DamageCategory) -> KnownDamageType {
+    KnownDamageType {
+        name,
+        category,
+        remaster: None,
+    }
+}
+
+const fn legacy(name: &'static str, remaster: &'static str) -> KnownDamageType {
+    KnownDamageType {
+        name,
+        category: DamageCategory::Legacy,
+        remaster: Some(remaster),
+    }
+}
+
+pub const KNOWN_DAMAGE_TYPES: &[KnownDamageType] = &[
+    known("bleed", DamageCategory::Physical),
+    known("bludgeoning", DamageCategory::Physical),
+    known("piercing", DamageCategory::Physical),
+    known("slashing", DamageCategory::Physical),
+    known("acid", DamageCategory::Energy),
+    known("cold", DamageCategory::Energy),
+    known("electricity", DamageCategory::Energy),
+    known("fire", DamageCategory::Energy),
+    known("force", DamageCategory::Energy),
+    known("sonic", DamageCategory::Energy),
+    known("vitality", DamageCategory::Energy),
+    known("void", DamageCategory::Energy),
+    known("mental", DamageCategory::Other),
+    known("poison", DamageCategory::Other),
+    known("precision", DamageCategory::Other),
+    known("spirit", DamageCategory::Other),
+    known("untyped", DamageCategory::Other),
+    legacy("chaotic", "spirit"),
+    legacy("evil", "spirit"),
+    legacy("good", "spirit"),
+    legacy("lawful", "spirit"),
+    legacy("negative", "void"),
+    legacy("positive", "vitality"),
+];
+
+// Resistances that cover more than one damage type, and so are lower than
+// resistances to a single type.
+const BROAD_RESISTANCES: &[&str] = &["all", "all damage", "physical", "energy"];
+
+// What else resistances and weaknesses may be to, besides damage types.
+const OTHER_RESISTANCES: &[&str] = &[
+    "adamantine",
+    "area damage",
+    "cold iron",
+    "critical hits",
+    "dawnsilver",
+    "holy",
+    "mithral",
+    "orichalcum",
+    "persistent damage",
+    "silver",
+    "splash damage",
+    "unholy",
+];
+
+const PERSISTENT: &str = "persistent ";
+
+pub fn names() -> Vec<&'static str> {
+    KNOWN_DAMAGE_TYPES.iter().map(|t| t.name).collect()
+}
+
+// Persistent damage has the type of the damage it persists as, such as
+// "persistent fire".
+fn split_persistent(damage_type: &str) -> (bool, &str) {
+    match damage_type.strip_prefix(PERSISTENT) {
+        Some(t) => (true, t.trim_start()),
+        None => (false, damage_type),
+    }
+}
+
+// A qualifier in parentheses, like the "(good)" of "spirit (good)", doesn't
+// change the damage type.
+pub fn find(damage_type: &str) -> Option<&'static KnownDamageType> {
+    let (_, damage_type) = split_persistent(damage_type);
+    let damage_type = match damage_type.split_once('(') {
+        Some((t, _)) => t.trim_end(),
+        None => damage_type,
+    };
+
+    KNOWN_DAMAGE_TYPES.iter().find(|t| t.name == damage_type)
+}
+
+pub fn is_known(damage_type: &str) -> bool {
+    find(damage_type).is_some()
+}
+
+pub fn is_broad(resistance: &str) -> bool {
+    BROAD_RESISTANCES.contains(&resistance)
+}
+
+pub fn is_known_resistance(resistance: &str) -> bool {
+    is_known(resistance) || is_broad(resistance) || OTHER_RESISTANCES.contains(&resistance)
+}
+
+// The known resistance or weakness that an unknown one is likely a typo of.
+pub fn suggest_resistance(resistance: &str) -> Option<&'static str> {
+    if is_known_resistance(resistance) {
+        return None;
+    }
+
+    let mut names = names();
+    names.extend(BROAD_RESISTANCES);
+    names.extend(OTHER_RESISTANCES);
+    suggest::suggest(resistance, &names)
+}
+
+// The known damage type that an unknown one is likely a typo of.
+pub fn suggest(damage_type: &str) -> Option<&'static str> {
+    if is_known(damage_type) {
+        return None;
+    }
+
+    suggest::suggest(damage_type, &names())
+}
+
+// The remaster equivalent of a legacy damage type, keeping persistent damage
+// persistent. Other damage types don't change.
+pub fn to_remaster(damage_type: &str) -> String {
+    let (persistent, base) = split_persistent(damage_type);
+
+    match find(base).and_then(|t| t.remaster) {
+        Some(remaster) if persistent => format!("{}{}", PERSISTENT, remaster),
+        Some(remaster) => String::from(remaster),
+        None => String::from(damage_type),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_known_damage_types() {
+        assert_eq!(find("fire").unwrap().category, DamageCategory::Energy);
+        assert_eq!(find("slashing").unwrap().category, DamageCategory::Physical);
+        assert_eq!(find("spirit").unwrap().category, DamageCategory::Other);
+        assert_eq!(find("negative").unwrap().category, DamageCategory::Legacy);
+        assert_eq!(find("persistent bleed").unwrap().name, "bleed");
+
+        assert!(is_known("persistent fire"));
+        assert!(is_known("spirit (good)"));
+        assert!(!is_known("vorpal"));
+        assert!(!is_known("persistent"));
+    }
+
+    #[test]
+    fn find_broad_resistances() {
+        assert!(is_broad("all"));
+        assert!(is_broad("physical"));
+        assert!(!is_broad("fire"));
+        assert!(!is_broad("cold iron"));
+    }
+
+    #[test]
+    fn find_resistances() {
+        assert!(is_known_resistance("fire"));
+        assert!(is_known_resistance("all damage"));
+        assert!(is_known_resistance("cold iron"));
+        assert!(!is_known_resistance("fier"));
+
+        assert_eq!(suggest_resistance("fier"), Some("fire"));
+        assert_eq!(suggest_resistance("cold irn"), Some("cold iron"));
+        assert_eq!(suggest_resistance("silver"), None);
+    }
+
+    #[test]
+    fn suggest_damage_types() {
+        assert_eq!(suggest("peircing"), Some("piercing"));
+        assert_eq!(suggest("fier"), Some("fire"));
+        assert_eq!(suggest("slahsing"), Some("slashing"));
+
+        assert_eq!(suggest("fire"), None);
+        assert_eq!(suggest("persistent fire"), None);
+        assert_eq!(suggest("vorpal"), None);
+    }
+
+    #[test]
+    fn convert_legacy_damage_types() {
+        assert_eq!(to_remaster("negative"), "void");
+        assert_eq!(to_remaster("positive"), "vitality");
+        assert_eq!(to_remaster("good"), "spirit");
+        assert_eq!(to_remaster("chaotic"), "spirit");
+        assert_eq!(to_remaster("persistent evil"), "persistent spirit");
+
+        assert_eq!(to_remaster("fire"), "fire");
+        assert_eq!(to_remaster("vorpal"), "vorpal");
+    }
+
+    #[test]
+    fn legacy_types_convert_to_known_types() {
+        for damage_type in KNOWN_DAMAGE_TYPES {
+            match damage_type.remaster {
+                Some(r) => assert_ne!(find(r).unwrap().category, DamageCategory::Legacy),
+                None => assert_ne!(damage_type.category, DamageCategory::Legacy),
+            }
+        }
+    }
+}
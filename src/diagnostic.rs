@@ -0,0 +1,165 @@
+use crate::logging::{self, LogLevel};
+
+// Errors in a line of input that point at the offending part of the line,
+// rendered like compiler errors:
+//
+// error: unknown die size: 7
+//   |
+//   | strike-damage 2d7 fire
+//   |                  ^
+//   = help: die sizes are d4, d6, d8, d10 and d12
+
+// A range of bytes in a line of input.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        assert!(start <= end);
+        Self { start, end }
+    }
+
+    // The span of text that starts at the given offset.
+    pub fn at(start: usize, text: &str) -> Self {
+        Self::new(start, start + text.len())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub span: Span,
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new<T: Into<String>>(message: T, span: Span) -> Self {
+        Self {
+            message: message.into(),
+            span,
+            help: None,
+        }
+    }
+
+    pub fn with_help<T: Into<String>>(mut self, help: T) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    // Move the span by the offset of the parsed text in a longer line.
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.span = Span::new(self.span.start + offset, self.span.end + offset);
+        self
+    }
+
+    // The line with a caret under the span, and the help note if any.
+    pub fn render(&self, line: &str) -> String {
+        // Lines read from the prompt still end with a newline.
+        let line = line.trim_end_matches(['\n', '\r']);
+
+        // Columns are counted in characters, so carets line up under
+        // multibyte characters too. Spans past the end point at the end.
+        let column = |byte: usize| {
+            line.get(..byte.min(line.len()))
+                .map_or(line.chars().count(), |l| l.chars().count())
+        };
+        let start = column(self.span.start);
+        let width = column(self.span.end).saturating_sub(start).max(1);
+
+        let mut rendered = format!(
+            "  |\n  | {}\n  | {}{}",
+            line,
+            " ".repeat(start),
+            "^".repeat(width)
+        );
+
+        if let Some(help) = &self.help {
+            rendered += &format!("\n  = help: {}", help);
+        }
+
+        rendered
+    }
+
+    pub fn log(&self, line: &str) {
+        logging::log(LogLevel::Error, self.message.as_str());
+        logging::log(LogLevel::Info, self.render(line));
+    }
+}
+
+// Log only the message of a failed result, for callers that don't have the
+// whole line to point into.
+pub fn log_message<T>(result: Result<T, Diagnostic>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(diagnostic) => {
+            logging::log(LogLevel::Error, diagnostic.message);
+            None
+        }
+    }
+}
+
+// The byte offset of a slice within the string it was taken from.
+pub fn offset_in(outer: &str, inner: &str) -> Option<usize> {
+    let outer_start = outer.as_ptr() as usize;
+    let inner_start = inner.as_ptr() as usize;
+
+    if inner_start >= outer_start && inner_start + inner.len() <= outer_start + outer.len() {
+        Some(inner_start - outer_start)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_caret_under_span() {
+        let line = "strike-damage 2dd6 fire";
+        let diagnostic = Diagnostic::new("invalid dice expression: 2dd6", Span::new(14, 18))
+            .with_help("dice are written as <count>d<size>, such as 2d6");
+
+        assert_eq!(
+            diagnostic.render(line),
+            "  |\n  | strike-damage 2dd6 fire\n  |               ^^^^\n  = help: dice are written as <count>d<size>, such as 2d6"
+        );
+    }
+
+    #[test]
+    fn render_empty_and_out_of_range_spans() {
+        // An empty span still gets a caret.
+        let diagnostic = Diagnostic::new("missing value", Span::new(3, 3));
+        assert!(diagnostic.render("ac ").ends_with("\n  |    ^"));
+
+        let diagnostic = Diagnostic::new("missing value", Span::new(10, 12));
+        assert!(diagnostic.render("ac").ends_with("\n  |   ^"));
+
+        // The newline of a prompt line isn't rendered.
+        let diagnostic = Diagnostic::new("missing value", Span::new(0, 2));
+        assert_eq!(diagnostic.render("ac\n"), "  |\n  | ac\n  | ^^");
+
+        // Columns are counted in characters.
+        let diagnostic = Diagnostic::new("invalid damage", Span::new(8, 12));
+        let rendered = diagnostic.render("1d4 hé fire");
+        assert!(rendered.ends_with(&format!("\n  | {}^^^^", " ".repeat(7))));
+    }
+
+    #[test]
+    fn offset_spans() {
+        let diagnostic = Diagnostic::new("bad", Span::new(1, 2)).offset(10);
+        assert_eq!(diagnostic.span, Span::new(11, 12));
+        assert_eq!(Span::at(3, "abc"), Span::new(3, 6));
+    }
+
+    #[test]
+    fn find_slice_offset() {
+        let line = "strike jaws +19 2d12 piercing";
+        assert_eq!(offset_in(line, &line[16..]), Some(16));
+        assert_eq!(offset_in(line, line), Some(0));
+        assert_eq!(offset_in(line, "2d12"), None);
+    }
+}
@@ -1,8 +1,13 @@
 use crate::logging::{self, LogLevel};
 
+// The level range of the official tables.
 pub const MIN_LEVEL: i32 = -1;
 pub const MAX_LEVEL: i32 = 24;
 
+// The level range we support by synthesising table rows outside the official range.
+pub const MIN_EXTENDED_LEVEL: i32 = -2;
+pub const MAX_EXTENDED_LEVEL: i32 = 30;
+
 pub fn num_levels() -> usize {
     (MAX_LEVEL - MIN_LEVEL + 1)
         .try_into()
@@ -15,9 +20,25 @@ pub struct Levels {
     pub target: i32,
 }
 
+pub fn is_official(level: i32) -> bool {
+    (MIN_LEVEL..=MAX_LEVEL).contains(&level)
+}
+
+fn warn_if_synthesised(kind: &str, level: i32) {
+    if !is_official(level) {
+        logging::log(
+            LogLevel::Warning,
+            format!(
+                "{} level {} is outside the official range [{}, {}]: values are synthesised",
+                kind, level, MIN_LEVEL, MAX_LEVEL
+            ),
+        );
+    }
+}
+
 impl Levels {
     fn is_valid(level: i32) -> bool {
-        (MIN_LEVEL..=MAX_LEVEL).contains(&level)
+        (MIN_EXTENDED_LEVEL..=MAX_EXTENDED_LEVEL).contains(&level)
     }
 
     pub fn new(current: i32, target: i32) -> Option<Self> {
@@ -26,7 +47,7 @@ impl Levels {
                 LogLevel::Error,
                 format!(
                     "current level {} out of range [{}, {}]",
-                    current, MIN_LEVEL, MAX_LEVEL
+                    current, MIN_EXTENDED_LEVEL, MAX_EXTENDED_LEVEL
                 ),
             );
             return None;
@@ -37,12 +58,19 @@ impl Levels {
                 LogLevel::Error,
                 format!(
                     "target level {} out of range [{}, {}]",
-                    target, MIN_LEVEL, MAX_LEVEL
+                    target, MIN_EXTENDED_LEVEL, MAX_EXTENDED_LEVEL
                 ),
             );
             return None;
         }
 
+        warn_if_synthesised("current", current);
+        warn_if_synthesised("target", target);
+
         Some(Self { current, target })
     }
+
+    pub fn is_synthesised(&self) -> bool {
+        !is_official(self.current) || !is_official(self.target)
+    }
 }
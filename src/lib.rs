@@ -1,8 +1,22 @@
 pub mod color;
+pub mod combat;
+pub mod creature;
 pub mod damage;
+pub mod damage_types;
+pub mod diagnostic;
 pub mod levels;
 pub mod logging;
+pub mod party;
+pub mod rng;
+pub mod roadmap;
+pub mod rounding;
 pub mod scaling;
+pub mod simulation;
+pub mod spells;
+pub mod stat_block;
 pub mod statistic;
+pub mod strike;
+pub mod suggest;
+pub mod table_file;
 pub mod tables;
 pub mod utils;
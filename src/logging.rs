@@ -1,4 +1,5 @@
 use crate::color::{self, Color};
+use std::cell::RefCell;
 use std::fmt;
 
 pub enum LogLevel {
@@ -7,9 +8,38 @@ pub enum LogLevel {
     Error,
 }
 
+thread_local! {
+    // Warnings logged while capturing, instead of being printed.
+    static CAPTURED: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+// Run f, returning the warnings it logged instead of printing them, so they
+// can be printed next to what they are about. Other messages are still printed.
+pub fn capture<T>(f: impl FnOnce() -> T) -> (T, Vec<String>) {
+    let outer = CAPTURED.with(|c| c.replace(Some(Vec::new())));
+    let result = f();
+    let captured = CAPTURED.with(|c| c.replace(outer)).unwrap_or_default();
+
+    (result, captured)
+}
+
 // Accept both String and &str so we can call this with string literals and format!().
 #[rustfmt::skip]
 pub fn log<T: Into<String> + fmt::Display>(level: LogLevel, msg: T) {
+    if let LogLevel::Warning = level {
+        let captured = CAPTURED.with(|c| {
+            let mut captured = c.borrow_mut();
+            if let Some(warnings) = captured.as_mut() {
+                warnings.push(msg.to_string());
+            }
+            captured.is_some()
+        });
+
+        if captured {
+            return;
+        }
+    }
+
     match level {
         LogLevel::Info => eprintln!("{}", msg),
         LogLevel::Warning => {
@@ -20,3 +50,26 @@ pub fn log<T: Into<String> + fmt::Display>(level: LogLevel, msg: T) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capture_warnings() {
+        let (value, warnings) = capture(|| {
+            log(LogLevel::Warning, "first");
+            let (_, inner) = capture(|| log(LogLevel::Warning, "inner"));
+            assert_eq!(inner, vec![String::from("inner")]);
+            log(LogLevel::Info, "printed");
+            log(LogLevel::Warning, format!("second {}", 2));
+            7
+        });
+
+        assert_eq!(value, 7);
+        assert_eq!(
+            warnings,
+            vec![String::from("first"), String::from("second 2")]
+        );
+    }
+}
@@ -3,19 +3,139 @@ use std::fs;
 use std::io::{self, IsTerminal};
 use std::process;
 use stronk::color::{self, Color};
-use stronk::damage::{self, Damage};
+use stronk::combat::{self, CombatReport, CombatStats, DcStats, PcReport, StrikeStats};
+use stronk::creature;
+use stronk::damage::{self, Damage, DamageComponent, DamageRoll};
+use stronk::damage_types;
+use stronk::diagnostic::{self, Diagnostic, Span};
 use stronk::levels::Levels;
 use stronk::logging::{self, LogLevel};
-use stronk::scaling::{self, ScaleMethod, ScaleResult};
+use stronk::party::{self, Pc};
+use stronk::rng;
+use stronk::roadmap::{self, RoadmapEntry, Role, Verdict};
+use stronk::rounding::{self, RoundingPolicy};
+use stronk::scaling::{self, Extrapolation, ProficiencyShift, ScaleMethod, ScaleResult};
+use stronk::simulation;
+use stronk::spells::{self, SpellShift};
+use stronk::stat_block::{self, Part, StatBlockLine};
 use stronk::statistic::{self, SavingThrowType, SkillType, StatType, Statistic};
+use stronk::strike::{self, CriticalTrait, Strike};
+use stronk::suggest;
+use stronk::table_file;
+use stronk::tables::{Proficiency, TableSet};
+
+#[derive(Clone, Default)]
+struct Options {
+    pub rounding: RoundingPolicy,
+    pub snap: bool,
+    // Extrapolation strategies per statistic, or for all statistics if None.
+    // Later entries override earlier ones.
+    pub extrapolation: Vec<(Option<StatType>, Extrapolation)>,
+    pub tables: TableSet,
+    // Proficiency shifts per statistic, or for all statistics if None.
+    // Later entries override earlier ones.
+    pub shifts: Vec<(Option<StatType>, ProficiencyShift)>,
+    // Print a combat math report after each creature of an input file.
+    pub report: bool,
+    // PCs to report against instead of typical PCs.
+    pub party: Vec<Pc>,
+    // Number of simulated fights per Strike in the report, and their seed.
+    pub simulate: Option<u32>,
+    pub seed: Option<u64>,
+    // Print an input file as a side-by-side comparison of original and scaled values.
+    pub compare: bool,
+    // Convert legacy damage types to their remaster equivalents while scaling.
+    pub remaster: bool,
+}
+
+impl Options {
+    fn shift_for(&self, kind: &StatType) -> Option<ProficiencyShift> {
+        self.shifts
+            .iter()
+            .rev()
+            .find(|(k, _)| k.as_ref().is_none_or(|k| k.covers(kind)))
+            .map(|(_, s)| *s)
+    }
+
+    fn extrapolation_for(&self, kind: &StatType) -> Extrapolation {
+        self.extrapolation
+            .iter()
+            .rev()
+            .find(|(k, _)| k.as_ref().is_none_or(|k| k.covers(kind)))
+            .map(|(_, e)| *e)
+            .unwrap_or_else(|| default_extrapolation(kind))
+    }
+}
+
+// The extrapolation of each group of statistics without an --extrapolation
+// rule, as listed in the usage.
+const DEFAULT_EXTRAPOLATION: [(&str, Extrapolation); 3] = [
+    ("bonuses, DCs and AC", Extrapolation::Additive),
+    ("hp, resistances and weaknesses", Extrapolation::Additive),
+    ("damage", Extrapolation::Additive),
+];
+
+fn default_extrapolation(kind: &StatType) -> Extrapolation {
+    let group = match kind {
+        StatType::HitPoints
+        | StatType::Resistance
+        | StatType::BroadResistance
+        | StatType::Weakness => 1,
+        StatType::StrikeDamage | StatType::UnlimitedAreaDamage | StatType::LimitedAreaDamage => 2,
+        _ => 0,
+    };
+
+    DEFAULT_EXTRAPOLATION[group].1
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Command {
+    Scale,
+    Generate(Role),
+    Check(Role),
+}
 
 struct Arguments {
+    pub command: Command,
+    // For generate and check, both levels are the creature's level.
     pub levels: Levels,
     pub input_file: Option<String>,
+    pub relative: bool,
+    pub options: Options,
 }
 
 fn print_usage() {
-    eprintln!("usage: stronk <current_level> <target_level> [input_file]");
+    eprintln!("usage: stronk [options] <current_level> <target_level> [input_file]");
+    eprintln!("       stronk [options] generate <role> <level>");
+    eprintln!("       stronk [options] check <role> <level> <input_file>");
+    eprintln!();
+    eprintln!("options:");
+    eprintln!("  --relative            scale each creature in the input file by target_level - current_level");
+    eprintln!(
+        "  --rounding <policy>   floor (default), nearest, ceil or floor-unless-within[=<epsilon>]"
+    );
+    eprintln!("  --snap                snap each value to the nearest proficiency column");
+    eprintln!("  --extrapolation <statistic|all>=<strategy>");
+    eprintln!("                        additive, proportional or column-gap, by default:");
+    for (statistics, extrapolation) in DEFAULT_EXTRAPOLATION {
+        eprintln!(
+            "                          {:<32}{}",
+            statistics, extrapolation
+        );
+    }
+    eprintln!("  --tables <file>       override built-in tables with the tables in file");
+    eprintln!("  --shift <statistic|all>=<shift>");
+    eprintln!("                        move by columns (+1, -1) or to a proficiency (high)");
+    eprintln!("  --remaster            convert legacy damage types like negative to void");
+    eprintln!("  --compare             print original and scaled values side by side");
+    eprintln!("  --report              report each creature's combat math against typical PCs");
+    eprintln!("  --party <file>        report combat math against the PCs in file");
+    eprintln!("  --simulate <fights>   add a dice simulation of each Strike to the report");
+    eprintln!("  --seed <seed>         seed of the simulation (default 1)");
+    eprintln!();
+    eprintln!(
+        "roles: brute, magical-striker, skill-paragon, skirmisher, sniper, soldier, spellcaster"
+    );
 }
 
 fn print_version() {
@@ -38,24 +158,175 @@ fn parse_level(level: &str) -> Option<i32> {
     }
 }
 
+fn parse_count(option: &str, value: &str) -> Option<u32> {
+    match value.parse() {
+        Ok(x) if x > 0 => Some(x),
+        _ => {
+            logging::log(
+                LogLevel::Error,
+                format!("{} is not a positive integer: {}", option, value),
+            );
+            None
+        }
+    }
+}
+
+// Any u64 is a valid seed, including 0.
+fn parse_seed(option: &str, value: &str) -> Option<u64> {
+    match value.parse() {
+        Ok(x) => Some(x),
+        Err(_) => {
+            logging::log(
+                LogLevel::Error,
+                format!("{} is not a non-negative integer: {}", option, value),
+            );
+            None
+        }
+    }
+}
+
+fn option_value<'a>(option: &str, value: Option<&&'a str>) -> Option<&'a str> {
+    match value {
+        Some(v) => Some(v),
+        None => {
+            logging::log(
+                LogLevel::Error,
+                format!("missing value for option {}", option),
+            );
+            print_usage();
+            None
+        }
+    }
+}
+
+// Parse a <statistic|all>=<value> rule. The statistic is None for all statistics.
+fn parse_stat_rule<'a>(option: &str, rule: &'a str) -> Option<(Option<StatType>, &'a str)> {
+    let (kind, value) = match rule.split_once('=') {
+        Some(r) => r,
+        None => {
+            logging::log(
+                LogLevel::Error,
+                format!("invalid {} rule: {}", option, rule),
+            );
+            logging::log(LogLevel::Info, "usage: <statistic|all>=<value>");
+            return None;
+        }
+    };
+
+    let kind = kind.trim().to_lowercase();
+    let kind = if kind == "all" {
+        None
+    } else if let Some(lore) = lore_kind(&kind) {
+        Some(lore)
+    } else if let Some(name) = kind.strip_prefix("skill ") {
+        Some(StatType::Skill(SkillType::Custom(normalize_label(name))))
+    } else {
+        Some(parse_stat_kind(&kind)?)
+    };
+
+    Some((kind, value))
+}
+
+// A Lore skill with its subject, like "lore warfare" or "warfare lore", so a
+// rule can apply to a single Lore. A rule for "lore" applies to all of them.
+fn lore_kind(kind: &str) -> Option<StatType> {
+    let subject = stat_block::lore_subject(kind).or_else(|| kind.strip_prefix("lore "))?;
+    Some(StatType::Skill(SkillType::Lore(normalize_label(subject))))
+}
+
 fn parse_args(args: &Vec<&str>) -> Option<Arguments> {
-    if !(args.len() == 3 || args.len() == 4) {
+    let mut positional: Vec<&str> = Vec::new();
+    let mut relative = false;
+    let mut options = Options::default();
+
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        match *arg {
+            "--relative" => relative = true,
+            "--snap" => options.snap = true,
+            "--report" => options.report = true,
+            "--compare" => options.compare = true,
+            "--remaster" => options.remaster = true,
+            "--extrapolation" => {
+                let value = option_value(arg, iter.next())?;
+                let (kind, strategy) = parse_stat_rule(arg, value)?;
+                let strategy = scaling::parse_extrapolation(strategy)?;
+                options.extrapolation.push((kind, strategy));
+            }
+            "--shift" => {
+                let value = option_value(arg, iter.next())?;
+                let (kind, shift) = parse_stat_rule(arg, value)?;
+                let shift = scaling::parse_proficiency_shift(shift)?;
+                options.shifts.push((kind, shift));
+            }
+            "--tables" => {
+                let value = option_value(arg, iter.next())?;
+                options.tables = table_file::load_table_file(value)?;
+            }
+            "--rounding" => {
+                let value = option_value(arg, iter.next())?;
+                options.rounding = rounding::parse_rounding_policy(value)?;
+            }
+            "--party" => {
+                let value = option_value(arg, iter.next())?;
+                options.party = party::load_party_file(value)?;
+                options.report = true;
+            }
+            "--simulate" => {
+                let value = option_value(arg, iter.next())?;
+                options.simulate = Some(parse_count(arg, value)?);
+                options.report = true;
+            }
+            "--seed" => {
+                let value = option_value(arg, iter.next())?;
+                options.seed = Some(parse_seed(arg, value)?);
+            }
+            _ if arg.starts_with("--") => {
+                logging::log(LogLevel::Error, format!("unknown option: {}", arg));
+                print_usage();
+                return None;
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    // generate and check take a single level, which is used as both the current
+    // and the target level.
+    let (command, positional) = match positional.first() {
+        Some(&"generate") if positional.len() == 3 => (
+            Command::Generate(roadmap::parse_role(positional[1])?),
+            vec![positional[2], positional[2]],
+        ),
+        Some(&"check") if positional.len() == 4 => (
+            Command::Check(roadmap::parse_role(positional[1])?),
+            vec![positional[2], positional[2], positional[3]],
+        ),
+        Some(&"generate") | Some(&"check") => {
+            print_usage();
+            return None;
+        }
+        _ => (Command::Scale, positional),
+    };
+
+    if !(positional.len() == 2 || positional.len() == 3) {
         print_usage();
         return None;
     }
 
-    let current_level = parse_level(args[1]);
+    let current_level = parse_level(positional[0]);
     if let Some(c) = current_level {
-        let target_level = parse_level(args[2]);
+        let target_level = parse_level(positional[1]);
         if let Some(t) = target_level {
             if let Some(levels) = Levels::new(c, t) {
-                let input_file = if args.len() == 4 {
-                    Some(String::from(args[3]))
-                } else {
-                    None
-                };
-
-                return Some(Arguments { levels, input_file });
+                let input_file = positional.get(2).map(|f| String::from(*f));
+
+                return Some(Arguments {
+                    command,
+                    levels,
+                    input_file,
+                    relative,
+                    options,
+                });
             }
         }
     }
@@ -63,103 +334,425 @@ fn parse_args(args: &Vec<&str>) -> Option<Arguments> {
     None
 }
 
+// Every name and alias a statistic can be written as, with its kind.
+const STAT_KINDS: &[(&str, StatType)] = &[
+    ("perception", StatType::Perception),
+    ("per", StatType::Perception),
+    ("acrobatics", StatType::Skill(SkillType::Acrobatics)),
+    ("arcana", StatType::Skill(SkillType::Arcana)),
+    ("athletics", StatType::Skill(SkillType::Athletics)),
+    ("crafting", StatType::Skill(SkillType::Crafting)),
+    ("deception", StatType::Skill(SkillType::Deception)),
+    ("diplomacy", StatType::Skill(SkillType::Diplomacy)),
+    ("intimidation", StatType::Skill(SkillType::Intimidation)),
+    ("lore", StatType::Skill(SkillType::Lore(String::new()))),
+    ("medicine", StatType::Skill(SkillType::Medicine)),
+    ("nature", StatType::Skill(SkillType::Nature)),
+    ("occultism", StatType::Skill(SkillType::Occultism)),
+    ("performance", StatType::Skill(SkillType::Performance)),
+    ("religion", StatType::Skill(SkillType::Religion)),
+    ("society", StatType::Skill(SkillType::Society)),
+    ("stealth", StatType::Skill(SkillType::Stealth)),
+    ("survival", StatType::Skill(SkillType::Survival)),
+    ("thievery", StatType::Skill(SkillType::Thievery)),
+    ("skill", StatType::Skill(SkillType::Custom(String::new()))),
+    ("ac", StatType::ArmorClass),
+    (
+        "fortitude",
+        StatType::SavingThrow(SavingThrowType::Fortitude),
+    ),
+    ("fort", StatType::SavingThrow(SavingThrowType::Fortitude)),
+    ("reflex", StatType::SavingThrow(SavingThrowType::Reflex)),
+    ("ref", StatType::SavingThrow(SavingThrowType::Reflex)),
+    ("will", StatType::SavingThrow(SavingThrowType::Will)),
+    ("hp", StatType::HitPoints),
+    ("resistance", StatType::Resistance),
+    ("broad-resistance", StatType::BroadResistance),
+    ("weakness", StatType::Weakness),
+    ("strike-attack", StatType::StrikeAttackBonus),
+    ("att", StatType::StrikeAttackBonus),
+    ("strike-damage", StatType::StrikeDamage),
+    ("dmg", StatType::StrikeDamage),
+    ("spell-dc", StatType::SpellDC),
+    ("ability-dc", StatType::AbilityDC),
+    ("spell-attack", StatType::SpellAttackBonus),
+    ("unlimited-area-damage", StatType::UnlimitedAreaDamage),
+    ("limited-area-damage", StatType::LimitedAreaDamage),
+];
+
+fn stat_kind_names() -> impl Iterator<Item = &'static str> {
+    STAT_KINDS.iter().map(|(name, _)| *name)
+}
+
+// Words that can start a line of input besides statistics.
+const LINE_KEYWORDS: &[&str] = &["strike", "spells"];
+
+fn line_start_words() -> Vec<&'static str> {
+    stat_kind_names()
+        .chain(LINE_KEYWORDS.iter().copied())
+        .collect()
+}
+
 fn parse_stat_kind(kind: &str) -> Option<StatType> {
-    match kind {
-        "perception" | "per" => Some(StatType::Perception),
-        "acrobatics" => Some(StatType::Skill(SkillType::Acrobatics)),
-        "arcana" => Some(StatType::Skill(SkillType::Arcana)),
-        "athletics" => Some(StatType::Skill(SkillType::Athletics)),
-        "crafting" => Some(StatType::Skill(SkillType::Crafting)),
-        "deception" => Some(StatType::Skill(SkillType::Deception)),
-        "diplomacy" => Some(StatType::Skill(SkillType::Diplomacy)),
-        "intimidation" => Some(StatType::Skill(SkillType::Intimidation)),
-        "lore" => Some(StatType::Skill(SkillType::Lore)),
-        "medicine" => Some(StatType::Skill(SkillType::Medicine)),
-        "nature" => Some(StatType::Skill(SkillType::Nature)),
-        "occultism" => Some(StatType::Skill(SkillType::Occultism)),
-        "performance" => Some(StatType::Skill(SkillType::Performance)),
-        "religion" => Some(StatType::Skill(SkillType::Religion)),
-        "society" => Some(StatType::Skill(SkillType::Society)),
-        "stealth" => Some(StatType::Skill(SkillType::Stealth)),
-        "survival" => Some(StatType::Skill(SkillType::Survival)),
-        "thievery" => Some(StatType::Skill(SkillType::Thievery)),
-        "ac" => Some(StatType::ArmorClass),
-        "fortitude" | "fort" => Some(StatType::SavingThrow(SavingThrowType::Fortitude)),
-        "reflex" | "ref" => Some(StatType::SavingThrow(SavingThrowType::Reflex)),
-        "will" => Some(StatType::SavingThrow(SavingThrowType::Will)),
-        "hp" => Some(StatType::HitPoints),
-        "resistance" => Some(StatType::Resistance),
-        "weakness" => Some(StatType::Weakness),
-        "strike-attack" | "att" => Some(StatType::StrikeAttackBonus),
-        "strike-damage" | "dmg" => Some(StatType::StrikeDamage),
-        "spell-dc" => Some(StatType::SpellDC),
-        "spell-attack" => Some(StatType::SpellAttackBonus),
-        "unlimited-area-damage" => Some(StatType::UnlimitedAreaDamage),
-        "limited-area-damage" => Some(StatType::LimitedAreaDamage),
-        _ => {
-            logging::log(LogLevel::Error, format!("unknown statistic: {}", kind));
-            None
+    let parsed = stat_kind(kind);
+    if parsed.is_none() {
+        logging::log(LogLevel::Error, format!("unknown statistic: {}", kind));
+        if let Some(suggestion) = suggest::suggest(kind, &stat_kind_names().collect::<Vec<_>>()) {
+            logging::log(LogLevel::Info, format!("did you mean {}?", suggestion));
         }
     }
+
+    parsed
+}
+
+fn stat_kind(kind: &str) -> Option<StatType> {
+    STAT_KINDS
+        .iter()
+        .find(|(name, _)| *name == kind)
+        .map(|(_, stat_kind)| stat_kind.clone())
 }
 
 // Ensure the input is an integer. However, we still want to store it as f64.
-fn parse_stat_value_integer(kind: StatType, value: &str) -> Option<f64> {
+fn parse_stat_value_integer(kind: &StatType, value: &str, start: usize) -> Result<f64, Diagnostic> {
     let parsed: Result<i32, _> = value.parse();
     match parsed {
-        Ok(p) => Some(p.into()),
-        Err(_) => {
-            logging::log(
-                LogLevel::Error,
-                format!("{} value is not a valid integer: {}", kind, value),
-            );
+        Ok(p) => Ok(p.into()),
+        Err(_) => Err(Diagnostic::new(
+            format!("{} value is not a valid integer: {}", kind, value),
+            Span::at(start, value),
+        )
+        .with_help(format!(
+            "{} values are whole numbers, such as 15 or +11",
+            kind
+        ))),
+    }
+}
+
+// Scale a statistic according to the options. Also returns notes about how
+// the options affected the result, to be printed after the scale details.
+// In a comparison, the warnings about the statistic are notes too, so they
+// are printed in its row.
+fn scale_with_options(
+    levels: Levels,
+    options: &Options,
+    stat: Statistic,
+    inline_shift: Option<ProficiencyShift>,
+) -> (ScaleResult, Vec<String>) {
+    if !options.compare {
+        return scale_with_notes(levels, options, stat, inline_shift);
+    }
+
+    let ((result, mut notes), warnings) =
+        logging::capture(|| scale_with_notes(levels, options, stat, inline_shift));
+    notes.extend(warnings.into_iter().map(|w| format!("warning: {}", w)));
+
+    (result, notes)
+}
+
+fn scale_with_notes(
+    levels: Levels,
+    options: &Options,
+    stat: Statistic,
+    inline_shift: Option<ProficiencyShift>,
+) -> (ScaleResult, Vec<String>) {
+    let mut notes: Vec<String> = Vec::new();
+    let current = stat.value;
+    let extrapolation = options.extrapolation_for(&stat.kind);
+    let table = options.tables.get_for_statistic(&stat.kind);
+    let shift = inline_shift
+        .or(options.shift_for(&stat.kind))
+        .or(default_shift(&stat.kind));
+
+    let result = if options.snap {
+        let snap = scaling::snap_statistic(levels, stat, &table, extrapolation);
+        notes.push(format!(
+            "moved {:+.2} from {:.2}",
+            snap.movement(),
+            snap.faithful.stat.value
+        ));
+
+        snap.snapped
+    } else {
+        scaling::scale_statistic_with(levels, stat, &table, extrapolation)
+    };
+
+    let shift = match shift {
+        Some(s) => s,
+        None => return (result, notes),
+    };
+
+    let mut shifted = scaling::shift_proficiency(levels, result, &table, shift);
+    let clamped = if scaling::hold_level_direction(levels, current, &mut shifted) {
+        format!(", held at {}", current)
+    } else if shifted.clamped {
+        String::from(", clamped to table")
+    } else {
+        String::new()
+    };
+    let shift = match shift {
+        ProficiencyShift::Columns(_) => format!("{}", shift),
+        ProficiencyShift::To(_) => format!("to {}", shift),
+    };
+    notes.push(format!(
+        "shifted {} from {:.2}{}",
+        shift, shifted.unshifted.stat.value, clamped
+    ));
+
+    (shifted.shifted, notes)
+}
+
+// GM Core recommends lower values for resistance to all damage or to a broad
+// category than for a single damage type, so they use the low end of the table.
+fn default_shift(kind: &StatType) -> Option<ProficiencyShift> {
+    match kind {
+        StatType::BroadResistance => Some(ProficiencyShift::To(Proficiency::Low)),
+        _ => None,
+    }
+}
+
+// Split an inline proficiency shift such as ">high" or ">+1" off the end of a prompt value.
+// Returns the value and the shift including the '>'.
+fn split_inline_shift(value: &str) -> (&str, Option<&str>) {
+    match value.rsplit_once(' ') {
+        Some((v, shift)) if shift.starts_with('>') => (v.trim(), Some(shift)),
+        _ => (value, None),
+    }
+}
+
+// Split an exception in parentheses off the end of a resistance or weakness,
+// like the "(except silver)" of "physical 5 (except silver)".
+fn split_qualifier(value: &str) -> (&str, Option<&str>) {
+    match value.find('(') {
+        Some(i) if value.ends_with(')') => (value[..i].trim_end(), Some(&value[i..])),
+        _ => (value, None),
+    }
+}
+
+const SUBJECT_FIRST_LORE: &str = " lore ";
+
+// The end of the subject of a Lore skill written with the subject first, like
+// "warfare lore +12". The subject may be a skill name, like "nature lore".
+fn subject_first_lore(prompt: &str) -> Option<usize> {
+    let first = prompt.split_whitespace().next()?.to_lowercase();
+    if matches!(stat_kind(&first), Some(k) if !matches!(k, StatType::Skill(_))) {
+        return None;
+    }
+
+    prompt.to_ascii_lowercase().find(SUBJECT_FIRST_LORE)
+}
+
+fn normalize_label(label: &str) -> String {
+    label
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+enum PromptValue {
+    Integer(f64),
+    Damage(Damage),
+}
+
+struct Prompt {
+    kind: StatType,
+    // Tells apart several statistics of the same kind, like ability DCs or
+    // the damage types of resistances.
+    label: Option<String>,
+    // An exception of a resistance or weakness, like "(except silver)".
+    qualifier: Option<String>,
+    value: PromptValue,
+    shift: Option<ProficiencyShift>,
+}
+
+impl Prompt {
+    // The statistic with its label and qualifier, like "resistance physical (except silver)".
+    fn name(&self) -> String {
+        self.name_with_label(self.label.as_deref())
+    }
+
+    // The name as printed for the scaled creature, with the label converted
+    // like the scaled output.
+    fn scaled_name(&self, options: &Options) -> String {
+        let label = scaled_label(options, &self.kind, self.label.as_deref());
+        self.name_with_label(label.as_deref())
+    }
+
+    fn name_with_label(&self, label: Option<&str>) -> String {
+        let mut name = self.kind.to_string();
+
+        for part in [label, self.qualifier.as_deref()].into_iter().flatten() {
+            name += &format!(" {}", part);
+        }
+
+        name
+    }
+
+    // The statistic to scale. Resistance to all damage or to a broad category
+    // scales as a broad resistance, but keeps the keyword it was written with.
+    fn stat_kind(&self) -> StatType {
+        match (&self.kind, &self.label) {
+            (StatType::Resistance, Some(l)) if damage_types::is_broad(l) => {
+                StatType::BroadResistance
+            }
+            _ => self.kind.clone(),
+        }
+    }
+
+    fn total_value(&self) -> f64 {
+        match &self.value {
+            PromptValue::Integer(v) => *v,
+            PromptValue::Damage(d) => d.total_average_value(),
+        }
+    }
+}
+
+fn parse_prompt(line: &str) -> Option<Prompt> {
+    match parse_prompt_at(line) {
+        Ok(p) => Some(p),
+        Err(d) => {
+            d.log(line);
             None
         }
     }
 }
 
-fn handle_prompt(levels: Levels, prompt: &str) -> Option<ScaleResult> {
-    // We must assign the String created by to_lowercase() to its own variable,
-    // or it becomes a temporary that is then dropped too early.
-    let prompt: String = prompt.trim().to_lowercase();
-    let prompt = match prompt.split_once(' ') {
-        Some(p) => p,
+// Parse a prompt, keeping track of where each part is in the line so errors
+// can point at it.
+fn parse_prompt_at(line: &str) -> Result<Prompt, Diagnostic> {
+    let prompt = line.trim();
+    let prompt_start = line.len() - line.trim_start().len();
+
+    let (kind, rest, rest_start) = match subject_first_lore(prompt) {
+        Some(i) => {
+            let rest_start = i + SUBJECT_FIRST_LORE.len();
+            (
+                StatType::Skill(SkillType::Lore(normalize_label(&prompt[..i]))),
+                &prompt[rest_start..],
+                prompt_start + rest_start,
+            )
+        }
         None => {
-            logging::log(LogLevel::Error, "invalid prompt");
-            logging::log(LogLevel::Info, "usage: <statistic> <current_value>");
-            return None;
+            let (prompt_kind, rest) = match prompt.split_once(' ') {
+                Some(p) => p,
+                None => {
+                    return Err(
+                        Diagnostic::new("invalid prompt", Span::at(prompt_start, prompt))
+                            .with_help("usage: <statistic> <current_value>"),
+                    );
+                }
+            };
+
+            let kind = match stat_kind(&prompt_kind.to_lowercase()) {
+                Some(k) => k,
+                None => {
+                    let help = match suggest::suggest(prompt_kind, &line_start_words()) {
+                        Some(s) => format!("did you mean {}?", s),
+                        None => String::from("usage: <statistic> <current_value>"),
+                    };
+
+                    return Err(Diagnostic::new(
+                        format!("invalid prompt: unknown statistic: {}", prompt_kind),
+                        Span::at(prompt_start, prompt_kind),
+                    )
+                    .with_help(help));
+                }
+            };
+
+            (kind, rest, prompt_start + prompt_kind.len() + 1)
         }
     };
-    let prompt_kind = prompt.0.trim();
-    let prompt_value = prompt.1.trim();
+    let value_start = rest_start + rest.len() - rest.trim_start().len();
+
+    // The shift is split off the end, so the value still starts at value_start.
+    let (prompt_value, shift_text) = split_inline_shift(rest.trim());
+    let shift = match shift_text {
+        Some(text) => match scaling::proficiency_shift(&text[1..]) {
+            Some(s) => Some(s),
+            None => {
+                let start = diagnostic::offset_in(line, text).unwrap_or(value_start);
+                return Err(Diagnostic::new(
+                    format!("invalid proficiency shift: {}", &text[1..]),
+                    Span::at(start, text),
+                )
+                .with_help(scaling::PROFICIENCY_SHIFT_HELP));
+            }
+        },
+        None => None,
+    };
 
-    let stat_kind = match parse_stat_kind(prompt_kind) {
-        Some(k) => k,
-        None => {
-            logging::log(
-                LogLevel::Error,
-                format!("invalid prompt: unknown statistic: {}", prompt_kind),
-            );
-            return None;
+    // Resistances and weaknesses may end with an exception in parentheses.
+    let (prompt_value, qualifier) = match kind {
+        StatType::Resistance | StatType::BroadResistance | StatType::Weakness => {
+            split_qualifier(prompt_value)
         }
+        _ => (prompt_value, None),
     };
 
-    match stat_kind {
-        StatType::StrikeDamage | StatType::UnlimitedAreaDamage | StatType::LimitedAreaDamage => {
-            let damage = match damage::parse_damage(prompt_value) {
-                Some(d) => d,
-                None => {
-                    return None;
+    let (kind, label, prompt_value, value_start) = match kind {
+        StatType::AbilityDC | StatType::Skill(SkillType::Custom(_)) => {
+            match prompt_value.rsplit_once(' ') {
+                // The label of a DC is kept as written, like the names of
+                // afflictions in stat blocks. A custom skill keeps its name
+                // in the skill type, like the subject of a Lore.
+                Some((l, v)) if !l.trim().is_empty() => {
+                    let (kind, label) = match kind {
+                        StatType::AbilityDC => (kind, Some(String::from(l.trim()))),
+                        _ => (StatType::Skill(SkillType::Custom(normalize_label(l))), None),
+                    };
+                    (kind, label, v, value_start + l.len() + 1)
                 }
-            };
-
-            let total_damage = Statistic::new(stat_kind, damage.total_average_value());
-            let scale_result = scaling::scale_statistic(levels, total_damage);
-            let scaled_damage = scaling::scale_damage_components(&damage, scale_result.stat.value);
+                _ => {
+                    let usage = match kind {
+                        StatType::AbilityDC => "usage: ability-dc <label> <value>",
+                        _ => "usage: skill <name> <value>",
+                    };
+                    return Err(Diagnostic::new(
+                        format!("invalid prompt: {} needs a label", kind),
+                        Span::at(value_start, prompt_value),
+                    )
+                    .with_help(usage));
+                }
+            }
+        }
+        // The subject is optional, and may be in parentheses like "lore (warfare) +12".
+        StatType::Skill(SkillType::Lore(ref s)) if s.is_empty() => {
+            match prompt_value.rsplit_once(' ') {
+                Some((l, v)) => {
+                    let l_trimmed = l.trim();
+                    let subject = l_trimmed
+                        .strip_prefix('(')
+                        .and_then(|l| l.strip_suffix(')'))
+                        .unwrap_or(l_trimmed);
+                    (
+                        StatType::Skill(SkillType::Lore(normalize_label(subject))),
+                        None,
+                        v,
+                        value_start + l.len() + 1,
+                    )
+                }
+                None => (kind, None, prompt_value, value_start),
+            }
+        }
+        // The damage type is optional, and may be several words like "cold iron".
+        StatType::Resistance | StatType::BroadResistance | StatType::Weakness => {
+            match prompt_value.rsplit_once(' ') {
+                Some((l, v)) => (kind, Some(normalize_label(l)), v, value_start + l.len() + 1),
+                None => (kind, None, prompt_value, value_start),
+            }
+        }
+        _ => (kind, None, prompt_value, value_start),
+    };
 
-            print_damage(&scaled_damage, scale_result);
+    if let (StatType::Resistance | StatType::BroadResistance | StatType::Weakness, Some(l)) =
+        (&kind, &label)
+    {
+        damage::check_resistance_type(l);
+    }
 
-            Some(scale_result)
+    let value = match kind {
+        StatType::StrikeDamage | StatType::UnlimitedAreaDamage | StatType::LimitedAreaDamage => {
+            PromptValue::Damage(damage::parse_damage_at(prompt_value, value_start)?)
         }
         StatType::Perception
         | StatType::Skill(_)
@@ -167,26 +760,240 @@ fn handle_prompt(levels: Levels, prompt: &str) -> Option<ScaleResult> {
         | StatType::SavingThrow(_)
         | StatType::HitPoints
         | StatType::Resistance
+        | StatType::BroadResistance
         | StatType::Weakness
         | StatType::StrikeAttackBonus
         | StatType::SpellDC
+        | StatType::AbilityDC
         | StatType::SpellAttackBonus => {
-            let stat_value = match parse_stat_value_integer(stat_kind, prompt_value) {
-                Some(s) => s,
-                None => {
-                    return None;
+            PromptValue::Integer(parse_stat_value_integer(&kind, prompt_value, value_start)?)
+        }
+    };
+
+    Ok(Prompt {
+        kind,
+        label,
+        qualifier: qualifier.map(String::from),
+        value,
+        shift,
+    })
+}
+
+// A statistic as scaled, with the notes printed after it.
+type Scaled = (ScaleResult, Vec<String>);
+
+// A line of input as it was parsed and scaled. Printing it, comparing it and
+// collecting it for the combat report all use the same results, so each
+// statistic is only scaled, and warned about, once.
+enum ScaledLine {
+    SpellList(SpellShift),
+    // One result for each value and damage part of the line.
+    StatBlock(StatBlockLine, Vec<Option<Scaled>>),
+    // The attack bonus and damage of the Strike.
+    Strike(Strike, Scaled, Scaled),
+    Prompt(Prompt, Scaled),
+}
+
+fn scale_prompt(levels: Levels, options: &Options, prompt: &Prompt) -> Scaled {
+    let stat = Statistic::new(prompt.stat_kind(), prompt.total_value());
+    scale_with_options(levels, options, stat, prompt.shift)
+}
+
+fn print_prompt(levels: Levels, prompt: &Prompt, scaled: &Scaled, options: &Options) {
+    let (scale_result, notes) = scaled;
+
+    match &prompt.value {
+        PromptValue::Damage(damage) => {
+            let scaled_damage = scale_damage(levels, damage, scale_result.stat.value, options);
+            print_damage(&scaled_damage, scale_result, options.rounding, notes);
+        }
+        PromptValue::Integer(_) => print_result(
+            &prompt.kind,
+            scale_result,
+            scaled_label(options, &prompt.kind, prompt.label.as_deref()).as_deref(),
+            prompt.qualifier.as_deref(),
+            options.rounding,
+            notes,
+        ),
+    }
+}
+
+// Spell lists keep their shape: every rank moves by the change in the top
+// spell rank, and the entries that need a human decision are flagged.
+fn scale_spell_list(levels: Levels, line: &str) -> Option<SpellShift> {
+    let list = spells::parse_spell_list(line, levels.current)?;
+    Some(spells::shift_spell_list(
+        &list,
+        levels.current,
+        levels.target,
+    ))
+}
+
+fn print_spell_shift(shift: &SpellShift) {
+    print!(
+        "{} [top rank {} -> {}]",
+        shift.list,
+        spells::format_rank(shift.old_top_rank),
+        spells::format_rank(shift.new_top_rank)
+    );
+
+    if !shift.removed.is_empty() {
+        let removed = format!("removed {}", shift.removed.join(", "));
+        print!(" ({})", color_if_terminal(&removed, Color::BrightYellow));
+    }
+
+    println!();
+}
+
+// The scaled stat block line in the layout it was pasted in. Attack bonuses
+// with the multiple attack penalty change as much as the attack bonus before them.
+fn format_scaled_stat_block_line(
+    levels: Levels,
+    options: &Options,
+    line: &StatBlockLine,
+    results: &[Option<Scaled>],
+) -> String {
+    let mut scaled_line = String::new();
+    let mut attack_change = 0.0;
+
+    // Deadly gets more dice as the weapon does.
+    let weapon_dice = match (&line.strike, stat_block_strike_results(line, results)) {
+        (Some(s), Some((_, damage))) => {
+            strike::weapon_dice(&scaled_damage_rolls(levels, &s.damage, &damage, options))
+        }
+        _ => 0,
+    };
+
+    for (part, result) in line.parts.iter().zip(results) {
+        let scaled_part = match (part, result) {
+            (Part::Value { kind, label, value }, Some((result, _))) => {
+                let scaled = options.rounding.round(result.stat.value);
+                if *kind == StatType::StrikeAttackBonus {
+                    attack_change = scaled - value;
                 }
-            };
 
-            let scaled = scaling::scale_statistic(levels, Statistic::new(stat_kind, stat_value));
+                // The type of a resistance is in the text before its value.
+                if let (Some(label), Some(scaled_label)) =
+                    (label, scaled_label(options, kind, label.as_deref()))
+                {
+                    replace_label_text(&mut scaled_line, label, &scaled_label);
+                }
+
+                stat_block::format_value(kind, scaled)
+            }
+            (Part::Damage { damage, .. }, Some((result, _))) => {
+                format_damage_text(&scaled_damage_expressions(levels, damage, result, options))
+            }
+            (Part::MultipleAttacks(values), _) => {
+                Part::MultipleAttacks(values.iter().map(|v| v + attack_change).collect())
+                    .to_string()
+            }
+            (Part::CriticalTrait(critical), _) => critical.scaled(weapon_dice).to_string(),
+            (part, _) => part.to_string(),
+        };
+
+        scaled_line += &scaled_part;
+    }
+
+    scaled_line
+}
+
+// Scale each value and damage part of a stat block line.
+fn scale_stat_block_parts(
+    levels: Levels,
+    options: &Options,
+    line: &StatBlockLine,
+) -> Vec<Option<Scaled>> {
+    let scale = |kind: StatType, value: f64| {
+        scale_with_options(levels, options, Statistic::new(kind, value), None)
+    };
+
+    line.parts
+        .iter()
+        .map(|part| match part {
+            Part::Value { kind, value, .. } => Some(scale(kind.clone(), *value)),
+            Part::Damage { kind, damage, .. } => {
+                Some(scale(kind.clone(), damage.total_average_value()))
+            }
+            Part::Text(_) | Part::MultipleAttacks(_) | Part::CriticalTrait(_) => None,
+        })
+        .collect()
+}
+
+// The scaled attack bonus and damage of the Strike of a stat block line.
+fn stat_block_strike_results(
+    line: &StatBlockLine,
+    results: &[Option<Scaled>],
+) -> Option<(ScaleResult, ScaleResult)> {
+    let find = |wanted: StatType| {
+        line.parts
+            .iter()
+            .zip(results)
+            .find_map(|(part, result)| match (part, result) {
+                (Part::Value { kind, .. } | Part::Damage { kind, .. }, Some((r, _)))
+                    if *kind == wanted =>
+                {
+                    Some(r.clone())
+                }
+                _ => None,
+            })
+    };
+
+    Some((
+        find(StatType::StrikeAttackBonus)?,
+        find(StatType::StrikeDamage)?,
+    ))
+}
+
+// Parse and scale a line of input, which is either a spell list, a stat block
+// line, a Strike or a prompt.
+fn scale_line(levels: Levels, options: &Options, line: &str) -> Option<ScaledLine> {
+    if spells::is_spell_list(line) {
+        return scale_spell_list(levels, line).map(ScaledLine::SpellList);
+    }
+
+    if stat_block::is_stat_block_line(line) {
+        let parsed = stat_block::parse_stat_block_line(line)?;
+        let results = scale_stat_block_parts(levels, options, &parsed);
+        return Some(ScaledLine::StatBlock(parsed, results));
+    }
+
+    if strike::is_strike(line) {
+        let strike = strike::parse_strike(line)?;
+        let (attack, damage) = scale_strike(levels, options, &strike);
+        return Some(ScaledLine::Strike(strike, attack, damage));
+    }
 
-            print_result(scaled);
+    let prompt = parse_prompt(line)?;
+    let scaled = scale_prompt(levels, options, &prompt);
+    Some(ScaledLine::Prompt(prompt, scaled))
+}
 
-            Some(scaled)
+// Stat block lines are printed without scale details, so they can be pasted
+// back into a stat block.
+fn print_scaled_line(levels: Levels, options: &Options, line: &ScaledLine) {
+    match line {
+        ScaledLine::SpellList(shift) => print_spell_shift(shift),
+        ScaledLine::StatBlock(parsed, results) => {
+            println!(
+                "{}",
+                format_scaled_stat_block_line(levels, options, parsed, results)
+            );
         }
+        ScaledLine::Strike(strike, attack, damage) => {
+            print_strike(levels, strike, attack, damage, options)
+        }
+        ScaledLine::Prompt(prompt, scaled) => print_prompt(levels, prompt, scaled, options),
     }
 }
 
+fn handle_line(levels: Levels, options: &Options, line: &str) -> Option<ScaledLine> {
+    let scaled = scale_line(levels, options, line)?;
+    print_scaled_line(levels, options, &scaled);
+
+    Some(scaled)
+}
+
 fn color_if_terminal(text: &str, color: Color) -> String {
     if io::stdout().is_terminal() {
         color::color_text(text, color)
@@ -195,49 +1002,102 @@ fn color_if_terminal(text: &str, color: Color) -> String {
     }
 }
 
-fn print_scale_details(result: ScaleResult) {
+fn format_scale_details(result: &ScaleResult, notes: &[String]) -> String {
     let colored_method = format!("{}", result.method);
     let color = match result.method {
         ScaleMethod::Exact => Color::Green,
         ScaleMethod::Interpolated => Color::Green,
         ScaleMethod::Extrapolated => Color::BrightYellow,
+        ScaleMethod::Snapped => Color::Green,
+        ScaleMethod::Synthesised => Color::BrightYellow,
     };
     let colored_method = color_if_terminal(&colored_method, color);
 
-    println!("[{}] [{}]", result.proficiency, colored_method);
+    let mut details = format!("[{}] [{}]", result.proficiency, colored_method);
+
+    for note in notes {
+        details += &format!(" ({})", note);
+    }
+
+    details
+}
+
+fn print_scale_details(result: &ScaleResult, notes: &[String]) {
+    println!("{}", format_scale_details(result, notes));
+}
+
+// Values that the rounding policy rounds differently than floor are highlighted,
+// so it's explicit which numbers were rounded up.
+fn rounding_color(rounding: RoundingPolicy, value: f64) -> Color {
+    if rounding.differs_from_floor(value) {
+        Color::BrightMagenta
+    } else {
+        Color::BrightCyan
+    }
+}
+
+fn format_fraction(rounding: RoundingPolicy, value: f64) -> String {
+    if rounding.differs_from_floor(value) {
+        format!("({:.2}, rounded up)", value)
+    } else {
+        format!("({:.2})", value)
+    }
 }
 
-fn print_result(result: ScaleResult) {
-    let stat_rounded = result.stat.value.floor();
+fn format_result_value(result: &ScaleResult, rounding: RoundingPolicy) -> String {
+    let stat_rounded = rounding.round(result.stat.value);
 
-    let colored_stat = if statistic::is_bonus(result.stat.kind) {
+    let colored_stat = if statistic::is_bonus(&result.stat.kind) {
         format!("+{}", stat_rounded)
     } else {
         format!("{}", stat_rounded)
     };
 
-    let colored_stat = color_if_terminal(&colored_stat, Color::BrightCyan);
-
-    print!("{} {} ", result.stat.kind, colored_stat);
+    let color = rounding_color(rounding, result.stat.value);
+    let mut value = color_if_terminal(&colored_stat, color);
 
-    if result.method != ScaleMethod::Exact {
-        print!("({:.2}) ", result.stat.value);
+    if result.method != ScaleMethod::Exact && result.method != ScaleMethod::Snapped {
+        value += &format!(" {}", format_fraction(rounding, result.stat.value));
     }
 
-    print_scale_details(result);
+    value
 }
 
-fn print_damage(damage: &Damage, result: ScaleResult) {
-    print!("{} ", result.stat.kind);
+// The kind is printed as written, which may differ from the kind it scaled as.
+fn print_result(
+    kind: &StatType,
+    result: &ScaleResult,
+    label: Option<&str>,
+    qualifier: Option<&str>,
+    rounding: RoundingPolicy,
+    notes: &[String],
+) {
+    print!("{} ", kind);
+
+    if let Some(label) = label {
+        print!("{} ", label);
+    }
 
-    for (i, component) in damage.components.iter().enumerate() {
-        #[rustfmt::skip]
-        let damage_expression = damage::build_damage_expression(component.average_value, result.proficiency);
+    print!("{} ", format_result_value(result, rounding));
 
-        let colored_damage_expression = color_if_terminal(&damage_expression, Color::BrightCyan);
-        print!(
-            "{} ({:.2}) {} ",
-            colored_damage_expression, component.average_value, component.damage_type
+    if let Some(qualifier) = qualifier {
+        print!("{} ", qualifier);
+    }
+
+    print_scale_details(result, notes);
+}
+
+fn print_damage_components(damage: &Damage, proficiency: Proficiency, rounding: RoundingPolicy) {
+    for (i, component) in damage.components.iter().enumerate() {
+        let damage_expression = damage_component_expression(component, proficiency, rounding);
+
+        let color = rounding_color(rounding, component.average_value);
+        let colored_damage_expression = color_if_terminal(&damage_expression, color);
+        print!(
+            "{} {} {} ",
+            colored_damage_expression,
+            format_fraction(rounding, component.average_value),
+            component.description()
         );
 
         let n = damage.components.len();
@@ -245,50 +1105,1331 @@ fn print_damage(damage: &Damage, result: ScaleResult) {
             print!("plus ");
         }
     }
+}
+
+fn print_damage(damage: &Damage, result: &ScaleResult, rounding: RoundingPolicy, notes: &[String]) {
+    print!("{} ", result.stat.kind);
+    print_damage_components(damage, result.proficiency, rounding);
+    print_scale_details(result, notes);
+}
+
+// The attack and damage of a strike are scaled separately, each reporting its
+// own proficiency and scaling method after the strike itself.
+fn print_strike(
+    levels: Levels,
+    strike: &Strike,
+    attack: &Scaled,
+    damage: &Scaled,
+    options: &Options,
+) {
+    let rounding = options.rounding;
+    let (attack, attack_notes) = attack;
+    let (damage_result, damage_notes) = damage;
+    let mut damage_notes = damage_notes.clone();
+    let scaled_damage = scale_damage(levels, &strike.damage, damage_result.stat.value, options);
+
+    let rolls = scaled_damage_rolls(levels, &strike.damage, damage_result, options);
+    let critical_traits = scaled_critical_traits(strike, &rolls);
+    if !critical_traits.is_empty() {
+        let critical = combat::critical_damage(damage_result.stat.value, &rolls, &critical_traits);
+        damage_notes.push(format!("critical hit {:.2}", critical));
+    }
+
+    print!(
+        "strike {} {} {}",
+        strike.name,
+        format_result_value(attack, rounding),
+        strike::format_traits(&strike::scale_traits(
+            &strike.traits,
+            strike::weapon_dice(&rolls)
+        ))
+    );
+    print_damage_components(&scaled_damage, damage_result.proficiency, rounding);
+
+    println!(
+        "attack {}, damage {}",
+        format_scale_details(attack, attack_notes),
+        format_scale_details(damage_result, &damage_notes)
+    );
+}
+
+// The deadly and fatal traits of a Strike for its scaled damage dice.
+fn scaled_critical_traits(strike: &Strike, scaled_rolls: &[DamageRoll]) -> Vec<CriticalTrait> {
+    let weapon_dice = strike::weapon_dice(scaled_rolls);
+
+    strike
+        .critical_traits()
+        .iter()
+        .map(|t| t.scaled(weapon_dice))
+        .collect()
+}
+
+fn scale_strike(levels: Levels, options: &Options, strike: &Strike) -> (Scaled, Scaled) {
+    let attack = Statistic::new(StatType::StrikeAttackBonus, strike.attack_bonus);
+    let attack = scale_with_options(levels, options, attack, None);
+
+    let damage = Statistic::new(StatType::StrikeDamage, strike.damage.total_average_value());
+    let damage = scale_with_options(levels, options, damage, None);
+
+    (attack, damage)
+}
+
+// Creatures with many resistances to single damage types, like undead and
+// golems, use the low end of the table for all of them.
+const MANY_RESISTANCES: usize = 3;
+
+// Only the keyword and label are read, so lines aren't parsed, and their
+// errors logged, before they are scaled.
+fn is_typed_resistance(line: &str) -> bool {
+    let (keyword, rest) = match line.trim().split_once(' ') {
+        Some(p) => p,
+        None => return false,
+    };
+
+    if stat_kind(&keyword.to_lowercase()) != Some(StatType::Resistance) {
+        return false;
+    }
+
+    let (value, _) = split_inline_shift(rest.trim());
+    let (value, _) = split_qualifier(value);
+    match value.rsplit_once(' ') {
+        Some((label, _)) => !damage_types::is_broad(&normalize_label(label)),
+        None => false,
+    }
+}
+
+fn count_typed_resistances(lines: &[&str]) -> usize {
+    lines
+        .iter()
+        .map(|line| {
+            if stat_block::is_stat_block_line(line) {
+                stat_block::parse_list_line(line)
+                    .iter()
+                    .filter(|p| {
+                        matches!(
+                            p,
+                            Part::Value {
+                                kind: StatType::Resistance,
+                                ..
+                            }
+                        )
+                    })
+                    .count()
+            } else {
+                usize::from(is_typed_resistance(line))
+            }
+        })
+        .sum()
+}
+
+// The options to scale a creature's lines with, up to the next creature
+// header. Shifts given on the command line still win over the low end.
+fn creature_options(options: &Options, lines: &[&str]) -> Options {
+    let mut options = options.clone();
+
+    let end = lines
+        .iter()
+        .position(|l| creature::is_creature_header(l))
+        .unwrap_or(lines.len());
+
+    if count_typed_resistances(&lines[..end]) >= MANY_RESISTANCES {
+        options.shifts.insert(
+            0,
+            (
+                Some(StatType::Resistance),
+                ProficiencyShift::To(Proficiency::Low),
+            ),
+        );
+    }
+
+    options
+}
+
+fn process_input_file(args: Arguments) -> bool {
+    let input_file = args
+        .input_file
+        .expect("input file argument should have been checked to exist");
+
+    let contents = match fs::read_to_string(input_file) {
+        Ok(c) => c,
+        Err(_) => {
+            logging::log(LogLevel::Error, "failed to read input file");
+            return false;
+        }
+    };
+
+    // Lines before the first creature header are scaled with the levels given
+    // on the command line. Each header then sets the levels for its own section.
+    let mut levels = args.levels;
+    let mut original = CombatStats::new();
+    let mut scaled = CombatStats::new();
+    let mut rows: Vec<CompareLine> = Vec::new();
+
+    let lines: Vec<&str> = contents.lines().collect();
+    let mut options = creature_options(&args.options, &lines);
+
+    for (i, line) in lines.iter().copied().enumerate() {
+        if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
+            // Comments separate the rows of the comparison table.
+            if !args.options.compare {
+                println!("{}", line);
+            } else if !line.is_empty() {
+                rows.push(CompareLine::Comment(String::from(line)));
+            }
+            continue;
+        }
+
+        if creature::is_creature_header(line) {
+            print_compare_table(&rows);
+            rows.clear();
+
+            if args.options.report {
+                print_combat_reports(levels, &args.options, &original, &scaled);
+            }
+            original = CombatStats::new();
+            scaled = CombatStats::new();
+            options = creature_options(&args.options, &lines[i + 1..]);
+
+            let header = match creature::parse_creature_header(line) {
+                Some(h) => h,
+                None => {
+                    logging::log(LogLevel::Error, "failed to process input file");
+                    return false;
+                }
+            };
+
+            let target = if args.relative {
+                header.level + (args.levels.target - args.levels.current)
+            } else {
+                args.levels.target
+            };
+
+            levels = match Levels::new(header.level, target) {
+                Some(l) => l,
+                None => {
+                    logging::log(
+                        LogLevel::Error,
+                        format!("failed to scale creature: {}", header.name),
+                    );
+                    logging::log(LogLevel::Error, "failed to process input file");
+                    return false;
+                }
+            };
+
+            println!("{}", creature::format_creature_header(&header.name, target));
+            continue;
+        }
+
+        let scaled_line = match scale_line(levels, &options, line) {
+            Some(s) => s,
+            None => {
+                logging::log(LogLevel::Error, "failed to process input file");
+                return false;
+            }
+        };
+
+        if args.options.compare {
+            rows.extend(
+                compare_line(levels, &options, &scaled_line)
+                    .into_iter()
+                    .map(CompareLine::Row),
+            );
+        } else {
+            print_scaled_line(levels, &options, &scaled_line);
+        }
+
+        if args.options.report {
+            collect_combat_stats(levels, &options, &scaled_line, &mut original, &mut scaled);
+        }
+    }
+
+    print_compare_table(&rows);
+
+    if args.options.report {
+        print_combat_reports(levels, &args.options, &original, &scaled);
+    }
+
+    true
+}
+
+// Changes of more than half the original value are highlighted in the
+// comparison, since they are worth a second look.
+const LARGE_JUMP_FRACTION: f64 = 0.5;
+
+// A line of the side-by-side comparison of an original and a scaled stat block.
+struct CompareRow {
+    statistic: String,
+    original: String,
+    scaled: String,
+    delta: String,
+    details: String,
+    highlight: Option<Color>,
+}
+
+// A line of the comparison table: a row of values, or a comment of the input
+// file kept as a separator.
+enum CompareLine {
+    Row(CompareRow),
+    Comment(String),
+}
+
+fn format_compare_value(kind: &StatType, value: f64) -> String {
+    if statistic::is_bonus(kind) {
+        format!("{:+}", value)
+    } else {
+        format!("{}", value)
+    }
+}
+
+fn format_damage_text(components: &[(String, String)]) -> String {
+    components
+        .iter()
+        .map(|(expression, damage_type)| format!("{} {}", expression, damage_type))
+        .collect::<Vec<String>>()
+        .join(" plus ")
+}
+
+fn original_damage_text(damage: &Damage) -> String {
+    let components: Vec<(String, String)> = damage
+        .components
+        .iter()
+        .map(|c| match &c.roll {
+            Some(r) => (r.to_string(), c.description()),
+            None => (format!("{:.2}", c.average_value), c.description()),
+        })
+        .collect();
+
+    format_damage_text(&components)
+}
+
+// Extrapolated and synthesised values are highlighted like in the normal
+// output. Otherwise, large jumps are highlighted.
+fn compare_highlight(method: ScaleMethod, original: f64, delta: f64) -> Option<Color> {
+    match method {
+        ScaleMethod::Extrapolated | ScaleMethod::Synthesised => Some(Color::BrightYellow),
+        _ if original != 0.0 && (delta / original).abs() > LARGE_JUMP_FRACTION => {
+            Some(Color::BrightRed)
+        }
+        _ => None,
+    }
+}
+
+fn compare_details(result: &ScaleResult, notes: &[String]) -> String {
+    let mut details = format!("{}, {}", result.proficiency, result.method);
+
+    for note in notes {
+        details += &format!(" ({})", note);
+    }
+
+    details
+}
+
+fn compare_integer(
+    statistic: String,
+    original: f64,
+    scaled: &Scaled,
+    rounding: RoundingPolicy,
+) -> CompareRow {
+    let (result, notes) = scaled;
+    let kind = &result.stat.kind;
+    let rounded = rounding.round(result.stat.value);
+    let delta = rounded - original;
+
+    CompareRow {
+        statistic,
+        original: format_compare_value(kind, original),
+        scaled: format_compare_value(kind, rounded),
+        delta: format!("{:+}", delta),
+        details: compare_details(result, notes),
+        highlight: compare_highlight(result.method, original, delta),
+    }
+}
+
+fn compare_damage(
+    levels: Levels,
+    statistic: String,
+    damage: &Damage,
+    scaled: &Scaled,
+    options: &Options,
+) -> CompareRow {
+    let (result, notes) = scaled;
+    let original = damage.total_average_value();
+    let delta = result.stat.value - original;
+
+    CompareRow {
+        statistic,
+        original: original_damage_text(damage),
+        scaled: format_damage_text(&scaled_damage_expressions(levels, damage, result, options)),
+        // Damage changes are measured on the average damage.
+        delta: format!("{:+.2}", delta),
+        details: compare_details(result, notes),
+        highlight: compare_highlight(result.method, original, delta),
+    }
+}
+
+// Statistics of a stat block line are named like the rows of prompts and
+// strikes. Only the values of a Strike are named after it, so the stage
+// damage of an affliction is still named like a strike-damage prompt.
+fn stat_block_statistic(line: &StatBlockLine, kind: &StatType, label: Option<&str>) -> String {
+    match (kind, label, &line.strike) {
+        (StatType::StrikeAttackBonus, Some(name), Some(_)) => format!("strike {} attack", name),
+        (StatType::StrikeDamage, Some(name), Some(_)) => format!("strike {} damage", name),
+        (_, Some(label), _) => format!("{} {}", kind, label),
+        (_, None, _) => kind.to_string(),
+    }
+}
+
+// The statistic of a stat block part as named in the scaled output.
+fn scaled_stat_block_statistic(
+    options: &Options,
+    line: &StatBlockLine,
+    kind: &StatType,
+    label: Option<&str>,
+) -> String {
+    stat_block_statistic(line, kind, scaled_label(options, kind, label).as_deref())
+}
+
+// The rows of the comparison for a scaled line, printed instead of the line.
+fn compare_line(levels: Levels, options: &Options, line: &ScaledLine) -> Vec<CompareRow> {
+    match line {
+        ScaledLine::SpellList(shift) => {
+            let details = if shift.removed.is_empty() {
+                String::new()
+            } else {
+                format!("removed {}", shift.removed.join(", "))
+            };
+
+            vec![CompareRow {
+                statistic: String::from("spells top rank"),
+                original: spells::format_rank(shift.old_top_rank),
+                scaled: spells::format_rank(shift.new_top_rank),
+                delta: format!("{:+}", shift.new_top_rank - shift.old_top_rank),
+                highlight: (!shift.removed.is_empty()).then_some(Color::BrightYellow),
+                details,
+            }]
+        }
+        ScaledLine::StatBlock(parsed, results) => parsed
+            .parts
+            .iter()
+            .zip(results)
+            .filter_map(|(part, result)| match (part, result) {
+                (Part::Value { kind, label, value }, Some(scaled)) => Some(compare_integer(
+                    scaled_stat_block_statistic(options, parsed, kind, label.as_deref()),
+                    *value,
+                    scaled,
+                    options.rounding,
+                )),
+                (
+                    Part::Damage {
+                        kind,
+                        label,
+                        damage,
+                    },
+                    Some(scaled),
+                ) => Some(compare_damage(
+                    levels,
+                    scaled_stat_block_statistic(options, parsed, kind, label.as_deref()),
+                    damage,
+                    scaled,
+                    options,
+                )),
+                _ => None,
+            })
+            .collect(),
+        ScaledLine::Strike(strike, attack, damage) => vec![
+            compare_integer(
+                format!("strike {} attack", strike.name),
+                strike.attack_bonus,
+                attack,
+                options.rounding,
+            ),
+            compare_damage(
+                levels,
+                format!("strike {} damage", strike.name),
+                &strike.damage,
+                damage,
+                options,
+            ),
+        ],
+        ScaledLine::Prompt(prompt, scaled) => {
+            let statistic = prompt.scaled_name(options);
+            let row = match &prompt.value {
+                PromptValue::Damage(d) => compare_damage(levels, statistic, d, scaled, options),
+                PromptValue::Integer(v) => compare_integer(statistic, *v, scaled, options.rounding),
+            };
+
+            vec![row]
+        }
+    }
+}
+
+fn print_compare_table(lines: &[CompareLine]) {
+    for line in format_compare_table(lines) {
+        println!("{}", line);
+    }
+}
+
+// The comparison table, with a header if it has any rows.
+fn format_compare_table(lines: &[CompareLine]) -> Vec<String> {
+    let rows: Vec<&CompareRow> = lines
+        .iter()
+        .filter_map(|l| match l {
+            CompareLine::Row(row) => Some(row),
+            CompareLine::Comment(_) => None,
+        })
+        .collect();
+
+    let headers = [
+        "statistic",
+        "original",
+        "scaled",
+        "delta",
+        "proficiency/method",
+    ];
+    let cells = |row: &CompareRow| {
+        [
+            row.statistic.clone(),
+            row.original.clone(),
+            row.scaled.clone(),
+            row.delta.clone(),
+            row.details.clone(),
+        ]
+    };
+
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(cells(row)) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    // Pad on plain text, since color codes have no width.
+    let format_line = |cells: [String; 5]| {
+        let line: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(i, (cell, width))| {
+                if i == 3 {
+                    format!("{:>width$}", cell, width = width)
+                } else {
+                    format!("{:<width$}", cell, width = width)
+                }
+            })
+            .collect();
+
+        String::from(line.join("  ").trim_end())
+    };
+
+    let mut table = Vec::new();
+    if !rows.is_empty() {
+        table.push(format_line(headers.map(String::from)));
+    }
+
+    for line in lines {
+        let row = match line {
+            CompareLine::Row(row) => row,
+            CompareLine::Comment(comment) => {
+                table.push(comment.clone());
+                continue;
+            }
+        };
+
+        let line = format_line(cells(row));
+        table.push(match row.highlight {
+            Some(color) => color_if_terminal(&line, color),
+            None => line,
+        });
+    }
+
+    table
+}
+
+// Record the statistics of a scaled line that the combat report uses, both as
+// given and as scaled.
+fn collect_combat_stats(
+    levels: Levels,
+    options: &Options,
+    line: &ScaledLine,
+    original: &mut CombatStats,
+    scaled: &mut CombatStats,
+) {
+    let mut collect_strike = |strike: &Strike, attack: &ScaleResult, damage: &ScaleResult| {
+        let scaled_rolls = scaled_damage_rolls(levels, &strike.damage, damage, options);
+
+        original.strikes.push(StrikeStats {
+            name: strike.name.clone(),
+            attack_bonus: strike.attack_bonus,
+            damage: strike.damage.total_average_value(),
+            rolls: damage_rolls(&strike.damage),
+            agile: strike.is_agile(),
+            critical_traits: strike.critical_traits(),
+        });
+        scaled.strikes.push(StrikeStats {
+            name: strike.name.clone(),
+            attack_bonus: options.rounding.round(attack.stat.value),
+            damage: damage.stat.value,
+            critical_traits: scaled_critical_traits(strike, &scaled_rolls),
+            rolls: scaled_rolls,
+            agile: strike.is_agile(),
+        });
+    };
+
+    let (prompt, scaled_result) = match line {
+        ScaledLine::SpellList(_) => return,
+        // The Strikes and affliction DCs of a stat block are used by the report.
+        ScaledLine::StatBlock(parsed, results) => {
+            let strike = parsed.strike.as_ref();
+            if let (Some(strike), Some((attack, damage))) =
+                (strike, stat_block_strike_results(parsed, results))
+            {
+                collect_strike(strike, &attack, &damage);
+            }
+
+            for (part, result) in parsed.parts.iter().zip(results) {
+                if let (
+                    Part::Value {
+                        kind: kind @ StatType::AbilityDC,
+                        label,
+                        value,
+                    },
+                    Some((result, _)),
+                ) = (part, result)
+                {
+                    let label = stat_block_statistic(parsed, kind, label.as_deref());
+                    original.dcs.push(DcStats {
+                        label: label.clone(),
+                        dc: *value,
+                    });
+                    scaled.dcs.push(DcStats {
+                        label,
+                        dc: options.rounding.round(result.stat.value),
+                    });
+                }
+            }
+            return;
+        }
+        ScaledLine::Strike(strike, (attack, _), (damage, _)) => {
+            collect_strike(strike, attack, damage);
+            return;
+        }
+        ScaledLine::Prompt(prompt, (result, _)) => (prompt, result),
+    };
+
+    let value = prompt.total_value();
+    let scaled_value = scaled_result.stat.value;
+
+    match prompt.kind {
+        StatType::StrikeAttackBonus => {
+            original.add_strike_attack(value);
+            scaled.add_strike_attack(options.rounding.round(scaled_value));
+        }
+        StatType::StrikeDamage => {
+            let (rolls, scaled_rolls) = match &prompt.value {
+                PromptValue::Damage(d) => (
+                    damage_rolls(d),
+                    scaled_damage_rolls(levels, d, scaled_result, options),
+                ),
+                PromptValue::Integer(_) => (Vec::new(), Vec::new()),
+            };
+
+            original.add_strike_damage(value, rolls);
+            scaled.add_strike_damage(scaled_value, scaled_rolls);
+        }
+        StatType::SpellDC | StatType::AbilityDC => {
+            let label = prompt.name();
+
+            original.dcs.push(DcStats {
+                label: label.clone(),
+                dc: value,
+            });
+            scaled.dcs.push(DcStats {
+                label,
+                dc: options.rounding.round(scaled_value),
+            });
+        }
+        StatType::UnlimitedAreaDamage | StatType::LimitedAreaDamage => {
+            original.area_damage.push(value);
+            scaled.area_damage.push(scaled_value);
+        }
+        _ => (),
+    }
+}
+
+// The dice of each baseline damage component as written. Conditional damage
+// isn't rolled in simulations.
+fn damage_rolls(damage: &Damage) -> Vec<DamageRoll> {
+    damage
+        .components
+        .iter()
+        .filter(|c| damage.is_baseline(c))
+        .filter_map(|c| c.roll.clone())
+        .collect()
+}
+
+// Scale damage components to the scaled total damage, with legacy damage
+// types converted to the remaster if the options ask for it.
+fn scale_damage(levels: Levels, damage: &Damage, scaled_total: f64, options: &Options) -> Damage {
+    let mut scaled = scaling::scale_damage_components(levels, damage, scaled_total);
+
+    if options.remaster {
+        for component in &mut scaled.components {
+            component.damage_type = damage_types::to_remaster(&component.damage_type);
+        }
+    }
+
+    scaled
+}
+
+// The label of a statistic as printed for the scaled creature. The types of
+// resistances and weaknesses are converted to the remaster like damage types.
+fn scaled_label(options: &Options, kind: &StatType, label: Option<&str>) -> Option<String> {
+    match kind {
+        StatType::Resistance | StatType::BroadResistance | StatType::Weakness
+            if options.remaster =>
+        {
+            label.map(damage_types::to_remaster)
+        }
+        _ => label.map(String::from),
+    }
+}
+
+// Replace the label at the end of a text, ignoring case and trailing whitespace.
+fn replace_label_text(text: &mut String, label: &str, scaled_label: &str) {
+    if label == scaled_label {
+        return;
+    }
+
+    let end = text.trim_end().len();
+    let start = match end.checked_sub(label.len()) {
+        Some(s) if text.is_char_boundary(s) && text[s..end].eq_ignore_ascii_case(label) => s,
+        _ => return,
+    };
+
+    text.replace_range(start..end, scaled_label);
+}
+
+fn scaled_damage_expression(
+    component: &DamageComponent,
+    result: &ScaleResult,
+    options: &Options,
+) -> String {
+    damage_component_expression(component, result.proficiency, options.rounding)
+}
+
+fn damage_component_expression(
+    component: &DamageComponent,
+    proficiency: Proficiency,
+    rounding: RoundingPolicy,
+) -> String {
+    // Conditional components keep their own dice.
+    if let (true, Some(roll)) = (component.is_conditional(), &component.roll) {
+        return roll.to_string();
+    }
+
+    let average_damage = rounding.damage_target(component.average_value);
+    damage::build_damage_expression(average_damage, proficiency)
+}
+
+// The damage expression of each scaled damage component, as printed for the
+// scaled creature.
+fn scaled_damage_expressions(
+    levels: Levels,
+    damage: &Damage,
+    result: &ScaleResult,
+    options: &Options,
+) -> Vec<(String, String)> {
+    scale_damage(levels, damage, result.stat.value, options)
+        .components
+        .iter()
+        .map(|c| {
+            (
+                scaled_damage_expression(c, result, options),
+                c.description(),
+            )
+        })
+        .collect()
+}
+
+fn scaled_damage_rolls(
+    levels: Levels,
+    damage: &Damage,
+    result: &ScaleResult,
+    options: &Options,
+) -> Vec<DamageRoll> {
+    let scaled = scale_damage(levels, damage, result.stat.value, options);
+
+    scaled
+        .components
+        .iter()
+        .filter(|c| scaled.is_baseline(c))
+        .filter_map(|c| damage::parse_damage_roll(&scaled_damage_expression(c, result, options)))
+        .collect()
+}
+
+fn format_percent(chance: f64) -> String {
+    format!("{:.0}%", chance * 100.0)
+}
+
+fn strike_title(name: &str) -> String {
+    if name.is_empty() {
+        String::from("strike")
+    } else {
+        format!("strike {}", name)
+    }
+}
+
+// Only Strikes with deadly or fatal report their critical hits' damage.
+fn format_critical_damage(critical_damage: Option<f64>) -> String {
+    match critical_damage {
+        Some(damage) => format!(" for {:.2}", damage),
+        None => String::new(),
+    }
+}
+
+fn print_rounds_to_down(target: &str, rounds: Option<f64>, damage_per_round: f64) {
+    match rounds {
+        Some(rounds) => {
+            let rounds = format!("{:.1} rounds", rounds);
+            println!(
+                "  downs {} in {} ({:.2} damage per round)",
+                target,
+                color_if_terminal(&rounds, Color::BrightCyan),
+                damage_per_round
+            );
+        }
+        None => println!("  no damage to down a PC with"),
+    }
+}
+
+fn print_combat_report(title: &str, level: i32, report: &CombatReport) {
+    let pc = report.pc;
+    println!(
+        "report {} (creature level {}, PC level {}: AC {}, save +{}, HP {})",
+        title, level, pc.level, pc.ac, pc.save, pc.hp
+    );
+
+    for strike in &report.strikes {
+        println!(
+            "  {} +{}: hits {}, crits {}{}, {:.2} damage per round",
+            strike_title(&strike.name),
+            strike.attack_bonus,
+            format_percent(strike.chances.success + strike.chances.critical_success),
+            format_percent(strike.chances.critical_success),
+            format_critical_damage(strike.critical_damage),
+            strike.damage_per_round
+        );
+    }
+
+    for dc in &report.dcs {
+        println!(
+            "  {} {}: PC fails {}, critically fails {}",
+            dc.label,
+            dc.dc,
+            format_percent(dc.chances.failure + dc.chances.critical_failure),
+            format_percent(dc.chances.critical_failure)
+        );
+    }
+
+    for (damage, expected) in &report.area_damage {
+        println!(
+            "  area damage {:.2}: {:.2} expected against a basic save",
+            damage, expected
+        );
+    }
+
+    print_rounds_to_down(
+        "a typical PC",
+        report.rounds_to_down(),
+        report.damage_per_round,
+    );
+}
+
+fn print_pc_report(title: &str, level: i32, report: &PcReport) {
+    let pc = &report.pc;
+    println!(
+        "report {} vs {} (creature level {}, PC level {}: AC {}, fort {:+}, ref {:+}, will {:+}, per {:+}, HP {})",
+        title,
+        pc.name,
+        level,
+        pc.level,
+        pc.ac,
+        pc.fortitude,
+        pc.reflex,
+        pc.will,
+        pc.perception,
+        pc.hp
+    );
+
+    for strike in &report.strikes {
+        println!(
+            "  {} +{}: hits {}, crits {}{}, average hit {} of HP, {:.2} damage per round",
+            strike_title(&strike.name),
+            strike.attack_bonus,
+            format_percent(strike.chances.success + strike.chances.critical_success),
+            format_percent(strike.chances.critical_success),
+            format_critical_damage(strike.critical_damage),
+            format_percent(strike.hp_per_hit),
+            strike.damage_per_round
+        );
+    }
+
+    let fails =
+        |chances: &combat::Chances| format_percent(chances.failure + chances.critical_failure);
+
+    for dc in &report.dcs {
+        println!(
+            "  {} {}: PC fails fort {}, ref {}, will {}, per {}",
+            dc.label,
+            dc.dc,
+            fails(&dc.fortitude),
+            fails(&dc.reflex),
+            fails(&dc.will),
+            fails(&dc.perception)
+        );
+    }
+
+    for (damage, expected) in &report.area_damage {
+        println!(
+            "  area damage {:.2}: {:.2} expected against a basic Reflex save",
+            damage, expected
+        );
+    }
+
+    print_rounds_to_down(&pc.name, report.rounds_to_down(), report.damage_per_round);
+}
+
+fn print_simulations(stats: &CombatStats, ac: f64, hp: f64, options: &Options) {
+    let trials = match options.simulate {
+        Some(t) => t,
+        None => return,
+    };
+    let seed = options.seed.unwrap_or(rng::DEFAULT_SEED);
+
+    for strike in &stats.strikes {
+        let report = simulation::simulate_strike(strike, ac, hp, trials, seed);
+
+        println!(
+            "  simulated {} ({} fights): damage per round median {}, 10th-90th percentile {}-{}, max {}; downs in 1 round {}, in 2 rounds {}; crits {}",
+            strike_title(&strike.name),
+            report.trials,
+            report.percentile(0.5),
+            report.percentile(0.1),
+            report.percentile(0.9),
+            report.percentile(1.0),
+            format_percent(report.downs_in_one_round),
+            format_percent(report.downs_in_two_rounds),
+            format_percent(report.critical_hits)
+        );
+    }
+}
+
+// Compare the original creature against PCs of its level with the scaled
+// creature against PCs of the target level. With a party, both are compared
+// against each PC of the party instead.
+fn print_combat_reports(
+    levels: Levels,
+    options: &Options,
+    original: &CombatStats,
+    scaled: &CombatStats,
+) {
+    if original.is_empty() {
+        return;
+    }
+
+    if !options.party.is_empty() {
+        for pc in &options.party {
+            print_pc_report("original", levels.current, &combat::pc_report(original, pc));
+            print_simulations(original, pc.ac, pc.hp, options);
+            print_pc_report("scaled", levels.target, &combat::pc_report(scaled, pc));
+            print_simulations(scaled, pc.ac, pc.hp, options);
+        }
+        return;
+    }
+
+    let original_report = combat::combat_report(original, levels.current);
+    let scaled_report = combat::combat_report(scaled, levels.target);
+
+    print_combat_report("original", levels.current, &original_report);
+    print_simulations(
+        original,
+        original_report.pc.ac,
+        original_report.pc.hp,
+        options,
+    );
+    print_combat_report("scaled", levels.target, &scaled_report);
+    print_simulations(scaled, scaled_report.pc.ac, scaled_report.pc.hp, options);
+}
+
+fn generate_stat_block(role: Role, args: &Arguments) {
+    let level = args.levels.current;
+
+    println!(
+        "{}",
+        creature::format_creature_header(&role.to_string(), level)
+    );
+
+    for line in roadmap::generate(role, level, &args.options.tables) {
+        println!("{}", line);
+    }
+}
+
+fn format_stat_value(kind: &StatType, value: f64) -> String {
+    if statistic::is_bonus(kind) {
+        format!("+{}", value)
+    } else {
+        format!("{}", value)
+    }
+}
+
+fn format_expectation(role: Role, entry: &RoadmapEntry, level: i32, tables: &TableSet) -> String {
+    let expected = entry.expected_value(level, tables);
+    let range = entry.allowed_range(level, tables);
+
+    match (expected, range) {
+        (Some(e), Some((low, high))) => format!(
+            "{} expects {} {}, allowed {} to {}",
+            role,
+            entry.proficiency,
+            format_stat_value(&entry.kind, e),
+            format_stat_value(&entry.kind, low),
+            format_stat_value(&entry.kind, high)
+        ),
+        _ => format!("{} expects {}", role, entry.proficiency),
+    }
+}
+
+fn print_check(
+    role: Role,
+    level: i32,
+    kind: StatType,
+    value: f64,
+    text: &str,
+    tables: &TableSet,
+) -> bool {
+    let entry = match roadmap::roadmap_entry(role, &kind) {
+        Some(e) => e,
+        None => {
+            println!("{} [not in roadmap]", text);
+            return true;
+        }
+    };
+
+    let verdict = roadmap::check_value(&entry, level, value, tables);
+    let expectation = format_expectation(role, &entry, level, tables);
+
+    let (verdict_text, color) = match verdict {
+        Some(Verdict::Within) => (Verdict::Within.to_string(), Color::Green),
+        Some(v) => (v.to_string(), Color::BrightYellow),
+        None => (String::from("unchecked"), Color::BrightYellow),
+    };
+
+    println!(
+        "{} [{}] ({})",
+        text,
+        color_if_terminal(&verdict_text, color),
+        expectation
+    );
+
+    verdict == Some(Verdict::Within)
+}
+
+// Check the attack and the damage of a strike as separate statistics.
+fn print_strike_check(role: Role, level: i32, strike: &Strike, tables: &TableSet) -> bool {
+    let attack = format!("strike {} attack {:+}", strike.name, strike.attack_bonus);
+    let damage = format!(
+        "strike {} damage {:.2}",
+        strike.name,
+        strike.damage.total_average_value()
+    );
+
+    let attack_fits = print_check(
+        role,
+        level,
+        StatType::StrikeAttackBonus,
+        strike.attack_bonus,
+        &attack,
+        tables,
+    );
+    let damage_fits = print_check(
+        role,
+        level,
+        StatType::StrikeDamage,
+        strike.damage.total_average_value(),
+        &damage,
+        tables,
+    );
+
+    attack_fits && damage_fits
+}
+
+// Check each value and damage of a line in stat block form on its own line.
+fn print_stat_block_check(
+    role: Role,
+    level: i32,
+    line: &StatBlockLine,
+    seen: &mut Vec<StatType>,
+    tables: &TableSet,
+) -> bool {
+    let mut fits = true;
+
+    for part in &line.parts {
+        let (kind, label, value) = match part {
+            Part::Value { kind, label, value } => (kind, label, *value),
+            Part::Damage {
+                kind,
+                label,
+                damage,
+            } => (kind, label, damage.total_average_value()),
+            _ => continue,
+        };
+
+        let text = format!(
+            "{} {}",
+            stat_block_statistic(line, kind, label.as_deref()),
+            part
+        );
+
+        // The stages of an affliction scale like Strike damage, but aren't a Strike.
+        if *kind == StatType::StrikeDamage && line.strike.is_none() {
+            println!("{} [not in roadmap]", text);
+            continue;
+        }
+
+        seen.push(kind.clone());
+        fits &= print_check(role, level, kind.clone(), value, &text, tables);
+    }
+
+    fits
+}
+
+fn print_missing(role: Role, level: i32, seen: &[StatType], tables: &TableSet) -> bool {
+    let mut complete = true;
+
+    for entry in roadmap::roadmap(role) {
+        if seen.contains(&entry.kind) {
+            continue;
+        }
+
+        println!(
+            "{} [{}] ({})",
+            entry.kind,
+            color_if_terminal("missing", Color::BrightYellow),
+            format_expectation(role, &entry, level, tables)
+        );
+        complete = false;
+    }
+
+    complete
+}
+
+// Check each statistic of the input file against the roadmap of a role, then
+// list the statistics of the roadmap the stat block lacks. Creature headers
+// start a new stat block at their own level. Returns whether everything fits.
+fn check_stat_block(role: Role, args: &Arguments) -> Option<bool> {
+    let input_file = args
+        .input_file
+        .as_ref()
+        .expect("input file argument should have been checked to exist");
+
+    let contents = match fs::read_to_string(input_file) {
+        Ok(c) => c,
+        Err(_) => {
+            logging::log(LogLevel::Error, "failed to read input file");
+            return None;
+        }
+    };
+
+    let tables = &args.options.tables;
+    let mut level = args.levels.current;
+    let mut seen: Vec<StatType> = Vec::new();
+    // Whether a stat block has started, so lines before the first header
+    // that are all comments don't count as an empty stat block.
+    let mut started = false;
+    let mut fits = true;
+
+    for line in contents.lines() {
+        if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
+            println!("{}", line);
+            continue;
+        }
+
+        if creature::is_creature_header(line) {
+            if started {
+                fits &= print_missing(role, level, &seen, tables);
+            }
+            seen.clear();
+            started = true;
+
+            let header = creature::parse_creature_header(line)?;
+            level = Levels::new(header.level, header.level)?.current;
+
+            println!("{}", line.trim());
+            continue;
+        }
+
+        started = true;
+
+        if spells::is_spell_list(line) {
+            spells::parse_spell_list(line, level)?;
+            println!("{} [not in roadmap]", line.trim());
+            continue;
+        }
+
+        if stat_block::is_stat_block_line(line) {
+            let parsed = stat_block::parse_stat_block_line(line)?;
+            fits &= print_stat_block_check(role, level, &parsed, &mut seen, tables);
+            continue;
+        }
+
+        if strike::is_strike(line) {
+            let strike = strike::parse_strike(line)?;
+            seen.push(StatType::StrikeAttackBonus);
+            seen.push(StatType::StrikeDamage);
+            fits &= print_strike_check(role, level, &strike, tables);
+            continue;
+        }
+
+        let prompt = parse_prompt(line)?;
+        seen.push(prompt.stat_kind());
+        fits &= print_check(
+            role,
+            level,
+            prompt.stat_kind(),
+            prompt.total_value(),
+            line.trim(),
+            tables,
+        );
+    }
+
+    fits &= print_missing(role, level, &seen, tables);
+
+    Some(fits)
+}
+
+// Answers a query, a line that ends with '?', with the words that start with
+// its last word. The first word matches statistics and keywords, and later
+// words of damage lines match damage types.
+fn query_line(line: &str) -> Option<Vec<&'static str>> {
+    let line = line.trim().strip_suffix('?')?;
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let querying_first = words.len() <= 1 && !line.ends_with(' ');
+
+    if querying_first {
+        let prefix = words.first().copied().unwrap_or("");
+        return Some(suggest::complete(prefix, &line_start_words()));
+    }
+
+    if !is_damage_line(words[0]) {
+        return Some(Vec::new());
+    }
+
+    let prefix = if line.ends_with(' ') {
+        ""
+    } else {
+        words.last().copied().unwrap_or("")
+    };
+
+    Some(suggest::complete(prefix, &damage_types::names()))
+}
+
+fn is_damage_line(first_word: &str) -> bool {
+    let first_word = first_word.to_lowercase();
+
+    first_word == "strike"
+        || matches!(
+            stat_kind(&first_word),
+            Some(
+                StatType::StrikeDamage
+                    | StatType::UnlimitedAreaDamage
+                    | StatType::LimitedAreaDamage
+            )
+        )
+}
+
+// The line with a mistyped statistic or damage type replaced by its
+// suggestion, or None if nothing looks mistyped.
+fn correct_line(line: &str) -> Option<String> {
+    if stat_block::is_stat_block_line(line) || subject_first_lore(line.trim()).is_some() {
+        return None;
+    }
 
-    print_scale_details(result);
-}
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let mut corrected: Vec<String> = words.iter().map(|w| String::from(*w)).collect();
 
-fn process_input_file(args: Arguments) -> bool {
-    let input_file = args
-        .input_file
-        .expect("input file argument should have been checked to exist");
+    let first = words.first()?.to_lowercase();
+    if stat_kind(&first).is_none() && !LINE_KEYWORDS.contains(&first.as_str()) {
+        corrected[0] = String::from(suggest::suggest(&first, &line_start_words())?);
+    }
 
-    let contents = match fs::read_to_string(input_file) {
-        Ok(c) => c,
-        Err(_) => {
-            logging::log(LogLevel::Error, "failed to read input file");
-            return false;
-        }
-    };
+    // Damage types come before "plus", an inline shift or the end of the line.
+    if is_damage_line(&corrected[0]) {
+        for i in 1..words.len() {
+            let is_type_position = words
+                .get(i + 1)
+                .is_none_or(|next| next.eq_ignore_ascii_case("plus") || next.starts_with('>'));
+            let word = words[i].to_lowercase();
 
-    for line in contents.lines() {
-        if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
-            println!("{}", line);
-            continue;
-        }
+            if !is_type_position || !word.chars().all(|c| c.is_ascii_alphabetic()) {
+                continue;
+            }
 
-        if handle_prompt(args.levels, line).is_none() {
-            logging::log(LogLevel::Error, "failed to process input file");
-            return false;
+            if let Some(suggestion) = damage_types::suggest(&word) {
+                corrected[i] = String::from(suggestion);
+            }
         }
     }
 
-    true
+    let corrected = corrected.join(" ");
+    if corrected == words.join(" ") {
+        None
+    } else {
+        Some(corrected)
+    }
 }
 
 fn start_interactive_prompt(args: Arguments) {
     print_version();
 
+    // The corrected version of the previous line, run by an empty line.
+    let mut correction: Option<String> = None;
+
     loop {
         eprint!("> ");
 
         let mut prompt = String::new();
-        io::stdin()
+        let read = io::stdin()
             .read_line(&mut prompt)
             .expect("failed to read prompt");
+        if read == 0 {
+            break;
+        }
+
+        let line = if prompt.trim().is_empty() {
+            match correction.take() {
+                Some(c) => {
+                    eprintln!("{}", c);
+                    c
+                }
+                None => continue,
+            }
+        } else {
+            prompt
+        };
 
-        handle_prompt(args.levels, &prompt);
+        if let Some(matches) = query_line(&line) {
+            eprintln!("{}", matches.join(" "));
+            continue;
+        }
+
+        // A line has no creature around it, so only its own resistances count.
+        let options = creature_options(&args.options, &[line.as_str()]);
+        handle_line(args.levels, &options, &line);
+
+        correction = correct_line(&line);
+        if let Some(c) = &correction {
+            logging::log(LogLevel::Info, format!("press enter to run: {}", c));
+        }
     }
 }
 
@@ -306,20 +2447,84 @@ fn main() {
         }
     };
 
-    if parsed_args.input_file.is_some() {
-        process_input_file(parsed_args);
-    } else {
-        start_interactive_prompt(parsed_args);
+    match parsed_args.command {
+        Command::Generate(role) => generate_stat_block(role, &parsed_args),
+        Command::Check(role) => match check_stat_block(role, &parsed_args) {
+            Some(true) => {}
+            // Deviations from the roadmap fail the check, so scripts can tell.
+            Some(false) => process::exit(1),
+            None => {
+                logging::log(LogLevel::Error, "failed to check input file");
+                process::exit(1);
+            }
+        },
+        Command::Scale => {
+            if parsed_args.input_file.is_some() {
+                process_input_file(parsed_args);
+            } else {
+                start_interactive_prompt(parsed_args);
+            }
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use stronk::damage::Dice;
     use stronk::scaling::ScaleMethod;
-    use stronk::tables::Proficiency;
+    use stronk::tables::{Proficiency, TableKind};
     use stronk::utils::float_eq;
 
+    // Handle a prompt or Strike, returning the scaled value or attack bonus.
+    fn handle_prompt(levels: Levels, options: &Options, line: &str) -> Option<ScaleResult> {
+        match handle_line(levels, options, line)? {
+            ScaledLine::Prompt(_, (result, _)) | ScaledLine::Strike(_, (result, _), _) => {
+                Some(result)
+            }
+            _ => None,
+        }
+    }
+
+    fn handle_strike(
+        levels: Levels,
+        options: &Options,
+        line: &str,
+    ) -> Option<(ScaleResult, ScaleResult)> {
+        match handle_line(levels, options, line)? {
+            ScaledLine::Strike(_, (attack, _), (damage, _)) => Some((attack, damage)),
+            _ => None,
+        }
+    }
+
+    fn handle_spell_list(levels: Levels, line: &str) -> Option<SpellShift> {
+        match handle_line(levels, &Options::default(), line)? {
+            ScaledLine::SpellList(shift) => Some(shift),
+            _ => None,
+        }
+    }
+
+    fn compare(levels: Levels, options: &Options, line: &str) -> Option<Vec<CompareRow>> {
+        scale_line(levels, options, line).map(|l| compare_line(levels, options, &l))
+    }
+
+    fn collect(
+        levels: Levels,
+        options: &Options,
+        line: &str,
+        original: &mut CombatStats,
+        scaled: &mut CombatStats,
+    ) {
+        let line = scale_line(levels, options, line).unwrap();
+        collect_combat_stats(levels, options, &line, original, scaled);
+    }
+
+    fn scale_stat_block_line(levels: Levels, options: &Options, line: &str) -> String {
+        let parsed = stat_block::parse_stat_block_line(line).unwrap();
+        let results = scale_stat_block_parts(levels, options, &parsed);
+        format_scaled_stat_block_line(levels, options, &parsed, &results)
+    }
+
     #[test]
     fn accept_valid_args() {
         let args = parse_args(&vec!["", "1", "2"]).unwrap();
@@ -330,9 +2535,28 @@ mod tests {
         assert!(parse_args(&vec!["", "1", "1"]).is_some());
         assert!(parse_args(&vec!["", "-1", "24"]).is_some());
         assert!(parse_args(&vec!["", "24", "-1"]).is_some());
+        assert!(parse_args(&vec!["", "-2", "30"]).is_some());
+        assert!(parse_args(&vec!["", "30", "-2"]).is_some());
         assert!(parse_args(&vec!["something", "1", "2"]).is_some());
 
         assert!(parse_args(&vec!["", "1", "2", "input.txt"]).is_some());
+
+        let args = parse_args(&vec!["", "--relative", "1", "2", "input.txt"]).unwrap();
+        assert!(args.relative);
+        assert!(parse_args(&vec!["", "1", "2", "input.txt", "--relative"]).is_some());
+        assert!(parse_args(&vec!["", "-1", "--relative", "2"]).is_some());
+
+        let args = parse_args(&vec!["", "--rounding", "nearest", "1", "2"]).unwrap();
+        assert_eq!(args.options.rounding, RoundingPolicy::Nearest);
+        let args =
+            parse_args(&vec!["", "1", "2", "--rounding", "floor-unless-within=0.2"]).unwrap();
+        assert_eq!(
+            args.options.rounding,
+            RoundingPolicy::FloorUnlessWithin(0.2)
+        );
+
+        let args = parse_args(&vec!["", "--remaster", "1", "2"]).unwrap();
+        assert!(args.options.remaster);
     }
 
     #[test]
@@ -342,53 +2566,152 @@ mod tests {
         assert!(parse_args(&vec!["", "1", "x"]).is_none());
         assert!(parse_args(&vec!["", "x", "1"]).is_none());
         assert!(parse_args(&vec!["", "x", "x"]).is_none());
-        assert!(parse_args(&vec!["", "-2", "2"]).is_none());
-        assert!(parse_args(&vec!["", "1", "25"]).is_none());
+        assert!(parse_args(&vec!["", "-3", "2"]).is_none());
+        assert!(parse_args(&vec!["", "1", "31"]).is_none());
         assert!(parse_args(&vec!["", "1", "2.345"]).is_none());
 
         assert!(parse_args(&vec!["", "1", "2", "input.txt", "4"]).is_none());
+        assert!(parse_args(&vec!["", "--unknown", "1", "2"]).is_none());
+        assert!(parse_args(&vec!["", "--relative", "1"]).is_none());
+        assert!(parse_args(&vec!["", "1", "2", "--rounding"]).is_none());
+        assert!(parse_args(&vec!["", "--rounding", "sideways", "1", "2"]).is_none());
+        assert!(parse_args(&vec!["", "--extrapolation", "hp", "1", "2"]).is_none());
+        assert!(parse_args(&vec!["", "--extrapolation", "hp=sideways", "1", "2"]).is_none());
+        assert!(parse_args(&vec!["", "--extrapolation", "invalid=additive", "1", "2"]).is_none());
+        assert!(parse_args(&vec!["", "1", "2", "--party"]).is_none());
+        assert!(parse_args(&vec!["", "--simulate", "0", "1", "2"]).is_none());
+        assert!(parse_args(&vec!["", "--simulate", "many", "1", "2"]).is_none());
+        assert!(parse_args(&vec!["", "--seed", "-1", "1", "2"]).is_none());
+        assert!(parse_args(&vec!["", "--seed", "99999999999999999999", "1", "2"]).is_none());
+        assert!(parse_args(&vec![
+            "",
+            "--party",
+            "testdata/nonexistent_party_file.txt",
+            "1",
+            "2"
+        ])
+        .is_none());
+        assert!(parse_args(&vec![
+            "",
+            "--tables",
+            "testdata/invalid_table_file.txt",
+            "1",
+            "2"
+        ])
+        .is_none());
+        assert!(parse_args(&vec![
+            "",
+            "--tables",
+            "testdata/nonexistent_table_file.txt",
+            "1",
+            "2"
+        ])
+        .is_none());
+
+        assert!(parse_args(&vec!["", "generate", "tank", "5"]).is_none());
+        assert!(parse_args(&vec!["", "generate", "brute"]).is_none());
+        assert!(parse_args(&vec!["", "generate", "brute", "5", "input.txt"]).is_none());
+        assert!(parse_args(&vec!["", "generate", "brute", "31"]).is_none());
+        assert!(parse_args(&vec!["", "check", "brute", "5"]).is_none());
+    }
+
+    #[test]
+    fn accept_roadmap_args() {
+        let args = parse_args(&vec!["", "generate", "brute", "5"]).unwrap();
+        assert_eq!(args.command, Command::Generate(Role::Brute));
+        assert_eq!(args.levels.current, 5);
+        assert_eq!(args.levels.target, 5);
+        assert!(args.input_file.is_none());
+
+        let args = parse_args(&vec!["", "check", "soldier", "-1", "input.txt"]).unwrap();
+        assert_eq!(args.command, Command::Check(Role::Soldier));
+        assert_eq!(args.levels.current, -1);
+        assert_eq!(args.input_file, Some(String::from("input.txt")));
+
+        let args = parse_args(&vec!["", "1", "2"]).unwrap();
+        assert_eq!(args.command, Command::Scale);
+    }
+
+    #[test]
+    fn check_stat_block_against_roadmap() {
+        let mut args = parse_args(&vec![
+            "",
+            "check",
+            "brute",
+            "5",
+            "testdata/brute_stat_block.txt",
+        ])
+        .unwrap();
+
+        assert_eq!(check_stat_block(Role::Brute, &args), Some(true));
+        assert_eq!(check_stat_block(Role::Soldier, &args), Some(false));
+        assert_eq!(check_stat_block(Role::Spellcaster, &args), Some(false));
+
+        // Lines in stat block form are checked value by value.
+        args.input_file = Some(String::from("testdata/brute_official_stat_block.txt"));
+        assert_eq!(check_stat_block(Role::Brute, &args), Some(true));
+        assert_eq!(check_stat_block(Role::Soldier, &args), Some(false));
+
+        args.input_file = Some(String::from("testdata/nonexistent_input_file.txt"));
+        assert_eq!(check_stat_block(Role::Brute, &args), None);
+
+        args.input_file = Some(String::from("testdata/invalid_input_file.txt"));
+        assert_eq!(check_stat_block(Role::Brute, &args), None);
     }
 
     #[rustfmt::skip]
     #[test]
     fn accept_valid_prompt_syntax() {
         let levels = Levels::new(1, 2).unwrap();
-
-        assert!(handle_prompt(levels, "ac 12").is_some());
-        assert!(handle_prompt(levels, "AC 12").is_some());
-        assert!(handle_prompt(levels, "   ac   12    ").is_some());
-        assert!(handle_prompt(levels, "AC 120").is_some());
-        assert!(handle_prompt(levels, "ac 0").is_some());
-        assert!(handle_prompt(levels, "ac -1").is_some());
-        assert!(handle_prompt(levels, "ac -34").is_some());
-
-        assert!(handle_prompt(levels, "strike-damage 2d12+11 bludgeoning").is_some());
-        assert!(handle_prompt(levels, "strike-damage 3d10 + 17 slashing plus 2d6+6 cold plus 1d4 acid plus 2 vitality").is_some());
+        let options = Options::default();
+
+        assert!(handle_prompt(levels, &options, "ac 12").is_some());
+        assert!(handle_prompt(levels, &options, "AC 12").is_some());
+        assert!(handle_prompt(levels, &options, "   ac   12    ").is_some());
+        assert!(handle_prompt(levels, &options, "AC 120").is_some());
+        assert!(handle_prompt(levels, &options, "ac 0").is_some());
+        assert!(handle_prompt(levels, &options, "ac -1").is_some());
+        assert!(handle_prompt(levels, &options, "ac -34").is_some());
+
+        assert!(handle_prompt(levels, &options, "ac 12 >high").is_some());
+        assert!(handle_prompt(levels, &options, "ac 12 >-1").is_some());
+
+        assert!(handle_prompt(levels, &options, "strike-damage 2d12+11 bludgeoning").is_some());
+        assert!(handle_prompt(levels, &options, "strike-damage 2d12+11 bludgeoning >+1").is_some());
+        assert!(handle_prompt(levels, &options, "strike-damage 3d10 + 17 slashing plus 2d6+6 cold plus 1d4 acid plus 2 vitality").is_some());
     }
 
     #[test]
     fn reject_invalid_prompt_syntax() {
         let levels = Levels::new(1, 2).unwrap();
-
-        assert!(handle_prompt(levels, "").is_none());
-        assert!(handle_prompt(levels, "ac").is_none());
-        assert!(handle_prompt(levels, "invalid").is_none());
-        assert!(handle_prompt(levels, "ac x").is_none());
-        assert!(handle_prompt(levels, "invalid 12").is_none());
-        assert!(handle_prompt(levels, "invalid x").is_none());
-        assert!(handle_prompt(levels, "ac 12 34").is_none());
-        assert!(handle_prompt(levels, "ac 12.34").is_none());
-
-        assert!(handle_prompt(levels, "ac 2d6+1 fire").is_none());
-        assert!(handle_prompt(levels, "damage 1d4+1").is_none());
-        assert!(handle_prompt(levels, "1d6+2").is_none());
+        let options = Options::default();
+
+        assert!(handle_prompt(levels, &options, "").is_none());
+        assert!(handle_prompt(levels, &options, "ac").is_none());
+        assert!(handle_prompt(levels, &options, "invalid").is_none());
+        assert!(handle_prompt(levels, &options, "ac x").is_none());
+        assert!(handle_prompt(levels, &options, "invalid 12").is_none());
+        assert!(handle_prompt(levels, &options, "invalid x").is_none());
+        assert!(handle_prompt(levels, &options, "ac 12 34").is_none());
+        assert!(handle_prompt(levels, &options, "ac 12.34").is_none());
+
+        assert!(handle_prompt(levels, &options, "ac 12 >legendary").is_none());
+        assert!(handle_prompt(levels, &options, "ac 12 >1").is_none());
+        assert!(handle_prompt(levels, &options, "ac >high").is_none());
+
+        assert!(handle_prompt(levels, &options, "ac 2d6+1 fire").is_none());
+        assert!(handle_prompt(levels, &options, "damage 1d4+1").is_none());
+        assert!(handle_prompt(levels, &options, "1d6+2").is_none());
     }
 
     #[test]
     fn accept_valid_input_file() {
         let args = Arguments {
+            command: Command::Scale,
             levels: Levels::new(9, 15).unwrap(),
             input_file: Some(String::from("testdata/valid_input_file.txt")),
+            relative: false,
+            options: Options::default(),
         };
 
         assert!(process_input_file(args));
@@ -397,28 +2720,877 @@ mod tests {
     #[test]
     fn reject_invalid_input_file() {
         let args = Arguments {
+            command: Command::Scale,
             levels: Levels::new(9, 15).unwrap(),
             input_file: Some(String::from("testdata/invalid_input_file.txt")),
+            relative: false,
+            options: Options::default(),
         };
 
         assert_eq!(process_input_file(args), false);
     }
 
+    #[test]
+    fn accept_valid_multi_creature_file() {
+        let args = Arguments {
+            command: Command::Scale,
+            levels: Levels::new(1, 5).unwrap(),
+            input_file: Some(String::from("testdata/valid_multi_creature_file.txt")),
+            relative: false,
+            options: Options::default(),
+        };
+
+        assert!(process_input_file(args));
+
+        let args = Arguments {
+            command: Command::Scale,
+            levels: Levels::new(1, 5).unwrap(),
+            input_file: Some(String::from("testdata/valid_multi_creature_file.txt")),
+            relative: true,
+            options: Options::default(),
+        };
+
+        assert!(process_input_file(args));
+    }
+
+    #[test]
+    fn reject_invalid_multi_creature_file() {
+        let args = Arguments {
+            command: Command::Scale,
+            levels: Levels::new(1, 5).unwrap(),
+            input_file: Some(String::from("testdata/invalid_multi_creature_file.txt")),
+            relative: false,
+            options: Options::default(),
+        };
+
+        assert!(!process_input_file(args));
+
+        // The level delta pushes the level 10 creature past the maximum level.
+        let args = Arguments {
+            command: Command::Scale,
+            levels: Levels::new(1, 24).unwrap(),
+            input_file: Some(String::from("testdata/valid_multi_creature_file.txt")),
+            relative: true,
+            options: Options::default(),
+        };
+
+        assert!(!process_input_file(args));
+    }
+
     #[test]
     fn reject_nonexistent_input_file() {
         let args = Arguments {
+            command: Command::Scale,
             levels: Levels::new(9, 15).unwrap(),
             input_file: Some(String::from("testdata/nonexistent_input_file.txt")),
+            relative: false,
+            options: Options::default(),
         };
 
         assert_eq!(process_input_file(args), false);
     }
 
+    #[test]
+    fn scale_with_snap() {
+        let levels = Levels::new(11, 2).unwrap();
+        let options = Options {
+            snap: true,
+            ..Options::default()
+        };
+
+        let result = handle_prompt(levels, &options, "ac 32").unwrap();
+        assert!(float_eq(result.stat.value, 18.0));
+        assert_eq!(result.proficiency, Proficiency::High);
+        assert_eq!(result.method, ScaleMethod::Snapped);
+
+        let result = handle_prompt(levels, &options, "strike-damage 2d10+10 slashing").unwrap();
+        assert_eq!(result.method, ScaleMethod::Snapped);
+    }
+
+    #[test]
+    fn scale_with_proportional_extrapolation() {
+        let levels = Levels::new(1, 20).unwrap();
+        let options = Options {
+            extrapolation: vec![(Some(StatType::HitPoints), Extrapolation::Proportional)],
+            ..Options::default()
+        };
+
+        let result = handle_prompt(levels, &options, "hp 52").unwrap();
+        assert!(float_eq(result.stat.value, 946.0));
+        assert_eq!(result.method, ScaleMethod::Extrapolated);
+
+        // Other statistics still use additive extrapolation.
+        let result = handle_prompt(levels, &options, "ac 25").unwrap();
+        assert!(float_eq(result.stat.value, 54.0));
+
+        // Without rules, each statistic uses the default of its group.
+        let options = Options::default();
+        for (kind, group) in [
+            (StatType::ArmorClass, 0),
+            (StatType::HitPoints, 1),
+            (StatType::Weakness, 1),
+            (StatType::LimitedAreaDamage, 2),
+        ] {
+            assert_eq!(
+                options.extrapolation_for(&kind),
+                DEFAULT_EXTRAPOLATION[group].1
+            );
+        }
+    }
+
+    #[test]
+    fn scale_with_custom_tables() {
+        let args = parse_args(&vec![
+            "",
+            "--tables",
+            "testdata/valid_table_file.txt",
+            "3",
+            "5",
+        ])
+        .unwrap();
+        assert!(args.options.tables.is_custom(TableKind::HitPoints));
+
+        // The custom table doubles the built-in HP values.
+        let result = handle_prompt(args.levels, &args.options, "hp 84").unwrap();
+        assert!(float_eq(result.stat.value, 144.0));
+        assert_eq!(result.proficiency, Proficiency::Moderate);
+        assert_eq!(result.method, ScaleMethod::Exact);
+
+        // Tables that aren't in the file stay built-in.
+        let result = handle_prompt(args.levels, &args.options, "ac 18").unwrap();
+        assert!(float_eq(result.stat.value, 21.0));
+    }
+
+    #[test]
+    fn scale_with_shift() {
+        let args = parse_args(&vec![
+            "", "--shift", "all=-1", "--shift", "ac=+1", "11", "2",
+        ])
+        .unwrap();
+
+        let result = handle_prompt(args.levels, &args.options, "ac 30").unwrap();
+        assert!(float_eq(result.stat.value, 18.0));
+        assert_eq!(result.proficiency, Proficiency::High);
+
+        let result = handle_prompt(args.levels, &args.options, "fortitude +21").unwrap();
+        assert!(float_eq(result.stat.value, 5.0));
+        assert_eq!(result.proficiency, Proficiency::Low);
+
+        // Inline shifts override the command line.
+        let result = handle_prompt(args.levels, &args.options, "ac 30 >extreme").unwrap();
+        assert!(float_eq(result.stat.value, 21.0));
+        assert_eq!(result.proficiency, Proficiency::Extreme);
+
+        let result = handle_prompt(
+            args.levels,
+            &args.options,
+            "strike-damage 2d8+9 piercing >high",
+        )
+        .unwrap();
+        assert!(float_eq(result.stat.value, 9.0));
+        assert_eq!(result.proficiency, Proficiency::High);
+    }
+
+    #[test]
+    fn scale_strike() {
+        let levels = Levels::new(5, 8).unwrap();
+        let options = Options::default();
+
+        let (attack, damage) =
+            handle_strike(levels, &options, "strike Jaws +15 2d10+9 piercing").unwrap();
+        assert_eq!(attack.stat.kind, StatType::StrikeAttackBonus);
+        assert!(float_eq(attack.stat.value, 20.0));
+        assert_eq!(attack.proficiency, Proficiency::High);
+        assert_eq!(damage.stat.kind, StatType::StrikeDamage);
+        assert!(float_eq(damage.stat.value, 28.0));
+        assert_eq!(damage.proficiency, Proficiency::Extreme);
+        assert_eq!(damage.method, ScaleMethod::Exact);
+
+        let (attack, _) = handle_strike(
+            levels,
+            &options,
+            "strike Claw +13 (agile, reach 10 feet) 2d8+7 slashing plus 1d6 fire",
+        )
+        .unwrap();
+        assert!(float_eq(attack.stat.value, 18.0));
+        assert_eq!(attack.proficiency, Proficiency::Moderate);
+
+        let result = handle_prompt(levels, &options, "strike tail +13 2d6+7 bludgeoning").unwrap();
+        assert_eq!(result.stat.kind, StatType::StrikeAttackBonus);
+
+        assert!(handle_strike(levels, &options, "strike tail 2d6+7 bludgeoning").is_none());
+        assert!(handle_prompt(levels, &options, "strike tail +13").is_none());
+    }
+
+    #[test]
+    fn scale_critical_traits() {
+        let levels = Levels::new(7, 14).unwrap();
+        let options = Options::default();
+
+        // The traits don't change the damage the table asks for.
+        let (_, plain) = handle_strike(levels, &options, "strike pick +19 2d8+9 piercing").unwrap();
+        let (_, deadly) = handle_strike(
+            levels,
+            &options,
+            "strike pick +19 (deadly d10, fatal d12) 2d8+9 piercing",
+        )
+        .unwrap();
+        assert!(float_eq(plain.stat.value, deadly.stat.value));
+
+        // Four weapon dice at the target level give deadly three dice.
+        assert_eq!(
+            scale_stat_block_line(
+                levels,
+                &options,
+                "Melee [one-action] pick +19 (deadly d10), Damage 2d8+9 piercing"
+            ),
+            "Melee [one-action] pick +30 (deadly 3d10), Damage 4d6+16 piercing"
+        );
+
+        let mut original = CombatStats::new();
+        let mut scaled = CombatStats::new();
+        let line = "strike pick +19 (deadly d10) 2d8+9 piercing";
+        collect(levels, &options, line, &mut original, &mut scaled);
+        assert_eq!(
+            original.strikes[0].critical_traits,
+            vec![CriticalTrait::Deadly(Dice { count: 1, size: 10 })]
+        );
+        assert_eq!(
+            scaled.strikes[0].critical_traits,
+            vec![CriticalTrait::Deadly(Dice { count: 3, size: 10 })]
+        );
+        assert!(float_eq(
+            scaled.strikes[0].critical_damage(),
+            2.0 * scaled.strikes[0].damage + 16.5
+        ));
+    }
+
+    #[test]
+    fn scale_spell_list() {
+        let levels = Levels::new(7, 11).unwrap();
+        let options = Options::default();
+
+        let line = "spells 4th: fireball; 3rd: slow; cantrips (4th): shield";
+        let shift = handle_spell_list(levels, line).unwrap();
+        assert_eq!(shift.old_top_rank, 4);
+        assert_eq!(shift.new_top_rank, 6);
+        assert!(handle_line(levels, &options, line).is_some());
+
+        assert!(handle_spell_list(levels, "spells fireball").is_none());
+        assert!(handle_line(levels, &options, "spells fireball").is_none());
+    }
+
+    #[test]
+    fn scale_stat_block_lines() {
+        let levels = Levels::new(10, 12).unwrap();
+        let options = Options::default();
+        let scale = |line: &str| scale_stat_block_line(levels, &options, line);
+
+        assert_eq!(
+            scale("AC 27; Fort +21, Ref +15, Will +18"),
+            "AC 30; Fort +24, Ref +17, Will +21"
+        );
+        assert_eq!(
+            scale("HP 190; Resistances cold 10; Weaknesses fire 5"),
+            "HP 233; Resistances cold 11; Weaknesses fire 6"
+        );
+        assert_eq!(
+            scale("Melee [one-action] jaws +19 [+15/+11] (reach 10 feet), Damage 2d12+17 piercing plus Grab"),
+            "Melee [one-action] jaws +22 [+18/+14] (reach 10 feet), Damage 3d10+18 piercing plus Grab"
+        );
+
+        assert!(handle_line(levels, &options, "Skills Acrobatics +14, Athletics +18").is_some());
+        assert!(handle_line(levels, &options, "Skills none").is_none());
+
+        let rows = compare(levels, &options, "HP 190; Resistances cold 10").unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].statistic, "resistance cold");
+        assert_eq!(rows[1].scaled, "11");
+
+        // Afflictions keep their onset, durations and conditions.
+        assert_eq!(
+            scale("Venom (poison) Saving Throw DC 29 Fortitude; Onset 1 round; Stage 1 4d6 poison and enfeebled 1 (1 round)"),
+            "Venom (poison) Saving Throw DC 32 Fortitude; Onset 1 round; Stage 1 4d4+7 poison and enfeebled 1 (1 round)"
+        );
+
+        // Stage damage scales on the strike damage table.
+        let rows = compare(
+            levels,
+            &options,
+            "Venom (poison) Saving Throw DC 29 Fortitude; Stage 1 4d6 poison",
+        )
+        .unwrap();
+        let prompt = compare(levels, &options, "strike-damage 4d6 poison").unwrap();
+        assert_eq!(rows[1].statistic, "strike-damage Venom stage 1");
+        assert_eq!(rows[1].scaled, prompt[0].scaled);
+        assert_eq!(rows[1].details, prompt[0].details);
+
+        let mut original = CombatStats::new();
+        let mut scaled = CombatStats::new();
+        let line = "Melee [one-action] claw +19 (agile), Damage 2d8+9 slashing";
+        collect(levels, &options, line, &mut original, &mut scaled);
+        assert_eq!(original.strikes.len(), 1);
+        assert!(original.strikes[0].agile);
+        assert!(float_eq(scaled.strikes[0].attack_bonus, 22.0));
+    }
+
+    #[test]
+    fn collect_combat_report_stats() {
+        let levels = Levels::new(5, 8).unwrap();
+        let options = Options::default();
+        let mut original = CombatStats::new();
+        let mut scaled = CombatStats::new();
+
+        for line in [
+            "strike Claw +15 (agile) 2d10+9 slashing",
+            "strike-attack +13",
+            "strike-damage 2d10+9 piercing",
+            "ability-dc poison 22",
+            "limited-area-damage 6d6 fire",
+            "ac 22",
+            "spells 3rd: fireball",
+        ] {
+            collect(levels, &options, line, &mut original, &mut scaled);
+        }
+
+        assert_eq!(original.strikes.len(), 2);
+        assert!(original.strikes[0].agile);
+        assert!(float_eq(original.strikes[0].attack_bonus, 15.0));
+        assert!(float_eq(scaled.strikes[0].attack_bonus, 20.0));
+        assert!(float_eq(scaled.strikes[0].damage, 28.0));
+        assert!(original.strikes[1].name.is_empty());
+        assert!(float_eq(scaled.strikes[1].attack_bonus, 18.0));
+
+        // The dice are kept for simulations, and rebuilt for the scaled damage.
+        assert_eq!(original.strikes[0].rolls[0].dice[0].size, 10);
+        assert_eq!(original.strikes[1].rolls.len(), 1);
+        assert_eq!(scaled.strikes[0].rolls.len(), 1);
+        assert!(scaled.strikes[0].rolls[0].average_value() <= scaled.strikes[0].damage);
+
+        assert_eq!(original.dcs[0].label, "ability-DC poison");
+        assert!(float_eq(original.dcs[0].dc, 22.0));
+
+        // Afflictions in stat block form have their DC collected too, with
+        // their name as written, like ability DCs.
+        collect(
+            levels,
+            &options,
+            "Giant Centipede Venom (poison) Saving Throw DC 14 Fortitude; Stage 1 1d8 poison (1 round)",
+            &mut original,
+            &mut scaled,
+        );
+        collect(
+            levels,
+            &options,
+            "ability-dc Breath Weapon 22",
+            &mut original,
+            &mut scaled,
+        );
+        assert_eq!(original.dcs[1].label, "ability-DC Giant Centipede Venom");
+        assert!(float_eq(original.dcs[1].dc, 14.0));
+        assert!(scaled.dcs[1].dc > 14.0);
+        assert_eq!(original.dcs[2].label, "ability-DC Breath Weapon");
+        assert!(float_eq(original.area_damage[0], 21.0));
+        assert_eq!(scaled.area_damage.len(), 1);
+    }
+
+    #[test]
+    fn process_input_file_with_report() {
+        let args = parse_args(&vec![
+            "",
+            "--report",
+            "1",
+            "5",
+            "testdata/valid_multi_creature_file.txt",
+        ])
+        .unwrap();
+        assert!(args.options.report);
+
+        assert!(process_input_file(args));
+    }
+
+    #[test]
+    fn process_input_file_with_party() {
+        let args = parse_args(&vec![
+            "",
+            "--party",
+            "testdata/valid_party_file.txt",
+            "1",
+            "5",
+            "testdata/valid_multi_creature_file.txt",
+        ])
+        .unwrap();
+        assert!(args.options.report);
+        assert_eq!(args.options.party.len(), 4);
+
+        assert!(process_input_file(args));
+    }
+
+    #[test]
+    fn parse_lore_and_custom_skill_prompts() {
+        for line in [
+            "lore warfare +12",
+            "lore (warfare) +12",
+            "Warfare Lore +12",
+            "lore  ( Warfare ) +12",
+        ] {
+            let prompt = parse_prompt(line).unwrap();
+            assert_eq!(
+                prompt.kind,
+                StatType::Skill(SkillType::Lore(String::from("warfare")))
+            );
+            assert_eq!(prompt.name(), "lore warfare");
+            assert!(float_eq(prompt.total_value(), 12.0));
+        }
+
+        // Subjects may be several words, or the name of a skill.
+        let prompt = parse_prompt("legal lore +12 >high").unwrap();
+        assert_eq!(prompt.name(), "lore legal");
+        let prompt = parse_prompt("lore river kingdoms +9").unwrap();
+        assert_eq!(prompt.name(), "lore river kingdoms");
+        let prompt = parse_prompt("nature lore +9").unwrap();
+        assert_eq!(prompt.name(), "lore nature");
+        let prompt = parse_prompt("lore +9").unwrap();
+        assert_eq!(prompt.kind, StatType::Skill(SkillType::Lore(String::new())));
+        assert_eq!(prompt.name(), "lore");
+
+        let prompt = parse_prompt("skill Pathfinding +10").unwrap();
+        assert_eq!(
+            prompt.kind,
+            StatType::Skill(SkillType::Custom(String::from("pathfinding")))
+        );
+        assert_eq!(prompt.name(), "skill pathfinding");
+        assert!(parse_prompt("skill +10").is_none());
+
+        // Warnings and comparisons name the custom skill.
+        let options = Options {
+            compare: true,
+            ..Options::default()
+        };
+        let levels = Levels::new(5, 5).unwrap();
+        let rows = compare(levels, &options, "Skills Athletics +12, Sailing +30").unwrap();
+        assert_eq!(rows[1].statistic, "skill sailing");
+        assert!(rows[1]
+            .details
+            .contains("warning: skill sailing 30 is too high"));
+        let rows = compare(levels, &options, "skill Sailing +12").unwrap();
+        assert_eq!(rows[0].statistic, "skill sailing");
+
+        // Rules for a custom skill apply to that skill only, and rules for
+        // "skill" to all of them.
+        let (kind, _) = parse_stat_rule("--shift", "skill Sailing=+1").unwrap();
+        let sailing = StatType::Skill(SkillType::Custom(String::from("sailing")));
+        assert_eq!(kind.as_ref(), Some(&sailing));
+        let (kind, _) = parse_stat_rule("--shift", "skill=+1").unwrap();
+        assert!(kind.unwrap().covers(&sailing));
+
+        // Custom skills scale on the skills table.
+        let levels = Levels::new(5, 10).unwrap();
+        let custom = handle_prompt(levels, &Options::default(), "skill pathfinding +12").unwrap();
+        let lore = handle_prompt(levels, &Options::default(), "warfare lore +12").unwrap();
+        assert!(float_eq(custom.stat.value, 19.0));
+        assert!(float_eq(lore.stat.value, 19.0));
+
+        // Rules for a Lore apply to that subject only, and rules for "lore" to all of them.
+        let mut options = Options::default();
+        let (kind, shift) = parse_stat_rule("--shift", "Warfare Lore=+1").unwrap();
+        options
+            .shifts
+            .push((kind, scaling::proficiency_shift(shift).unwrap()));
+        let warfare = handle_prompt(levels, &options, "lore warfare +12").unwrap();
+        let legal = handle_prompt(levels, &options, "lore legal +12").unwrap();
+        assert!(warfare.stat.value > legal.stat.value);
+        assert!(float_eq(legal.stat.value, 19.0));
+
+        options.shifts = vec![(
+            Some(StatType::Skill(SkillType::Lore(String::new()))),
+            ProficiencyShift::Columns(1),
+        )];
+        let legal = handle_prompt(levels, &options, "lore legal +12").unwrap();
+        assert!(float_eq(legal.stat.value, warfare.stat.value));
+    }
+
+    #[test]
+    fn parse_resistance_and_weakness_prompts() {
+        let prompt = parse_prompt("weakness cold iron 10").unwrap();
+        assert_eq!(prompt.kind, StatType::Weakness);
+        assert_eq!(prompt.label.as_deref(), Some("cold iron"));
+        assert!(float_eq(prompt.total_value(), 10.0));
+
+        let prompt = parse_prompt("resistance Physical 5 (except silver) >high").unwrap();
+        assert_eq!(prompt.label.as_deref(), Some("physical"));
+        assert_eq!(prompt.qualifier.as_deref(), Some("(except silver)"));
+        assert!(prompt.shift.is_some());
+        assert_eq!(prompt.name(), "resistance physical (except silver)");
+        assert_eq!(prompt.stat_kind(), StatType::BroadResistance);
+
+        let prompt = parse_prompt("resistance all 5").unwrap();
+        assert_eq!(prompt.label.as_deref(), Some("all"));
+
+        // The damage type is optional.
+        let prompt = parse_prompt("resistance 8").unwrap();
+        assert_eq!(prompt.label, None);
+        assert_eq!(prompt.name(), "resistance");
+
+        let error = parse_prompt_at("resistance fire ten").err().unwrap();
+        assert_eq!(error.span, Span::new(16, 19));
+    }
+
+    #[test]
+    fn scale_broad_and_many_resistances() {
+        let levels = Levels::new(5, 8).unwrap();
+        let options = Options::default();
+
+        let prompt = parse_prompt("resistance all 3").unwrap();
+        assert_eq!(prompt.stat_kind(), StatType::BroadResistance);
+        assert_eq!(prompt.name(), "resistance all");
+        let prompt = parse_prompt("broad-resistance all 3").unwrap();
+        assert_eq!(prompt.name(), "resistance all");
+        assert_eq!(
+            parse_prompt("resistance fire 3").unwrap().stat_kind(),
+            StatType::Resistance
+        );
+
+        // Broad resistances use the low end of the resistance table.
+        let broad = handle_prompt(levels, &options, "resistance physical 5").unwrap();
+        let typed = handle_prompt(levels, &options, "resistance fire 5").unwrap();
+        assert!(float_eq(broad.stat.value, 6.0));
+        assert!(broad.stat.value < typed.stat.value);
+
+        // Three typed resistances move to the low end of the table, up to
+        // the next creature.
+        let lines = [
+            "resistance fire 10",
+            "resistance all 5",
+            "resistance 5",
+            "HP 100; Resistances cold 10, acid 10",
+            "[creature imp level 1]",
+            "resistance fire 5",
+        ];
+        assert_eq!(count_typed_resistances(&lines), 4);
+
+        let low = (
+            Some(StatType::Resistance),
+            ProficiencyShift::To(Proficiency::Low),
+        );
+        assert!(creature_options(&options, &lines).shifts.contains(&low));
+        assert!(creature_options(&options, &lines[1..]).shifts.is_empty());
+        assert!(creature_options(&options, &lines[5..]).shifts.is_empty());
+
+        // Shifts from the command line still win.
+        let mut options = Options::default();
+        options.shifts.push((None, ProficiencyShift::Columns(1)));
+        let options = creature_options(&options, &lines);
+        assert_eq!(
+            options.shift_for(&StatType::Resistance),
+            Some(ProficiencyShift::Columns(1))
+        );
+
+        // The low end never takes a resistance below where it started while
+        // the creature levels up.
+        let lines = ["HP 190; Resistances negative 10, evil 5, fire 5"];
+        let options = creature_options(&Options::default(), &lines);
+        let prompt = parse_prompt("resistance negative 10").unwrap();
+        let (result, notes) = scale_prompt(levels, &options, &prompt);
+        assert!(float_eq(result.stat.value, 10.0));
+        assert!(notes[0].ends_with(", held at 10"));
+        let prompt = parse_prompt("resistance fire 5").unwrap();
+        let (result, _) = scale_prompt(levels, &options, &prompt);
+        assert!(float_eq(result.stat.value, 6.0));
+
+        // Broad resistances are printed with the keyword they were written with.
+        let options = Options {
+            compare: true,
+            ..Options::default()
+        };
+        let rows = compare(levels, &options, "HP 100; Resistances all 5").unwrap();
+        assert_eq!(rows[1].statistic, "resistance all");
+        let rows = compare(Levels::new(5, 5).unwrap(), &options, "resistance all 20").unwrap();
+        assert!(rows[0].details.contains("warning: resistance 20"));
+        assert!(!rows[0].details.contains("broad-resistance"));
+    }
+
+    #[test]
+    fn point_at_prompt_errors() {
+        let span = |line: &str| parse_prompt_at(line).err().unwrap().span;
+
+        assert_eq!(span("hp"), Span::new(0, 2));
+        assert_eq!(span("  perceptoin +12"), Span::new(2, 12));
+        assert_eq!(span("ac  2x"), Span::new(4, 6));
+        assert_eq!(span("ac 20 >legendary"), Span::new(6, 16));
+        assert_eq!(span("ability-dc 27"), Span::new(11, 13));
+        assert_eq!(span("ability-dc breath 2x"), Span::new(18, 20));
+        assert_eq!(span("Strike-Damage 2dd6 fire"), Span::new(14, 18));
+        assert_eq!(span("dmg 2d6+4 fire plus 1d6"), Span::new(20, 23));
+
+        // Parsing itself is case-insensitive, but the label of a DC is kept as written.
+        let prompt = parse_prompt_at("Ability-DC Breath 27 >High").unwrap();
+        assert_eq!(prompt.kind, StatType::AbilityDC);
+        assert_eq!(prompt.label.as_deref(), Some("Breath"));
+        assert!(prompt.shift.is_some());
+    }
+
+    #[test]
+    fn stat_kind_names_are_unique() {
+        let mut names: Vec<&str> = stat_kind_names().collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), STAT_KINDS.len());
+    }
+
+    #[test]
+    fn suggest_statistics() {
+        let help = |line: &str| parse_prompt_at(line).err().unwrap().help.unwrap();
+
+        assert_eq!(help("perceptoin +12"), "did you mean perception?");
+        assert_eq!(help("strke-damage 2d6 fire"), "did you mean strike-damage?");
+        assert_eq!(help("strke jaws +19 2d6 fire"), "did you mean strike?");
+        assert_eq!(help("xyz 12"), "usage: <statistic> <current_value>");
+    }
+
+    #[test]
+    fn correct_mistyped_lines() {
+        assert_eq!(correct_line("relfex +12").as_deref(), Some("reflex +12"));
+        assert_eq!(
+            correct_line("dmg 2d6+4 peircing plus 1d6 fier").as_deref(),
+            Some("dmg 2d6+4 piercing plus 1d6 fire")
+        );
+        assert_eq!(
+            correct_line("strike jaws +19 (agile) 2d6 slahsing >+1").as_deref(),
+            Some("strike jaws +19 (agile) 2d6 slashing >+1")
+        );
+
+        assert_eq!(correct_line("reflex +12"), None);
+        assert_eq!(correct_line("dmg 2d6 fire"), None);
+        assert_eq!(correct_line("spells 1st: void"), None);
+        assert_eq!(correct_line("xyz 12"), None);
+        assert_eq!(correct_line("fort lore +12"), None);
+    }
+
+    #[test]
+    fn query_lines() {
+        assert_eq!(
+            query_line("strike-?").unwrap(),
+            vec!["strike-attack", "strike-damage"]
+        );
+        assert_eq!(query_line("?").unwrap().len(), STAT_KINDS.len() + 2);
+        assert_eq!(
+            query_line("dmg 2d6 bl?").unwrap(),
+            vec!["bleed", "bludgeoning"]
+        );
+        assert_eq!(
+            query_line("dmg 2d6 ?").unwrap().len(),
+            damage_types::KNOWN_DAMAGE_TYPES.len()
+        );
+        assert!(query_line("ac 2?").unwrap().is_empty());
+        assert_eq!(query_line("ac 20"), None);
+    }
+
+    #[test]
+    fn convert_legacy_damage_types() {
+        let levels = Levels::new(5, 8).unwrap();
+        let mut options = Options::default();
+        let line = "dmg 2d6+4 slashing plus 1d6 negative plus 1d4 good";
+
+        let rows = compare(levels, &options, line).unwrap();
+        assert!(rows[0].scaled.contains("negative"));
+
+        options.remaster = true;
+        let rows = compare(levels, &options, line).unwrap();
+        assert_eq!(
+            rows[0].original,
+            "2d6+4 slashing plus 1d6 negative plus 1d4 good"
+        );
+        assert!(rows[0].scaled.contains(" slashing plus "));
+        assert!(rows[0].scaled.contains(" void plus "));
+        assert!(rows[0].scaled.ends_with(" spirit"));
+
+        let damage = damage::parse_damage("1d6 positive").unwrap();
+        let scaled = scale_damage(levels, &damage, 7.0, &options);
+        assert_eq!(scaled.components[0].damage_type, "vitality");
+
+        // The types of resistances and weaknesses are converted too.
+        let prompt = parse_prompt("weakness good 10").unwrap();
+        let label = scaled_label(&options, &prompt.kind, prompt.label.as_deref());
+        assert_eq!(label.as_deref(), Some("spirit"));
+        let prompt = parse_prompt("resistance negative 5").unwrap();
+        let label = scaled_label(&options, &prompt.kind, prompt.label.as_deref());
+        assert_eq!(label.as_deref(), Some("void"));
+
+        let line = "HP 90; Resistances fire 5; Weaknesses Good 10";
+        let scaled = scale_stat_block_line(levels, &options, line);
+        assert!(scaled.ends_with("; Weaknesses spirit 13"));
+        assert!(scaled.contains("Resistances fire "));
+
+        // The comparison names them the same way.
+        let rows = compare(
+            levels,
+            &options,
+            "HP 90; Resistances negative 5; Weaknesses good 10",
+        )
+        .unwrap();
+        assert_eq!(rows[1].statistic, "resistance void");
+        assert_eq!(rows[2].statistic, "weakness spirit");
+        let rows = compare(levels, &options, "weakness good 10 (except holy water)").unwrap();
+        assert_eq!(rows[0].statistic, "weakness spirit (except holy water)");
+
+        options.remaster = false;
+        let scaled = scale_stat_block_line(levels, &options, line);
+        assert!(scaled.ends_with("; Weaknesses Good 13"));
+        let rows = compare(levels, &options, "weakness good 10").unwrap();
+        assert_eq!(rows[0].statistic, "weakness good");
+    }
+
+    #[test]
+    fn compare_lines() {
+        let levels = Levels::new(5, 8).unwrap();
+        let options = Options::default();
+
+        let rows = compare(levels, &options, "ac 22").unwrap();
+        assert_eq!(rows[0].statistic, "AC");
+        assert_eq!(rows[0].original, "22");
+        assert_eq!(rows[0].scaled, "27");
+        assert_eq!(rows[0].delta, "+5");
+        assert_eq!(rows[0].details, "High, Exact");
+        assert!(rows[0].highlight.is_none());
+
+        let rows = compare(levels, &options, "strike jaws +15 2d10+9 piercing").unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].statistic, "strike jaws attack");
+        assert_eq!(rows[0].original, "+15");
+        assert_eq!(rows[1].original, "2d10+9 piercing");
+        assert!(rows[1].scaled.ends_with(" piercing"));
+
+        let rows = compare(levels, &options, "ability-dc poison 22").unwrap();
+        assert_eq!(rows[0].statistic, "ability-DC poison");
+
+        let rows = compare(levels, &options, "spells 3rd: fireball").unwrap();
+        assert_eq!(rows[0].original, "3rd");
+        assert_eq!(rows[0].scaled, "4th");
+        assert_eq!(rows[0].delta, "+1");
+
+        // Values far off the table are extrapolated and highlighted.
+        let rows = compare(levels, &options, "hp 500").unwrap();
+        assert_eq!(rows[0].highlight, Some(Color::BrightYellow));
+
+        // Large jumps are highlighted too.
+        let levels = Levels::new(1, 10).unwrap();
+        let rows = compare(levels, &options, "hp 20").unwrap();
+        assert_eq!(rows[0].highlight, Some(Color::BrightRed));
+
+        assert!(compare(levels, &options, "ac x").is_none());
+
+        // Warnings about a statistic are printed in its row.
+        let options = Options {
+            compare: true,
+            ..Options::default()
+        };
+        let levels = Levels::new(4, 6).unwrap();
+        let rows = compare(levels, &options, "HP 90; Resistances fire 5").unwrap();
+        assert!(rows[0]
+            .details
+            .ends_with("(warning: HP 90 is too high for a level 4 creature: maximum 78)"));
+        assert!(!rows[1].details.contains("warning"));
+    }
+
+    #[test]
+    fn scale_lines_down_to_level_minus_2() {
+        let levels = Levels::new(1, -2).unwrap();
+        let options = Options::default();
+
+        for line in [
+            "strike-damage 1d4 piercing",
+            "unlimited-area-damage 1d4 fire",
+            "limited-area-damage 1d4 fire",
+            "strike claw +5 1d4 piercing",
+        ] {
+            let rows = compare(levels, &options, line).unwrap();
+            assert!(rows.last().unwrap().scaled.starts_with("1 "), "{}", line);
+        }
+
+        let rows = compare(levels, &options, "hp 5").unwrap();
+        assert_eq!(rows[0].scaled, "1");
+
+        let levels = Levels::new(5, -2).unwrap();
+        assert!(compare(
+            levels,
+            &options,
+            "strike-damage 2d6 fire plus 1d6 precision"
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn format_compare_table_with_comments() {
+        let levels = Levels::new(5, 8).unwrap();
+        let options = Options::default();
+        let mut lines = vec![CompareLine::Comment(String::from("# defense"))];
+        for line in ["ac 22", "hp 100"] {
+            let rows = compare(levels, &options, line).unwrap();
+            lines.extend(rows.into_iter().map(CompareLine::Row));
+        }
+        lines.insert(2, CompareLine::Comment(String::from("# fire")));
+
+        let table = format_compare_table(&lines);
+        assert_eq!(table.len(), 5);
+        assert!(table[0].starts_with("statistic  original"));
+        assert_eq!(table[1], "# defense");
+        assert!(table[2].starts_with("AC         22"));
+        assert_eq!(table[3], "# fire");
+        assert!(table[4].starts_with("HP         100"));
+
+        // Comments alone don't make a table.
+        let table = format_compare_table(&lines[..1]);
+        assert_eq!(table, vec![String::from("# defense")]);
+    }
+
+    #[test]
+    fn process_input_file_with_compare() {
+        let args = parse_args(&vec![
+            "",
+            "--compare",
+            "1",
+            "5",
+            "testdata/valid_multi_creature_file.txt",
+        ])
+        .unwrap();
+        assert!(args.options.compare);
+
+        assert!(process_input_file(args));
+    }
+
+    #[test]
+    fn process_input_file_with_simulation() {
+        let args = parse_args(&vec![
+            "",
+            "--simulate",
+            "100",
+            "--seed",
+            "7",
+            "5",
+            "8",
+            "testdata/valid_input_file.txt",
+        ])
+        .unwrap();
+        assert!(args.options.report);
+        assert_eq!(args.options.simulate, Some(100));
+        assert_eq!(args.options.seed, Some(7));
+
+        assert!(process_input_file(args));
+
+        // Seeds may be 0 and go beyond u32.
+        let args = parse_args(&vec!["", "--seed", "0", "1", "2"]).unwrap();
+        assert_eq!(args.options.seed, Some(0));
+        let args = parse_args(&vec!["", "--seed", "18446744073709551615", "1", "2"]).unwrap();
+        assert_eq!(args.options.seed, Some(u64::MAX));
+    }
+
     #[test]
     fn scale_perception() {
         let levels = Levels::new(19, 15).unwrap();
+        let options = Options::default();
 
-        let result = handle_prompt(levels, "perception +29").unwrap();
+        let result = handle_prompt(levels, &options, "perception +29").unwrap();
         assert_eq!(result.stat.kind, StatType::Perception);
         assert!(float_eq(result.stat.value, 23.0));
         assert_eq!(result.proficiency, Proficiency::Low);
@@ -428,104 +3600,108 @@ mod tests {
     #[test]
     fn scale_skill() {
         let levels = Levels::new(3, 4).unwrap();
+        let options = Options::default();
 
-        let result = handle_prompt(levels, "acrobatics +13").unwrap();
+        let result = handle_prompt(levels, &options, "acrobatics +13").unwrap();
         assert_eq!(result.stat.kind, StatType::Skill(SkillType::Acrobatics));
         assert!(float_eq(result.stat.value, 15.0));
         assert_eq!(result.proficiency, Proficiency::Extreme);
         assert_eq!(result.method, ScaleMethod::Exact);
 
-        let result = handle_prompt(levels, "arcana +7").unwrap();
+        let result = handle_prompt(levels, &options, "arcana +7").unwrap();
         assert_eq!(result.stat.kind, StatType::Skill(SkillType::Arcana));
         assert!(float_eq(result.stat.value, 8.0));
         assert_eq!(result.proficiency, Proficiency::Low);
         assert_eq!(result.method, ScaleMethod::Exact);
 
-        let result = handle_prompt(levels, "athletics +10").unwrap();
+        let result = handle_prompt(levels, &options, "athletics +10").unwrap();
         assert_eq!(result.stat.kind, StatType::Skill(SkillType::Athletics));
         assert!(float_eq(result.stat.value, 12.0));
         assert_eq!(result.proficiency, Proficiency::High);
         assert_eq!(result.method, ScaleMethod::Exact);
 
-        let result = handle_prompt(levels, "crafting +10").unwrap();
+        let result = handle_prompt(levels, &options, "crafting +10").unwrap();
         assert_eq!(result.stat.kind, StatType::Skill(SkillType::Crafting));
         assert!(float_eq(result.stat.value, 12.0));
         assert_eq!(result.proficiency, Proficiency::High);
         assert_eq!(result.method, ScaleMethod::Exact);
 
-        let result = handle_prompt(levels, "deception +13").unwrap();
+        let result = handle_prompt(levels, &options, "deception +13").unwrap();
         assert_eq!(result.stat.kind, StatType::Skill(SkillType::Deception));
         assert!(float_eq(result.stat.value, 15.0));
         assert_eq!(result.proficiency, Proficiency::Extreme);
         assert_eq!(result.method, ScaleMethod::Exact);
 
-        let result = handle_prompt(levels, "diplomacy +13").unwrap();
+        let result = handle_prompt(levels, &options, "diplomacy +13").unwrap();
         assert_eq!(result.stat.kind, StatType::Skill(SkillType::Diplomacy));
         assert!(float_eq(result.stat.value, 15.0));
         assert_eq!(result.proficiency, Proficiency::Extreme);
         assert_eq!(result.method, ScaleMethod::Exact);
 
-        let result = handle_prompt(levels, "intimidation +9").unwrap();
+        let result = handle_prompt(levels, &options, "intimidation +9").unwrap();
         assert_eq!(result.stat.kind, StatType::Skill(SkillType::Intimidation));
         assert!(float_eq(result.stat.value, 10.0));
         assert_eq!(result.proficiency, Proficiency::Moderate);
         assert_eq!(result.method, ScaleMethod::Exact);
 
-        let result = handle_prompt(levels, "lore +7").unwrap();
-        assert_eq!(result.stat.kind, StatType::Skill(SkillType::Lore));
+        let result = handle_prompt(levels, &options, "lore +7").unwrap();
+        assert_eq!(
+            result.stat.kind,
+            StatType::Skill(SkillType::Lore(String::new()))
+        );
         assert!(float_eq(result.stat.value, 8.0));
         assert_eq!(result.proficiency, Proficiency::Low);
         assert_eq!(result.method, ScaleMethod::Exact);
 
-        let result = handle_prompt(levels, "medicine +13").unwrap();
+        let result = handle_prompt(levels, &options, "medicine +13").unwrap();
         assert_eq!(result.stat.kind, StatType::Skill(SkillType::Medicine));
         assert!(float_eq(result.stat.value, 15.0));
         assert_eq!(result.proficiency, Proficiency::Extreme);
         assert_eq!(result.method, ScaleMethod::Exact);
 
-        let result = handle_prompt(levels, "nature +9").unwrap();
+        let result = handle_prompt(levels, &options, "nature +9").unwrap();
         assert_eq!(result.stat.kind, StatType::Skill(SkillType::Nature));
         assert!(float_eq(result.stat.value, 10.0));
         assert_eq!(result.proficiency, Proficiency::Moderate);
         assert_eq!(result.method, ScaleMethod::Exact);
 
-        let result = handle_prompt(levels, "occultism +13").unwrap();
+        let result = handle_prompt(levels, &options, "occultism +13").unwrap();
         assert_eq!(result.stat.kind, StatType::Skill(SkillType::Occultism));
         assert!(float_eq(result.stat.value, 15.0));
         assert_eq!(result.proficiency, Proficiency::Extreme);
         assert_eq!(result.method, ScaleMethod::Exact);
 
-        let result = handle_prompt(levels, "performance +5").unwrap();
+        let result = handle_prompt(levels, &options, "performance +5").unwrap();
         assert_eq!(result.stat.kind, StatType::Skill(SkillType::Performance));
         assert!(float_eq(result.stat.value, 7.0));
         assert_eq!(result.proficiency, Proficiency::Low);
         assert_eq!(result.method, ScaleMethod::Exact);
 
-        let result = handle_prompt(levels, "religion +5").unwrap();
+        let result = handle_prompt(levels, &options, "religion +5").unwrap();
         assert_eq!(result.stat.kind, StatType::Skill(SkillType::Religion));
         assert!(float_eq(result.stat.value, 7.0));
         assert_eq!(result.proficiency, Proficiency::Low);
         assert_eq!(result.method, ScaleMethod::Exact);
 
-        let result = handle_prompt(levels, "society +9").unwrap();
+        let result = handle_prompt(levels, &options, "society +9").unwrap();
         assert_eq!(result.stat.kind, StatType::Skill(SkillType::Society));
         assert!(float_eq(result.stat.value, 10.0));
         assert_eq!(result.proficiency, Proficiency::Moderate);
         assert_eq!(result.method, ScaleMethod::Exact);
 
-        let result = handle_prompt(levels, "stealth +7").unwrap();
+        let result = handle_prompt(levels, &options, "stealth +7").unwrap();
         assert_eq!(result.stat.kind, StatType::Skill(SkillType::Stealth));
         assert!(float_eq(result.stat.value, 8.0));
         assert_eq!(result.proficiency, Proficiency::Low);
         assert_eq!(result.method, ScaleMethod::Exact);
 
-        let result = handle_prompt(levels, "survival +5").unwrap();
+        let result = handle_prompt(levels, &options, "survival +5").unwrap();
         assert_eq!(result.stat.kind, StatType::Skill(SkillType::Survival));
         assert!(float_eq(result.stat.value, 7.0));
         assert_eq!(result.proficiency, Proficiency::Low);
         assert_eq!(result.method, ScaleMethod::Exact);
 
-        let result = handle_prompt(levels, "thievery +5").unwrap();
+        let result = handle_prompt(levels, &options, "thievery +5").unwrap();
         assert_eq!(result.stat.kind, StatType::Skill(SkillType::Thievery));
         assert!(float_eq(result.stat.value, 7.0));
         assert_eq!(result.proficiency, Proficiency::Low);
@@ -535,8 +3711,9 @@ mod tests {
     #[test]
     fn scale_armor_class() {
         let levels = Levels::new(3, 14).unwrap();
+        let options = Options::default();
 
-        let result = handle_prompt(levels, "ac 18").unwrap();
+        let result = handle_prompt(levels, &options, "ac 18").unwrap();
         assert_eq!(result.stat.kind, StatType::ArmorClass);
         assert!(float_eq(result.stat.value, 35.0));
         assert_eq!(result.proficiency, Proficiency::Moderate);
@@ -547,20 +3724,21 @@ mod tests {
     #[test]
     fn scale_saving_throw() {
         let levels = Levels::new(6, 0).unwrap();
+        let options = Options::default();
 
-        let result = handle_prompt(levels, "fortitude +11").unwrap();
+        let result = handle_prompt(levels, &options, "fortitude +11").unwrap();
         assert_eq!(result.stat.kind, StatType::SavingThrow(SavingThrowType::Fortitude));
         assert!(float_eq(result.stat.value, 3.0));
         assert_eq!(result.proficiency, Proficiency::Low);
         assert_eq!(result.method, ScaleMethod::Exact);
 
-        let result = handle_prompt(levels, "reflex +17").unwrap();
+        let result = handle_prompt(levels, &options, "reflex +17").unwrap();
         assert_eq!(result.stat.kind, StatType::SavingThrow(SavingThrowType::Reflex));
         assert!(float_eq(result.stat.value, 9.0));
         assert_eq!(result.proficiency, Proficiency::High);
         assert_eq!(result.method, ScaleMethod::Exact);
 
-        let result = handle_prompt(levels, "will +14").unwrap();
+        let result = handle_prompt(levels, &options, "will +14").unwrap();
         assert_eq!(result.stat.kind, StatType::SavingThrow(SavingThrowType::Will));
         assert!(float_eq(result.stat.value, 6.0));
         assert_eq!(result.proficiency, Proficiency::Moderate);
@@ -570,8 +3748,9 @@ mod tests {
     #[test]
     fn scale_hit_points() {
         let levels = Levels::new(24, 10).unwrap();
+        let options = Options::default();
 
-        let result = handle_prompt(levels, "hp 367").unwrap();
+        let result = handle_prompt(levels, &options, "hp 367").unwrap();
         assert_eq!(result.stat.kind, StatType::HitPoints);
         assert!(float_eq(result.stat.value, 127.0));
         assert_eq!(result.proficiency, Proficiency::Low);
@@ -581,8 +3760,9 @@ mod tests {
     #[test]
     fn scale_resistance() {
         let levels = Levels::new(7, 12).unwrap();
+        let options = Options::default();
 
-        let result = handle_prompt(levels, "resistance 10").unwrap();
+        let result = handle_prompt(levels, &options, "resistance 10").unwrap();
         assert_eq!(result.stat.kind, StatType::Resistance);
         assert!(float_eq(result.stat.value, 15.0));
         assert_eq!(result.proficiency, Proficiency::High);
@@ -592,8 +3772,9 @@ mod tests {
     #[test]
     fn scale_weakness() {
         let levels = Levels::new(8, 23).unwrap();
+        let options = Options::default();
 
-        let result = handle_prompt(levels, "weakness 6").unwrap();
+        let result = handle_prompt(levels, &options, "weakness 6").unwrap();
         assert_eq!(result.stat.kind, StatType::Weakness);
         assert!(float_eq(result.stat.value, 13.0));
         assert_eq!(result.proficiency, Proficiency::Low);
@@ -603,8 +3784,9 @@ mod tests {
     #[test]
     fn scale_strike_attack_bonus() {
         let levels = Levels::new(11, 19).unwrap();
+        let options = Options::default();
 
-        let result = handle_prompt(levels, "strike-attack +24").unwrap();
+        let result = handle_prompt(levels, &options, "strike-attack +24").unwrap();
         assert_eq!(result.stat.kind, StatType::StrikeAttackBonus);
         assert!(float_eq(result.stat.value, 36.0));
         assert_eq!(result.proficiency, Proficiency::High);
@@ -614,30 +3796,85 @@ mod tests {
     #[test]
     fn scale_strike_damage() {
         let levels = Levels::new(7, 17).unwrap();
+        let options = Options::default();
 
-        let result = handle_prompt(levels, "strike-damage 2d12+12 piercing").unwrap();
+        let result = handle_prompt(levels, &options, "strike-damage 2d12+12 piercing").unwrap();
         assert_eq!(result.stat.kind, StatType::StrikeDamage);
         assert!(float_eq(result.stat.value, 50.0));
         assert_eq!(result.proficiency, Proficiency::Extreme);
         assert_eq!(result.method, ScaleMethod::Exact);
+
+        // Precision and conditional damage don't change the lookup.
+        let conditional = handle_prompt(
+            levels,
+            &options,
+            "strike-damage 2d12+12 piercing plus 2d6 precision plus 1d6 fire on a critical hit",
+        )
+        .unwrap();
+        assert!(float_eq(conditional.stat.value, result.stat.value));
+        assert_eq!(conditional.proficiency, result.proficiency);
+
+        let damage = damage::parse_damage("2d12+12 piercing plus 2d6 precision").unwrap();
+        assert_eq!(damage_rolls(&damage).len(), 1);
+        assert_eq!(
+            scaled_damage_rolls(levels, &damage, &result, &options).len(),
+            1
+        );
+        let expressions = scaled_damage_expressions(levels, &damage, &result, &options);
+        assert_eq!(expressions[1].1, "precision");
+
+        // Conditional damage keeps its dice, and damage that is only
+        // conditional has nothing to look up.
+        let levels = Levels::new(10, 15).unwrap();
+        let rows = compare(
+            levels,
+            &options,
+            "strike claw +20 (agile) 2d8+10 slashing plus 1d6 precision",
+        )
+        .unwrap();
+        assert!(rows[1].scaled.ends_with(" plus 2d6 precision"));
+        assert!(compare(levels, &options, "strike-damage 1d6 precision").is_none());
     }
 
     #[test]
     fn scale_spell_dc() {
         let levels = Levels::new(22, 20).unwrap();
+        let options = Options::default();
 
-        let result = handle_prompt(levels, "spell-dc 50").unwrap();
+        let result = handle_prompt(levels, &options, "spell-dc 50").unwrap();
         assert_eq!(result.stat.kind, StatType::SpellDC);
         assert!(float_eq(result.stat.value, 47.0));
         assert_eq!(result.proficiency, Proficiency::Extreme);
         assert_eq!(result.method, ScaleMethod::Exact);
     }
 
+    #[test]
+    fn scale_ability_dc() {
+        let levels = Levels::new(22, 20).unwrap();
+        let options = Options::default();
+
+        let result = handle_prompt(levels, &options, "ability-dc breath-weapon 50").unwrap();
+        assert_eq!(result.stat.kind, StatType::AbilityDC);
+        assert!(float_eq(result.stat.value, 47.0));
+        assert_eq!(result.proficiency, Proficiency::Extreme);
+        assert_eq!(result.method, ScaleMethod::Exact);
+
+        let prompt = parse_prompt("ability-dc Frightful Presence 50 >high").unwrap();
+        assert_eq!(prompt.label.as_deref(), Some("Frightful Presence"));
+        assert_eq!(prompt.name(), "ability-DC Frightful Presence");
+        assert!(float_eq(prompt.total_value(), 50.0));
+        assert!(prompt.shift.is_some());
+
+        assert!(handle_prompt(levels, &options, "ability-dc 50").is_none());
+        assert!(handle_prompt(levels, &options, "ability-dc poison x").is_none());
+    }
+
     #[test]
     fn scale_spell_attack_bonus() {
         let levels = Levels::new(12, 5).unwrap();
+        let options = Options::default();
 
-        let result = handle_prompt(levels, "spell-attack +21").unwrap();
+        let result = handle_prompt(levels, &options, "spell-attack +21").unwrap();
         assert_eq!(result.stat.kind, StatType::SpellAttackBonus);
         assert!(float_eq(result.stat.value, 11.0));
         assert_eq!(result.proficiency, Proficiency::Moderate);
@@ -647,8 +3884,9 @@ mod tests {
     #[test]
     fn scale_unlimited_area_damage() {
         let levels = Levels::new(6, 14).unwrap();
+        let options = Options::default();
 
-        let result = handle_prompt(levels, "unlimited-area-damage 4d6 fire").unwrap();
+        let result = handle_prompt(levels, &options, "unlimited-area-damage 4d6 fire").unwrap();
         assert_eq!(result.stat.kind, StatType::UnlimitedAreaDamage);
         assert!(float_eq(result.stat.value, 26.0));
         assert_eq!(result.proficiency, Proficiency::Moderate);
@@ -658,8 +3896,9 @@ mod tests {
     #[test]
     fn scale_limited_area_damage() {
         let levels = Levels::new(17, 12).unwrap();
+        let options = Options::default();
 
-        let result = handle_prompt(levels, "limited-area-damage 18d6 cold").unwrap();
+        let result = handle_prompt(levels, &options, "limited-area-damage 18d6 cold").unwrap();
         assert_eq!(result.stat.kind, StatType::LimitedAreaDamage);
         assert!(float_eq(result.stat.value, 46.0));
         assert_eq!(result.proficiency, Proficiency::Moderate);
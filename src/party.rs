@@ -0,0 +1,296 @@
+use crate::logging::{self, LogLevel};
+use std::fs;
+
+// A party file describes the player characters a creature is tuned against,
+// one section per PC:
+//
+// [pc Valeros level 5]
+// ac 25
+// fortitude +14
+// reflex +11
+// will +9
+// perception +11
+// hp 75
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pc {
+    pub name: String,
+    pub level: i32,
+    pub ac: f64,
+    pub fortitude: f64,
+    pub reflex: f64,
+    pub will: f64,
+    pub perception: f64,
+    pub hp: f64,
+}
+
+// The statistics of a PC section, filled in line by line.
+#[derive(Default)]
+struct PendingPc {
+    name: String,
+    level: i32,
+    ac: Option<f64>,
+    fortitude: Option<f64>,
+    reflex: Option<f64>,
+    will: Option<f64>,
+    perception: Option<f64>,
+    hp: Option<f64>,
+}
+
+fn log_line_error<T: Into<String> + std::fmt::Display>(line_number: usize, msg: T) {
+    logging::log(
+        LogLevel::Error,
+        format!("party file line {}: {}", line_number, msg),
+    );
+}
+
+// Parse a [pc <name> level <level>] header. The name may contain spaces.
+fn parse_header(line_number: usize, line: &str) -> Option<PendingPc> {
+    let inner = line
+        .strip_prefix('[')
+        .and_then(|l| l.strip_suffix(']'))
+        .map(|l| l.trim())
+        .unwrap_or_default();
+
+    let parsed = inner.split_once(' ').and_then(|(keyword, rest)| {
+        let (rest, level) = rest.trim().rsplit_once(' ')?;
+        let (name, level_keyword) = rest.trim().rsplit_once(' ')?;
+        let name = name.trim();
+
+        let valid = keyword.eq_ignore_ascii_case("pc")
+            && level_keyword.eq_ignore_ascii_case("level")
+            && !name.is_empty();
+
+        valid.then_some((name, level))
+    });
+
+    let (name, level) = match parsed {
+        Some(p) => p,
+        None => {
+            log_line_error(line_number, format!("invalid PC header: {}", line));
+            logging::log(LogLevel::Info, "usage: [pc <name> level <level>]");
+            return None;
+        }
+    };
+
+    let level: i32 = match level.parse() {
+        Ok(l) => l,
+        Err(_) => {
+            log_line_error(
+                line_number,
+                format!("PC level is not a valid integer: {}", level),
+            );
+            return None;
+        }
+    };
+
+    Some(PendingPc {
+        name: String::from(name),
+        level,
+        ..Default::default()
+    })
+}
+
+fn parse_statistic(line_number: usize, line: &str, pc: &mut PendingPc) -> Option<()> {
+    let (kind, value) = match line.split_once(' ') {
+        Some(s) => s,
+        None => {
+            log_line_error(
+                line_number,
+                format!("expected <statistic> <value>, got {}", line),
+            );
+            return None;
+        }
+    };
+
+    let value: f64 = match value.trim().parse::<i32>() {
+        Ok(v) => v.into(),
+        Err(_) => {
+            log_line_error(
+                line_number,
+                format!("value is not a valid integer: {}", value.trim()),
+            );
+            return None;
+        }
+    };
+
+    let field = match kind.to_lowercase().as_str() {
+        "ac" => &mut pc.ac,
+        "fortitude" | "fort" => &mut pc.fortitude,
+        "reflex" | "ref" => &mut pc.reflex,
+        "will" => &mut pc.will,
+        "perception" | "per" => &mut pc.perception,
+        "hp" => &mut pc.hp,
+        _ => {
+            log_line_error(line_number, format!("unknown PC statistic: {}", kind));
+            logging::log(
+                LogLevel::Info,
+                "PC statistics: ac, fortitude, reflex, will, perception, hp",
+            );
+            return None;
+        }
+    };
+
+    if field.is_some() {
+        log_line_error(line_number, format!("duplicate PC statistic: {}", kind));
+        return None;
+    }
+
+    *field = Some(value);
+    Some(())
+}
+
+fn finish_pc(pending: PendingPc) -> Option<Pc> {
+    let missing: Vec<&str> = [
+        ("ac", pending.ac),
+        ("fortitude", pending.fortitude),
+        ("reflex", pending.reflex),
+        ("will", pending.will),
+        ("perception", pending.perception),
+        ("hp", pending.hp),
+    ]
+    .into_iter()
+    .filter(|(_, v)| v.is_none())
+    .map(|(name, _)| name)
+    .collect();
+
+    if !missing.is_empty() {
+        logging::log(
+            LogLevel::Error,
+            format!(
+                "party file: PC {} is missing {}",
+                pending.name,
+                missing.join(", ")
+            ),
+        );
+        return None;
+    }
+
+    Some(Pc {
+        name: pending.name,
+        level: pending.level,
+        ac: pending.ac?,
+        fortitude: pending.fortitude?,
+        reflex: pending.reflex?,
+        will: pending.will?,
+        perception: pending.perception?,
+        hp: pending.hp?,
+    })
+}
+
+pub fn parse_party_file(contents: &str) -> Option<Vec<Pc>> {
+    let mut party: Vec<Pc> = Vec::new();
+    let mut pending: Option<PendingPc> = None;
+
+    for (i, line) in contents.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
+            continue;
+        }
+
+        if line.starts_with('[') {
+            if let Some(p) = pending.take() {
+                party.push(finish_pc(p)?);
+            }
+
+            pending = Some(parse_header(line_number, line)?);
+            continue;
+        }
+
+        let pc = match pending.as_mut() {
+            Some(p) => p,
+            None => {
+                log_line_error(line_number, "expected a [pc <name> level <level>] header");
+                return None;
+            }
+        };
+
+        parse_statistic(line_number, line, pc)?;
+    }
+
+    if let Some(p) = pending.take() {
+        party.push(finish_pc(p)?);
+    }
+
+    if party.is_empty() {
+        logging::log(LogLevel::Error, "party file has no PCs");
+        return None;
+    }
+
+    Some(party)
+}
+
+pub fn load_party_file(path: &str) -> Option<Vec<Pc>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(_) => {
+            logging::log(
+                LogLevel::Error,
+                format!("failed to read party file: {}", path),
+            );
+            return None;
+        }
+    };
+
+    parse_party_file(&contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALEROS: &str = "[pc Valeros level 5]
+ac 25
+fortitude +14
+reflex +11
+will +9
+perception +11
+hp 75
+";
+
+    #[test]
+    fn accept_valid_party_file() {
+        let party = parse_party_file(VALEROS).unwrap();
+        assert_eq!(
+            party,
+            vec![Pc {
+                name: String::from("Valeros"),
+                level: 5,
+                ac: 25.0,
+                fortitude: 14.0,
+                reflex: 11.0,
+                will: 9.0,
+                perception: 11.0,
+                hp: 75.0,
+            }]
+        );
+
+        let contents = format!(
+            "# our party\n{}\n[PC Ezren the Wizard LEVEL 5]\nAC 20\nfort +9\nref +11\nwill +14\nper +12\nhp 52\n",
+            VALEROS
+        );
+        let party = parse_party_file(&contents).unwrap();
+        assert_eq!(party.len(), 2);
+        assert_eq!(party[1].name, "Ezren the Wizard");
+        assert_eq!(party[1].will, 14.0);
+    }
+
+    #[test]
+    fn reject_invalid_party_file() {
+        assert!(parse_party_file("").is_none());
+        assert!(parse_party_file("ac 25").is_none());
+        assert!(parse_party_file(&VALEROS.replace("[pc", "[npc")).is_none());
+        assert!(parse_party_file(&VALEROS.replace("level 5", "level x")).is_none());
+        assert!(parse_party_file(&VALEROS.replace("hp 75\n", "")).is_none());
+        assert!(parse_party_file(&VALEROS.replace("hp 75", "hp x")).is_none());
+        assert!(parse_party_file(&VALEROS.replace("hp 75", "mana 75")).is_none());
+        assert!(parse_party_file(&VALEROS.replace("hp 75", "ac 26")).is_none());
+    }
+
+    #[test]
+    fn reject_nonexistent_party_file() {
+        assert!(load_party_file("testdata/nonexistent_party_file.txt").is_none());
+    }
+}
@@ -0,0 +1,76 @@
+// A small seeded pseudorandom number generator (SplitMix64). Simulations use
+// it instead of a system randomness source so that their results can be
+// reproduced from the seed.
+
+pub const DEFAULT_SEED: u64 = 1;
+
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // Roll a die with the given number of sides, from 1 to sides.
+    pub fn roll(&mut self, sides: i32) -> i32 {
+        assert!(sides > 0);
+        let sides = sides as u64;
+
+        // Reject the top values that would make some sides more likely.
+        let limit = u64::MAX - u64::MAX % sides;
+        loop {
+            let value = self.next_u64();
+            if value < limit {
+                return (value % sides) as i32 + 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_rolls() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        let mut c = Rng::new(43);
+
+        let rolls_a: Vec<i32> = (0..100).map(|_| a.roll(20)).collect();
+        let rolls_b: Vec<i32> = (0..100).map(|_| b.roll(20)).collect();
+        let rolls_c: Vec<i32> = (0..100).map(|_| c.roll(20)).collect();
+
+        assert_eq!(rolls_a, rolls_b);
+        assert_ne!(rolls_a, rolls_c);
+    }
+
+    #[test]
+    fn rolls_cover_every_side() {
+        let mut rng = Rng::new(DEFAULT_SEED);
+        let mut counts = [0; 6];
+
+        for _ in 0..6000 {
+            let roll = rng.roll(6);
+            assert!((1..=6).contains(&roll));
+            counts[(roll - 1) as usize] += 1;
+        }
+
+        // Each side should come up roughly 1000 times.
+        for count in counts {
+            assert!((850..1150).contains(&count));
+        }
+    }
+}
@@ -0,0 +1,368 @@
+use crate::damage;
+use crate::logging::{self, LogLevel};
+use crate::statistic::{self, SavingThrowType, StatType};
+use crate::tables::{Proficiency, TableSet};
+use std::fmt;
+
+// Creature roadmaps from the Building Creatures section of GM Core. Each role
+// prescribes a proficiency for the statistics that define it, along with the
+// range of proficiencies that still fits the role.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Role {
+    Brute,
+    MagicalStriker,
+    SkillParagon,
+    Skirmisher,
+    Sniper,
+    Soldier,
+    Spellcaster,
+}
+
+pub const ROLES: [Role; 7] = [
+    Role::Brute,
+    Role::MagicalStriker,
+    Role::SkillParagon,
+    Role::Skirmisher,
+    Role::Sniper,
+    Role::Soldier,
+    Role::Spellcaster,
+];
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Role::Brute => "brute",
+            Role::MagicalStriker => "magical-striker",
+            Role::SkillParagon => "skill-paragon",
+            Role::Skirmisher => "skirmisher",
+            Role::Sniper => "sniper",
+            Role::Soldier => "soldier",
+            Role::Spellcaster => "spellcaster",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+pub fn parse_role(role: &str) -> Option<Role> {
+    let role = role.trim().to_lowercase();
+
+    match ROLES.into_iter().find(|r| r.to_string() == role) {
+        Some(r) => Some(r),
+        None => {
+            logging::log(LogLevel::Error, format!("unknown role: {}", role));
+            let names: Vec<String> = ROLES.iter().map(|r| r.to_string()).collect();
+            logging::log(LogLevel::Info, format!("roles: {}", names.join(", ")));
+            None
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoadmapEntry {
+    pub kind: StatType,
+    pub proficiency: Proficiency,
+    pub lowest: Proficiency,
+    pub highest: Proficiency,
+}
+
+fn entry(
+    kind: StatType,
+    proficiency: Proficiency,
+    lowest: Proficiency,
+    highest: Proficiency,
+) -> RoadmapEntry {
+    RoadmapEntry {
+        kind,
+        proficiency,
+        lowest,
+        highest,
+    }
+}
+
+pub fn roadmap(role: Role) -> Vec<RoadmapEntry> {
+    use Proficiency::{Extreme, High, Low, Moderate, Terrible};
+
+    let per = StatType::Perception;
+    let ac = StatType::ArmorClass;
+    let fort = StatType::SavingThrow(SavingThrowType::Fortitude);
+    let reflex = StatType::SavingThrow(SavingThrowType::Reflex);
+    let will = StatType::SavingThrow(SavingThrowType::Will);
+    let hp = StatType::HitPoints;
+    let attack = StatType::StrikeAttackBonus;
+    let damage = StatType::StrikeDamage;
+    let spell_dc = StatType::SpellDC;
+    let spell_attack = StatType::SpellAttackBonus;
+
+    match role {
+        Role::Brute => vec![
+            entry(per, Low, Low, Moderate),
+            entry(ac, Low, Low, Moderate),
+            entry(fort, High, High, Extreme),
+            entry(reflex, Low, Terrible, Moderate),
+            entry(will, Low, Terrible, Moderate),
+            entry(hp, High, High, High),
+            entry(attack, Moderate, Moderate, High),
+            entry(damage, Extreme, High, Extreme),
+        ],
+        Role::MagicalStriker => vec![
+            entry(per, Moderate, Moderate, High),
+            entry(ac, Moderate, Moderate, High),
+            entry(fort, Moderate, Low, High),
+            entry(reflex, Moderate, Low, High),
+            entry(will, Moderate, Low, High),
+            entry(hp, Moderate, Low, Moderate),
+            entry(attack, High, High, High),
+            entry(damage, High, High, High),
+            entry(spell_dc, Moderate, Moderate, High),
+            entry(spell_attack, Moderate, Moderate, High),
+        ],
+        Role::SkillParagon => vec![
+            entry(per, High, Moderate, High),
+            entry(ac, Moderate, Moderate, High),
+            entry(fort, Low, Low, Moderate),
+            entry(reflex, High, High, Extreme),
+            entry(will, Moderate, Low, High),
+            entry(hp, Moderate, Moderate, Moderate),
+            entry(attack, Moderate, Moderate, High),
+            entry(damage, Moderate, Moderate, High),
+        ],
+        Role::Skirmisher => vec![
+            entry(per, Moderate, Moderate, High),
+            entry(ac, Moderate, Moderate, High),
+            entry(fort, Low, Low, Moderate),
+            entry(reflex, High, High, Extreme),
+            entry(will, Moderate, Low, High),
+            entry(hp, Moderate, Low, Moderate),
+            entry(attack, High, Moderate, High),
+            entry(damage, Moderate, Moderate, High),
+        ],
+        Role::Sniper => vec![
+            entry(per, High, High, Extreme),
+            entry(ac, Moderate, Low, Moderate),
+            entry(fort, Low, Low, Moderate),
+            entry(reflex, High, High, Extreme),
+            entry(will, Moderate, Low, High),
+            entry(hp, Low, Low, Moderate),
+            entry(attack, High, Moderate, High),
+            entry(damage, High, High, Extreme),
+        ],
+        Role::Soldier => vec![
+            entry(per, Moderate, Moderate, High),
+            entry(ac, High, High, Extreme),
+            entry(fort, High, High, Extreme),
+            entry(reflex, Moderate, Low, High),
+            entry(will, Moderate, Low, High),
+            entry(hp, Moderate, Moderate, High),
+            entry(attack, High, High, High),
+            entry(damage, High, High, High),
+        ],
+        Role::Spellcaster => vec![
+            entry(per, Moderate, Moderate, High),
+            entry(ac, Low, Low, Moderate),
+            entry(fort, Low, Terrible, Low),
+            entry(reflex, Moderate, Low, Moderate),
+            entry(will, High, High, Extreme),
+            entry(hp, Low, Low, Low),
+            entry(attack, Low, Low, Moderate),
+            entry(damage, Low, Low, Moderate),
+            entry(spell_dc, High, High, Extreme),
+            entry(spell_attack, High, High, Extreme),
+        ],
+    }
+}
+
+pub fn roadmap_entry(role: Role, kind: &StatType) -> Option<RoadmapEntry> {
+    roadmap(role).into_iter().find(|e| e.kind == *kind)
+}
+
+impl RoadmapEntry {
+    // The prescribed value is the middle of the proficiency's columns, rounded
+    // down like the rest of the tables. None if the table lacks the proficiency.
+    pub fn expected_value(&self, level: i32, tables: &TableSet) -> Option<f64> {
+        let table = tables.get_for_statistic(&self.kind);
+        let (low, high) = table.proficiency_range(level, self.proficiency)?;
+
+        Some(((low + high) / 2.0).floor())
+    }
+
+    // The values that still fit the role, from the lowest column of the lowest
+    // proficiency to the highest column of the highest one.
+    pub fn allowed_range(&self, level: i32, tables: &TableSet) -> Option<(f64, f64)> {
+        let table = tables.get_for_statistic(&self.kind);
+        let (low, _) = table.proficiency_range(level, self.lowest)?;
+        let (_, high) = table.proficiency_range(level, self.highest)?;
+
+        Some((low, high))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Verdict {
+    Below,
+    Within,
+    Above,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Verdict::Below => "below roadmap",
+            Verdict::Within => "ok",
+            Verdict::Above => "above roadmap",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+pub fn check_value(
+    entry: &RoadmapEntry,
+    level: i32,
+    value: f64,
+    tables: &TableSet,
+) -> Option<Verdict> {
+    let (low, high) = entry.allowed_range(level, tables)?;
+
+    if value < low {
+        Some(Verdict::Below)
+    } else if value > high {
+        Some(Verdict::Above)
+    } else {
+        Some(Verdict::Within)
+    }
+}
+
+fn log_missing_proficiency(entry: &RoadmapEntry) {
+    logging::log(
+        LogLevel::Warning,
+        format!(
+            "the {} table has no {} column: skipping {}",
+            entry.kind, entry.proficiency, entry.kind
+        ),
+    );
+}
+
+// Generate a stat block for a role as prompt lines, so it can be scaled or
+// checked like any other input file.
+pub fn generate(role: Role, level: i32, tables: &TableSet) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+
+    for entry in roadmap(role) {
+        let value = match entry.expected_value(level, tables) {
+            Some(v) => v,
+            None => {
+                log_missing_proficiency(&entry);
+                continue;
+            }
+        };
+
+        let line = match entry.kind {
+            StatType::StrikeDamage
+            | StatType::UnlimitedAreaDamage
+            | StatType::LimitedAreaDamage => {
+                let expression = damage::build_damage_expression(value, entry.proficiency);
+                format!("{} {} untyped", entry.kind, expression)
+            }
+            _ if statistic::is_bonus(&entry.kind) => format!("{} +{}", entry.kind, value),
+            _ => format!("{} {}", entry.kind, value),
+        };
+
+        lines.push(line);
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::levels::{MAX_EXTENDED_LEVEL, MIN_EXTENDED_LEVEL};
+
+    #[test]
+    fn accept_valid_role() {
+        assert_eq!(parse_role("brute"), Some(Role::Brute));
+        assert_eq!(parse_role(" Spellcaster "), Some(Role::Spellcaster));
+        assert_eq!(parse_role("magical-striker"), Some(Role::MagicalStriker));
+
+        for role in ROLES {
+            assert_eq!(parse_role(&role.to_string()), Some(role));
+        }
+    }
+
+    #[test]
+    fn reject_invalid_role() {
+        assert_eq!(parse_role(""), None);
+        assert_eq!(parse_role("tank"), None);
+        assert_eq!(parse_role("magical striker"), None);
+    }
+
+    #[test]
+    fn validate_roadmaps() {
+        for role in ROLES {
+            let entries = roadmap(role);
+
+            for (i, e) in entries.iter().enumerate() {
+                assert!(e.lowest <= e.proficiency && e.proficiency <= e.highest);
+                assert!(!entries[..i].iter().any(|other| other.kind == e.kind));
+            }
+        }
+    }
+
+    #[test]
+    fn roadmap_values_exist_at_every_level() {
+        let tables = TableSet::new();
+
+        for role in ROLES {
+            for level in MIN_EXTENDED_LEVEL..=MAX_EXTENDED_LEVEL {
+                for e in roadmap(role) {
+                    let value = e.expected_value(level, &tables).unwrap();
+                    let (low, high) = e.allowed_range(level, &tables).unwrap();
+                    assert!(low <= value && value <= high);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn generate_stat_block() {
+        let tables = TableSet::new();
+        let lines = generate(Role::Brute, 5, &tables);
+
+        assert_eq!(
+            lines,
+            vec![
+                "perception +9",
+                "AC 19",
+                "fortitude +15",
+                "reflex +9",
+                "will +9",
+                "HP 94",
+                "strike-attack +13",
+                "strike-damage 2d10+9 untyped",
+            ]
+        );
+
+        let lines = generate(Role::Spellcaster, 5, &tables);
+        assert!(lines.contains(&String::from("spell-DC 22")));
+        assert!(lines.contains(&String::from("HP 56")));
+    }
+
+    #[test]
+    fn check_against_roadmap() {
+        let tables = TableSet::new();
+        let hp = roadmap_entry(Role::Brute, &StatType::HitPoints).unwrap();
+
+        assert_eq!(check_value(&hp, 5, 94.0, &tables), Some(Verdict::Within));
+        assert_eq!(check_value(&hp, 5, 91.0, &tables), Some(Verdict::Within));
+        assert_eq!(check_value(&hp, 5, 90.0, &tables), Some(Verdict::Below));
+        assert_eq!(check_value(&hp, 5, 98.0, &tables), Some(Verdict::Above));
+
+        let ac = roadmap_entry(Role::Soldier, &StatType::ArmorClass).unwrap();
+        assert_eq!(check_value(&ac, 5, 21.0, &tables), Some(Verdict::Below));
+        assert_eq!(check_value(&ac, 5, 25.0, &tables), Some(Verdict::Within));
+
+        assert_eq!(roadmap_entry(Role::Brute, &StatType::SpellDC), None);
+    }
+}
@@ -0,0 +1,174 @@
+use crate::logging::{self, LogLevel};
+use std::fmt;
+
+pub const DEFAULT_EPSILON: f64 = 0.1;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RoundingPolicy {
+    #[default]
+    Floor,
+    Nearest,
+    Ceil,
+    // Round down unless the value is within epsilon of the next integer.
+    FloorUnlessWithin(f64),
+}
+
+impl fmt::Display for RoundingPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RoundingPolicy::Floor => write!(f, "floor"),
+            RoundingPolicy::Nearest => write!(f, "nearest"),
+            RoundingPolicy::Ceil => write!(f, "ceil"),
+            RoundingPolicy::FloorUnlessWithin(epsilon) => {
+                write!(f, "floor-unless-within={}", epsilon)
+            }
+        }
+    }
+}
+
+impl RoundingPolicy {
+    pub fn round(&self, value: f64) -> f64 {
+        match self {
+            RoundingPolicy::Floor => value.floor(),
+            RoundingPolicy::Nearest => value.round(),
+            RoundingPolicy::Ceil => value.ceil(),
+            RoundingPolicy::FloorUnlessWithin(epsilon) => {
+                if value.ceil() - value < *epsilon {
+                    value.ceil()
+                } else {
+                    value.floor()
+                }
+            }
+        }
+    }
+
+    // Whether this policy gives a different result than rounding down,
+    // which is the usual Pathfinder convention.
+    pub fn differs_from_floor(&self, value: f64) -> bool {
+        self.round(value) != value.floor()
+    }
+
+    // The average damage to build a damage expression for. The expression
+    // builder never exceeds its target, so it already rounds down on its own,
+    // and we only round the target when the policy could round up.
+    pub fn damage_target(&self, average_damage: f64) -> f64 {
+        if !self.differs_from_floor(average_damage) {
+            return average_damage;
+        }
+
+        self.round(average_damage)
+    }
+}
+
+fn parse_epsilon(epsilon: &str) -> Option<f64> {
+    match epsilon.parse::<f64>() {
+        Ok(e) if e > 0.0 && e < 1.0 => Some(e),
+        _ => {
+            logging::log(
+                LogLevel::Error,
+                format!(
+                    "rounding epsilon must be a number between 0 and 1: {}",
+                    epsilon
+                ),
+            );
+            None
+        }
+    }
+}
+
+pub fn parse_rounding_policy(policy: &str) -> Option<RoundingPolicy> {
+    let policy = policy.trim().to_lowercase();
+
+    let (name, epsilon) = match policy.split_once('=') {
+        Some((n, e)) => (n, Some(e)),
+        None => (policy.as_str(), None),
+    };
+
+    match (name, epsilon) {
+        ("floor", None) => Some(RoundingPolicy::Floor),
+        ("nearest", None) => Some(RoundingPolicy::Nearest),
+        ("ceil", None) => Some(RoundingPolicy::Ceil),
+        ("floor-unless-within", None) => Some(RoundingPolicy::FloorUnlessWithin(DEFAULT_EPSILON)),
+        ("floor-unless-within", Some(e)) => parse_epsilon(e).map(RoundingPolicy::FloorUnlessWithin),
+        _ => {
+            logging::log(
+                LogLevel::Error,
+                format!("unknown rounding policy: {}", policy),
+            );
+            logging::log(
+                LogLevel::Info,
+                "rounding policies: floor, nearest, ceil, floor-unless-within[=<epsilon>]",
+            );
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::float_eq;
+
+    #[test]
+    fn accept_valid_rounding_policy() {
+        assert_eq!(parse_rounding_policy("floor"), Some(RoundingPolicy::Floor));
+        assert_eq!(
+            parse_rounding_policy("Nearest"),
+            Some(RoundingPolicy::Nearest)
+        );
+        assert_eq!(parse_rounding_policy("ceil"), Some(RoundingPolicy::Ceil));
+        assert_eq!(
+            parse_rounding_policy("floor-unless-within"),
+            Some(RoundingPolicy::FloorUnlessWithin(DEFAULT_EPSILON))
+        );
+        assert_eq!(
+            parse_rounding_policy("floor-unless-within=0.25"),
+            Some(RoundingPolicy::FloorUnlessWithin(0.25))
+        );
+    }
+
+    #[test]
+    fn reject_invalid_rounding_policy() {
+        assert_eq!(parse_rounding_policy(""), None);
+        assert_eq!(parse_rounding_policy("round"), None);
+        assert_eq!(parse_rounding_policy("floor=0.5"), None);
+        assert_eq!(parse_rounding_policy("floor-unless-within="), None);
+        assert_eq!(parse_rounding_policy("floor-unless-within=x"), None);
+        assert_eq!(parse_rounding_policy("floor-unless-within=0"), None);
+        assert_eq!(parse_rounding_policy("floor-unless-within=1"), None);
+    }
+
+    #[test]
+    fn round_with_policy() {
+        assert!(float_eq(RoundingPolicy::Floor.round(14.9), 14.0));
+        assert!(float_eq(RoundingPolicy::Nearest.round(14.4), 14.0));
+        assert!(float_eq(RoundingPolicy::Nearest.round(14.5), 15.0));
+        assert!(float_eq(RoundingPolicy::Ceil.round(14.1), 15.0));
+        assert!(float_eq(RoundingPolicy::Ceil.round(14.0), 14.0));
+
+        let policy = RoundingPolicy::FloorUnlessWithin(0.1);
+        assert!(float_eq(policy.round(14.95), 15.0));
+        assert!(float_eq(policy.round(14.85), 14.0));
+        assert!(float_eq(policy.round(14.0), 14.0));
+
+        assert!(float_eq(RoundingPolicy::Floor.round(-2.5), -3.0));
+        assert!(float_eq(RoundingPolicy::Ceil.round(-2.5), -2.0));
+    }
+
+    #[test]
+    fn detect_difference_from_floor() {
+        assert!(!RoundingPolicy::Floor.differs_from_floor(14.9));
+        assert!(RoundingPolicy::Nearest.differs_from_floor(14.9));
+        assert!(!RoundingPolicy::Nearest.differs_from_floor(14.1));
+        assert!(!RoundingPolicy::Ceil.differs_from_floor(14.0));
+        assert!(RoundingPolicy::Ceil.differs_from_floor(14.1));
+    }
+
+    #[test]
+    fn round_damage_target() {
+        assert!(float_eq(RoundingPolicy::Floor.damage_target(16.67), 16.67));
+        assert!(float_eq(RoundingPolicy::Nearest.damage_target(16.67), 17.0));
+        assert!(float_eq(RoundingPolicy::Nearest.damage_target(16.4), 16.4));
+        assert!(float_eq(RoundingPolicy::Ceil.damage_target(0.2), 1.0));
+    }
+}
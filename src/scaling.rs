@@ -1,5 +1,5 @@
-use crate::damage::{Damage, DamageComponent};
-use crate::levels::{Levels, MIN_LEVEL};
+use crate::damage::{Damage, DamageComponent, DamageRoll, Dice};
+use crate::levels::Levels;
 use crate::logging::{self, LogLevel};
 use crate::statistic::Statistic;
 use crate::tables::{self, Proficiency, StatTable};
@@ -11,6 +11,9 @@ pub enum ScaleMethod {
     Exact,
     Interpolated,
     Extrapolated,
+    Snapped,
+    // Scaled from or to a level outside the official tables.
+    Synthesised,
 }
 
 impl fmt::Display for ScaleMethod {
@@ -19,19 +22,79 @@ impl fmt::Display for ScaleMethod {
             ScaleMethod::Exact => "Exact",
             ScaleMethod::Interpolated => "Interpolated",
             ScaleMethod::Extrapolated => "Extrapolated",
+            ScaleMethod::Snapped => "Snapped",
+            ScaleMethod::Synthesised => "Synthesised",
         };
 
         write!(f, "{}", s)
     }
 }
 
-#[derive(Clone, Copy)]
+// How to scale values that are outside the current row.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Extrapolation {
+    // Keep the absolute distance from the row edge. Suits bonuses and AC.
+    #[default]
+    Additive,
+    // Keep the ratio to the row edge. Suits multiplicative stats like HP and damage.
+    Proportional,
+    // Keep the distance measured in units of the gap between the edge column
+    // and its neighbour.
+    ColumnGap,
+}
+
+impl fmt::Display for Extrapolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Extrapolation::Additive => "additive",
+            Extrapolation::Proportional => "proportional",
+            Extrapolation::ColumnGap => "column-gap",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+pub fn parse_extrapolation(extrapolation: &str) -> Option<Extrapolation> {
+    match extrapolation.trim().to_lowercase().as_str() {
+        "additive" => Some(Extrapolation::Additive),
+        "proportional" => Some(Extrapolation::Proportional),
+        "column-gap" => Some(Extrapolation::ColumnGap),
+        _ => {
+            logging::log(
+                LogLevel::Error,
+                format!("unknown extrapolation strategy: {}", extrapolation),
+            );
+            logging::log(
+                LogLevel::Info,
+                "extrapolation strategies: additive, proportional, column-gap",
+            );
+            None
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct ScaleResult {
     pub stat: Statistic,
     pub proficiency: Proficiency,
     pub method: ScaleMethod,
 }
 
+#[derive(Clone)]
+pub struct SnapResult {
+    pub snapped: ScaleResult,
+    // What scaling by interpolation would have given, to report how far the
+    // value moved when snapping.
+    pub faithful: ScaleResult,
+}
+
+impl SnapResult {
+    pub fn movement(&self) -> f64 {
+        self.snapped.stat.value - self.faithful.stat.value
+    }
+}
+
 // Map a value from ]al, ar[ to [bl, br].
 // a-interval can't be a point, b-interval can.
 fn interpolate(al: f64, ar: f64, bl: f64, br: f64, val: f64) -> f64 {
@@ -61,49 +124,129 @@ fn extrapolate(cur_edge: f64, tgt_edge: f64, value: f64) -> f64 {
     }
 }
 
-fn scale_by_table(levels: Levels, stat: Statistic, table: StatTable) -> ScaleResult {
-    let cur_row_i: usize = (levels.current - MIN_LEVEL)
-        .try_into()
-        .expect("levels should be in range");
+// Map a value that is outside current min/max by some ratio to outside
+// target min/max by the same ratio. Only meaningful for positive edges.
+fn extrapolate_proportional(cur_edge: f64, tgt_edge: f64, value: f64) -> f64 {
+    assert!(cur_edge > 0.0);
 
-    let tgt_row_i: usize = (levels.target - MIN_LEVEL)
-        .try_into()
-        .expect("levels should be in range");
+    tgt_edge * value / cur_edge
+}
 
-    let cur_row = &table.values[cur_row_i];
-    let tgt_row = &table.values[tgt_row_i];
+// Map a value that is outside current min/max by x column gaps to outside
+// target min/max by x column gaps.
+fn extrapolate_column_gap(
+    cur_edge: f64,
+    cur_gap: f64,
+    tgt_edge: f64,
+    tgt_gap: f64,
+    value: f64,
+) -> f64 {
+    assert!(cur_gap > 0.0);
+
+    let gaps = (value - cur_edge) / cur_gap;
+    tgt_edge + gaps * tgt_gap
+}
+
+fn log_extrapolation_fallback(stat: &Statistic, extrapolation: Extrapolation) {
+    logging::log(
+        LogLevel::Warning,
+        format!(
+            "can't use {} extrapolation for {} {}: using additive extrapolation",
+            extrapolation, stat.kind, stat.value
+        ),
+    );
+}
 
+fn extrapolate_edge(
+    stat: &Statistic,
+    cur_row: &[f64],
+    tgt_row: &[f64],
+    edge: usize,
+    extrapolation: Extrapolation,
+) -> f64 {
+    let cur_edge = cur_row[edge];
+    let tgt_edge = tgt_row[edge];
+
+    match extrapolation {
+        Extrapolation::Additive => extrapolate(cur_edge, tgt_edge, stat.value),
+        Extrapolation::Proportional => {
+            if cur_edge > 0.0 {
+                extrapolate_proportional(cur_edge, tgt_edge, stat.value)
+            } else {
+                log_extrapolation_fallback(stat, extrapolation);
+                extrapolate(cur_edge, tgt_edge, stat.value)
+            }
+        }
+        Extrapolation::ColumnGap => {
+            // Single-column tables and equal edge columns have no gap to measure with.
+            let neighbor = if edge == 0 { 1 } else { edge.saturating_sub(1) };
+            let cur_gap = cur_row.get(neighbor).map(|n| (cur_edge - n).abs());
+            let tgt_gap = tgt_row.get(neighbor).map(|n| (tgt_edge - n).abs());
+
+            match (cur_gap, tgt_gap) {
+                (Some(cg), Some(tg)) if cg > 0.0 && neighbor != edge => {
+                    extrapolate_column_gap(cur_edge, cg, tgt_edge, tg, stat.value)
+                }
+                _ => {
+                    log_extrapolation_fallback(stat, extrapolation);
+                    extrapolate(cur_edge, tgt_edge, stat.value)
+                }
+            }
+        }
+    }
+}
+
+// Warn if the value lies outside the current row. Returns true if it does.
+fn check_out_of_row(levels: Levels, stat: &Statistic, cur_row: &[f64]) -> bool {
     let cur_min = *cur_row.first().unwrap();
     let cur_max = *cur_row.last().unwrap();
 
-    if stat.value < cur_min || stat.value > cur_max {
-        if stat.value < cur_min {
-            logging::log(
-                LogLevel::Warning,
-                format!(
-                    "{} {} is too low for a level {} creature: minimum {}",
-                    stat.kind, stat.value, levels.current, cur_min
-                ),
-            );
-        } else {
-            logging::log(
-                LogLevel::Warning,
-                format!(
-                    "{} {} is too high for a level {} creature: maximum {}",
-                    stat.kind, stat.value, levels.current, cur_max
-                ),
-            );
-        }
+    if stat.value < cur_min {
+        logging::log(
+            LogLevel::Warning,
+            format!(
+                "{} {} is too low for a level {} creature: minimum {}",
+                stat.kind, stat.value, levels.current, cur_min
+            ),
+        );
+        return true;
+    }
+
+    if stat.value > cur_max {
+        logging::log(
+            LogLevel::Warning,
+            format!(
+                "{} {} is too high for a level {} creature: maximum {}",
+                stat.kind, stat.value, levels.current, cur_max
+            ),
+        );
+        return true;
+    }
+
+    false
+}
+
+fn scale_by_table(
+    levels: Levels,
+    stat: &Statistic,
+    table: &StatTable,
+    extrapolation: Extrapolation,
+) -> ScaleResult {
+    let cur_row = &table.row(levels.current);
+    let tgt_row = &table.row(levels.target);
+
+    let cur_min = *cur_row.first().unwrap();
 
+    if check_out_of_row(levels, stat, cur_row) {
         let edge = if stat.value < cur_min {
             0
         } else {
             cur_row.len() - 1
         };
 
-        let scaled = extrapolate(cur_row[edge], tgt_row[edge], stat.value);
+        let scaled = extrapolate_edge(stat, cur_row, tgt_row, edge, extrapolation);
         return ScaleResult {
-            stat: Statistic::new(stat.kind, scaled),
+            stat: Statistic::new(stat.kind.clone(), scaled),
             proficiency: table.proficiencies[edge],
             method: ScaleMethod::Extrapolated,
         };
@@ -113,7 +256,7 @@ fn scale_by_table(levels: Levels, stat: Statistic, table: StatTable) -> ScaleRes
         if float_eq(cur_row[i], stat.value) {
             let scaled = tgt_row[i];
             return ScaleResult {
-                stat: Statistic::new(stat.kind, scaled),
+                stat: Statistic::new(stat.kind.clone(), scaled),
                 proficiency: table.proficiencies[i],
                 method: ScaleMethod::Exact,
             };
@@ -129,7 +272,7 @@ fn scale_by_table(levels: Levels, stat: Statistic, table: StatTable) -> ScaleRes
             );
 
             return ScaleResult {
-                stat: Statistic::new(stat.kind, scaled),
+                stat: Statistic::new(stat.kind.clone(), scaled),
                 proficiency: table.proficiencies[i],
                 method: ScaleMethod::Interpolated,
             };
@@ -139,6 +282,222 @@ fn scale_by_table(levels: Levels, stat: Statistic, table: StatTable) -> ScaleRes
     unreachable!("we should always either get an exact result, interpolate or extrapolate");
 }
 
+// Round the position of the value on the current row to the nearest column
+// and use the value of that column on the target row. A value between two
+// columns snaps to the nearer one, or to the lower one on a tie. We don't warn
+// about values outside the row here, because snapping is always reported
+// together with the faithfully scaled value.
+fn snap_by_table(levels: Levels, stat: &Statistic, table: &StatTable) -> ScaleResult {
+    let cur_row = &table.row(levels.current);
+    let tgt_row = &table.row(levels.target);
+
+    let last = cur_row.len() - 1;
+
+    let column = if stat.value <= cur_row[0] {
+        0
+    } else if stat.value >= cur_row[last] {
+        last
+    } else if let Some(i) = cur_row.iter().position(|v| float_eq(*v, stat.value)) {
+        i
+    } else {
+        let i = (0..last)
+            .find(|i| cur_row[*i] < stat.value && stat.value < cur_row[i + 1])
+            .expect("value should be between two columns");
+
+        if cur_row[i + 1] - stat.value < stat.value - cur_row[i] {
+            i + 1
+        } else {
+            i
+        }
+    };
+
+    ScaleResult {
+        stat: Statistic::new(stat.kind.clone(), tgt_row[column]),
+        proficiency: table.proficiencies[column],
+        method: ScaleMethod::Snapped,
+    }
+}
+
+// A proficiency shift moves a scaled value by whole columns on the target row,
+// or to a given proficiency column.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProficiencyShift {
+    Columns(i32),
+    To(Proficiency),
+}
+
+impl fmt::Display for ProficiencyShift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProficiencyShift::Columns(n) => write!(f, "{:+}", n),
+            ProficiencyShift::To(p) => write!(f, "{}", p),
+        }
+    }
+}
+
+pub const PROFICIENCY_SHIFT_HELP: &str =
+    "proficiency shift is a signed number of columns (+1, -2) or a proficiency (high)";
+
+// Parse a proficiency shift without logging errors.
+pub fn proficiency_shift(shift: &str) -> Option<ProficiencyShift> {
+    let shift = shift.trim();
+
+    if shift.starts_with('+') || shift.starts_with('-') {
+        shift.parse::<i32>().ok().map(ProficiencyShift::Columns)
+    } else {
+        tables::parse_proficiency(shift).map(ProficiencyShift::To)
+    }
+}
+
+pub fn parse_proficiency_shift(shift: &str) -> Option<ProficiencyShift> {
+    let parsed = proficiency_shift(shift);
+    if parsed.is_some() {
+        return parsed;
+    }
+
+    let shift = shift.trim();
+    logging::log(
+        LogLevel::Error,
+        format!("invalid proficiency shift: {}", shift),
+    );
+    logging::log(LogLevel::Info, PROFICIENCY_SHIFT_HELP);
+    None
+}
+
+#[derive(Clone)]
+pub struct ShiftResult {
+    pub shifted: ScaleResult,
+    pub unshifted: ScaleResult,
+    // The shift would have moved the value past the edge of the table.
+    pub clamped: bool,
+}
+
+// Fractional column position of a value on a row, clamped to the row.
+fn row_position(row: &[f64], value: f64) -> f64 {
+    let last = row.len() - 1;
+
+    if value <= row[0] {
+        return 0.0;
+    }
+
+    if value >= row[last] {
+        return last as f64;
+    }
+
+    if let Some(i) = row.iter().position(|v| float_eq(*v, value)) {
+        return i as f64;
+    }
+
+    let i = (0..last)
+        .find(|i| row[*i] < value && value < row[i + 1])
+        .expect("value should be between two columns");
+
+    i as f64 + (value - row[i]) / (row[i + 1] - row[i])
+}
+
+fn value_at_position(row: &[f64], position: f64) -> f64 {
+    let i = position.floor() as usize;
+    let fraction = position - position.floor();
+
+    if i >= row.len() - 1 || float_eq(fraction, 0.0) {
+        return row[i.min(row.len() - 1)];
+    }
+
+    row[i] + fraction * (row[i + 1] - row[i])
+}
+
+// Shifting on the target row is the same as shifting on the current row before
+// mapping the level, because scaling keeps the relative position on the row.
+// Values outside the row keep their distance from the edge if the shift points
+// further out, and otherwise start from the edge column.
+pub fn shift_proficiency(
+    levels: Levels,
+    result: ScaleResult,
+    table: &StatTable,
+    shift: ProficiencyShift,
+) -> ShiftResult {
+    let tgt_row = &table.row(levels.target);
+    let last = (tgt_row.len() - 1) as f64;
+
+    let position = row_position(tgt_row, result.stat.value);
+    let below = result.stat.value < tgt_row[0] && !float_eq(result.stat.value, tgt_row[0]);
+    let above = result.stat.value > tgt_row[tgt_row.len() - 1]
+        && !float_eq(result.stat.value, tgt_row[tgt_row.len() - 1]);
+
+    let (target, clamped) = match shift {
+        ProficiencyShift::Columns(n) => {
+            let target = position + f64::from(n);
+            (target.clamp(0.0, last), target < 0.0 || target > last)
+        }
+        ProficiencyShift::To(p) => {
+            match table.proficiencies.iter().position(|c| *c == p) {
+                Some(i) => (i as f64, false),
+                // The table doesn't have this proficiency, use the nearest column towards it.
+                None => match table.proficiencies.iter().position(|c| *c > p) {
+                    Some(i) => (i as f64, true),
+                    None => (last, true),
+                },
+            }
+        }
+    };
+
+    let outward = (above && target >= position) || (below && target <= position);
+
+    if outward || float_eq(target, position) && !above && !below {
+        return ShiftResult {
+            shifted: result.clone(),
+            unshifted: result,
+            clamped,
+        };
+    }
+
+    let value = value_at_position(tgt_row, target);
+    let column = target.floor() as usize;
+
+    let method = match result.method {
+        ScaleMethod::Snapped | ScaleMethod::Synthesised => result.method,
+        _ if float_eq(target, target.floor()) => ScaleMethod::Exact,
+        _ => ScaleMethod::Interpolated,
+    };
+
+    ShiftResult {
+        shifted: floor_amount(
+            levels,
+            ScaleResult {
+                stat: Statistic::new(result.stat.kind.clone(), value),
+                proficiency: table.proficiencies[column],
+                method,
+            },
+        ),
+        unshifted: result,
+        clamped,
+    }
+}
+
+// A shift never moves a statistic back past its current value, so a creature
+// doesn't lose resistance while it levels up, or gain it while it levels
+// down. Returns whether the value was held.
+pub fn hold_level_direction(levels: Levels, current: f64, shift: &mut ShiftResult) -> bool {
+    let unshifted = shift.unshifted.stat.value;
+    let value = shift.shifted.stat.value;
+
+    let bound = if levels.target > levels.current && value < current.min(unshifted) {
+        current.min(unshifted)
+    } else if levels.target < levels.current && value > current.max(unshifted) {
+        current.max(unshifted)
+    } else {
+        return false;
+    };
+
+    if float_eq(value, bound) {
+        return false;
+    }
+
+    shift.shifted.stat.value = bound;
+    shift.shifted.method = ScaleMethod::Interpolated;
+    true
+}
+
 fn scale_all_damage_components(damage: &Damage, scaled_total: f64) -> Damage {
     assert!(scaled_total > 0.0);
     assert!(!damage.components.is_empty());
@@ -159,6 +518,8 @@ fn scale_all_damage_components(damage: &Damage, scaled_total: f64) -> Damage {
         let new_component = DamageComponent {
             average_value: scaled_average_damage,
             damage_type: damage.components[i].damage_type.clone(),
+            roll: None,
+            condition: damage.components[i].condition.clone(),
         };
         scaled_damage.components.push(new_component);
     }
@@ -177,6 +538,7 @@ fn scale_first_damage_component(damage: &Damage, scaled_total: f64) -> Damage {
 
     let mut scaled_damage = damage.clone();
     scaled_damage.components[0].average_value += delta;
+    scaled_damage.components[0].roll = None;
 
     scaled_damage
 }
@@ -185,31 +547,175 @@ fn scale_first_damage_component(damage: &Damage, scaled_total: f64) -> Damage {
 // Typically this means scaling the main physical damage and not touching the extra
 // elemental damage. If we scale down so much that the first component goes to zero,
 // scale every component proportionally instead.
-pub fn scale_damage_components(damage: &Damage, scaled_total: f64) -> Damage {
-    let current_total = damage.total_average_value();
+//
+// Conditional components, like precision damage, aren't part of the scaled
+// total. They follow their own progression by level instead.
+pub fn scale_damage_components(levels: Levels, damage: &Damage, scaled_total: f64) -> Damage {
+    let mut baseline = Damage::new();
+    baseline.components = damage
+        .components
+        .iter()
+        .filter(|c| damage.is_baseline(c))
+        .cloned()
+        .collect();
+
+    let current_total = baseline.total_average_value();
     let delta = scaled_total - current_total;
 
-    if damage.components.len() >= 2 && damage.components[0].average_value + delta <= 0.0 {
-        logging::log(
-            LogLevel::Info,
-            "damage was greatly decreased: scaling all damage components proportionally",
-        );
+    let scaled_baseline =
+        if baseline.components.len() >= 2 && baseline.components[0].average_value + delta <= 0.0 {
+            logging::log(
+                LogLevel::Info,
+                "damage was greatly decreased: scaling all damage components proportionally",
+            );
 
-        scale_all_damage_components(damage, scaled_total)
-    } else {
-        scale_first_damage_component(damage, scaled_total)
+            scale_all_damage_components(&baseline, scaled_total)
+        } else {
+            scale_first_damage_component(&baseline, scaled_total)
+        };
+
+    let mut scaled_baseline = scaled_baseline.components.into_iter();
+
+    let mut scaled = Damage::new();
+    for component in &damage.components {
+        if damage.is_baseline(component) {
+            scaled.components.push(
+                scaled_baseline
+                    .next()
+                    .expect("every baseline component should be scaled"),
+            );
+        } else {
+            scaled
+                .components
+                .push(scale_conditional_damage_component(levels, component));
+        }
+    }
+
+    scaled
+}
+
+// Conditional components keep their dice, and only gain or lose dice with the
+// levels. They keep at least one die, like a sneak attack at level 1.
+fn scale_conditional_damage_component(
+    levels: Levels,
+    component: &DamageComponent,
+) -> DamageComponent {
+    let ratio = conditional_damage_ratio(levels);
+
+    let roll = match &component.roll {
+        Some(r) if !r.dice.is_empty() => DamageRoll {
+            dice: r
+                .dice
+                .iter()
+                .map(|d| Dice {
+                    count: ((f64::from(d.count) * ratio).round() as i32).max(1),
+                    size: d.size,
+                })
+                .collect(),
+            modifier: (r.modifier * ratio).round(),
+        },
+        _ => {
+            return DamageComponent {
+                average_value: component.average_value * ratio,
+                roll: None,
+                ..component.clone()
+            }
+        }
+    };
+
+    DamageComponent {
+        average_value: roll.average_value(),
+        roll: Some(roll),
+        ..component.clone()
+    }
+}
+
+// The number of dice of extra damage that only applies sometimes, like
+// precision damage, at a level. As with a rogue's sneak attack, a die is added
+// at levels 5, 11 and 17.
+fn conditional_damage_dice(level: i32) -> i32 {
+    (level.max(1) + 7) / 6
+}
+
+// How much conditional damage components grow or shrink between the levels.
+pub fn conditional_damage_ratio(levels: Levels) -> f64 {
+    f64::from(conditional_damage_dice(levels.target))
+        / f64::from(conditional_damage_dice(levels.current))
+}
+
+// Results that use synthesised table rows are marked as such regardless of
+// how they were scaled, so they are never mistaken for official benchmarks.
+fn mark_synthesised(levels: Levels, result: ScaleResult) -> ScaleResult {
+    if !levels.is_synthesised() {
+        return result;
+    }
+
+    ScaleResult {
+        method: ScaleMethod::Synthesised,
+        ..result
+    }
+}
+
+// HP, damage and resistances are at least 1, even when they are extrapolated
+// far below the table or scaled to a synthesised row.
+fn floor_amount(levels: Levels, result: ScaleResult) -> ScaleResult {
+    let kind = &result.stat.kind;
+    if result.stat.value >= 1.0 || !tables::table_kind_for_statistic(kind).is_amount() {
+        return result;
+    }
+
+    logging::log(
+        LogLevel::Warning,
+        format!(
+            "{} scales to {:.2} at level {}: using 1",
+            kind, result.stat.value, levels.target
+        ),
+    );
+
+    ScaleResult {
+        stat: Statistic::new(kind.clone(), 1.0),
+        ..result
     }
 }
 
 pub fn scale_statistic(levels: Levels, stat: Statistic) -> ScaleResult {
-    let table = tables::get_table_for_statistic(stat.kind);
+    let table = tables::get_table_for_statistic(&stat.kind);
 
-    scale_by_table(levels, stat, table)
+    scale_statistic_with(levels, stat, &table, Extrapolation::Additive)
+}
+
+pub fn scale_statistic_with(
+    levels: Levels,
+    stat: Statistic,
+    table: &StatTable,
+    extrapolation: Extrapolation,
+) -> ScaleResult {
+    let result = scale_by_table(levels, &stat, table, extrapolation);
+    floor_amount(levels, mark_synthesised(levels, result))
+}
+
+pub fn snap_statistic(
+    levels: Levels,
+    stat: Statistic,
+    table: &StatTable,
+    extrapolation: Extrapolation,
+) -> SnapResult {
+    SnapResult {
+        snapped: floor_amount(
+            levels,
+            mark_synthesised(levels, snap_by_table(levels, &stat, table)),
+        ),
+        faithful: floor_amount(
+            levels,
+            mark_synthesised(levels, scale_by_table(levels, &stat, table, extrapolation)),
+        ),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::damage;
     use crate::scaling::ScaleMethod;
     use crate::statistic::StatType;
     use crate::tables::Proficiency;
@@ -262,6 +768,69 @@ mod tests {
         assert!(float_eq(extrapolate(-4.0, -8.0, -7.0), -11.0));
     }
 
+    #[test]
+    fn test_extrapolate_proportional() {
+        assert!(float_eq(extrapolate_proportional(20.0, 400.0, 40.0), 800.0));
+        assert!(float_eq(extrapolate_proportional(20.0, 400.0, 10.0), 200.0));
+        assert!(float_eq(extrapolate_proportional(400.0, 20.0, 500.0), 25.0));
+    }
+
+    #[test]
+    fn test_extrapolate_column_gap() {
+        // One gap above the edge.
+        assert!(float_eq(
+            extrapolate_column_gap(20.0, 4.0, 400.0, 60.0, 24.0),
+            460.0
+        ));
+        // Half a gap below the edge.
+        assert!(float_eq(
+            extrapolate_column_gap(10.0, 2.0, 100.0, 20.0, 9.0),
+            90.0
+        ));
+    }
+
+    #[test]
+    fn hit_points_extrapolate() {
+        let levels = Levels::new(1, 20).unwrap();
+        let table = tables::get_table_for_statistic(&StatType::HitPoints);
+        let stat = Statistic::new(StatType::HitPoints, 40.0);
+
+        // The level 1 row ends at 26 and the level 20 row at 473.
+        let additive = scale_statistic(levels, stat.clone());
+        assert!(float_eq(additive.stat.value, 487.0));
+        assert_eq!(additive.method, ScaleMethod::Extrapolated);
+
+        let proportional =
+            scale_statistic_with(levels, stat.clone(), &table, Extrapolation::Proportional);
+        assert!(float_eq(proportional.stat.value, 473.0 * 40.0 / 26.0));
+        assert_eq!(proportional.proficiency, Proficiency::High);
+        assert_eq!(proportional.method, ScaleMethod::Extrapolated);
+
+        // Gaps between the two High columns are 2 and 8.
+        let column_gap = scale_statistic_with(levels, stat, &table, Extrapolation::ColumnGap);
+        assert!(float_eq(column_gap.stat.value, 473.0 + 7.0 * 8.0));
+    }
+
+    #[test]
+    fn extrapolation_fallback() {
+        let levels = Levels::new(1, 20).unwrap();
+        let table = tables::get_table_for_statistic(&StatType::LimitedAreaDamage);
+
+        // Area damage tables have a single column, so there's no gap to measure with.
+        let stat = Statistic::new(StatType::LimitedAreaDamage, 10.0);
+        let additive = scale_statistic(levels, stat.clone());
+        let column_gap = scale_statistic_with(levels, stat, &table, Extrapolation::ColumnGap);
+        assert!(float_eq(additive.stat.value, column_gap.stat.value));
+
+        // Perception has a zero edge at level -1.
+        let levels = Levels::new(-1, 20).unwrap();
+        let table = tables::get_table_for_statistic(&StatType::Perception);
+        let stat = Statistic::new(StatType::Perception, -2.0);
+        let additive = scale_statistic(levels, stat.clone());
+        let proportional = scale_statistic_with(levels, stat, &table, Extrapolation::Proportional);
+        assert!(float_eq(additive.stat.value, proportional.stat.value));
+    }
+
     #[test]
     fn test_scale_all_damage_components() {
         let mut damage = Damage::new();
@@ -269,6 +838,8 @@ mod tests {
         let first = DamageComponent {
             average_value: 15.0,
             damage_type: String::from("bludgeoning"),
+            roll: None,
+            condition: None,
         };
         damage.components.push(first);
 
@@ -280,12 +851,16 @@ mod tests {
         let second = DamageComponent {
             average_value: 5.0,
             damage_type: String::from("fire"),
+            roll: None,
+            condition: None,
         };
         damage.components.push(second);
 
         let third = DamageComponent {
             average_value: 1.0,
             damage_type: String::from("void"),
+            roll: None,
+            condition: None,
         };
         damage.components.push(third);
 
@@ -303,6 +878,8 @@ mod tests {
         let first = DamageComponent {
             average_value: 15.0,
             damage_type: String::from("bludgeoning"),
+            roll: None,
+            condition: None,
         };
         damage.components.push(first);
 
@@ -314,12 +891,16 @@ mod tests {
         let second = DamageComponent {
             average_value: 5.0,
             damage_type: String::from("fire"),
+            roll: None,
+            condition: None,
         };
         damage.components.push(second);
 
         let third = DamageComponent {
             average_value: 1.0,
             damage_type: String::from("void"),
+            roll: None,
+            condition: None,
         };
         damage.components.push(third);
 
@@ -330,6 +911,66 @@ mod tests {
         assert!(float_eq(scaled.components[2].average_value, 1.0));
     }
 
+    #[test]
+    fn scale_conditional_damage_components() {
+        let damage =
+            damage::parse_damage("2d8+9 piercing plus 1d6 precision plus 2d6 fire").unwrap();
+
+        // Precision damage gains a die at level 5, whatever the baseline does.
+        let levels = Levels::new(4, 5).unwrap();
+        let scaled = scale_damage_components(levels, &damage, 30.0);
+        assert!(float_eq(scaled.total_average_value(), 30.0));
+        assert!(float_eq(scaled.components[0].average_value, 23.0));
+        assert_eq!(scaled.components[1].damage_type, "precision");
+        assert!(float_eq(scaled.components[1].average_value, 7.0));
+        assert!(float_eq(scaled.components[2].average_value, 7.0));
+
+        // The baseline grows from level 5 to 8, but precision damage doesn't.
+        let levels = Levels::new(5, 8).unwrap();
+        let scaled = scale_damage_components(levels, &damage, 35.0);
+        assert!(float_eq(scaled.total_average_value(), 35.0));
+        assert!(float_eq(scaled.components[1].average_value, 3.5));
+
+        // And it shrinks with the levels even if the baseline stays the same,
+        // but keeps at least one die.
+        let damage =
+            damage::parse_damage("2d8+9 piercing plus 4d6 precision plus 2d6 fire").unwrap();
+        let levels = Levels::new(17, 5).unwrap();
+        let scaled = scale_damage_components(levels, &damage, 25.0);
+        assert!(float_eq(scaled.total_average_value(), 25.0));
+        assert_eq!(
+            scaled.components[1].roll.as_ref().unwrap().to_string(),
+            "2d6"
+        );
+        let levels = Levels::new(17, 1).unwrap();
+        let scaled = scale_damage_components(levels, &damage, 25.0);
+        assert_eq!(
+            scaled.components[1].roll.as_ref().unwrap().to_string(),
+            "1d6"
+        );
+
+        // The dice keep their size: 1d6 at level 10 is 1.5 dice at level 15.
+        let damage = damage::parse_damage("2d8+10 slashing plus 1d6 precision").unwrap();
+        let levels = Levels::new(10, 15).unwrap();
+        let scaled = scale_damage_components(levels, &damage, 30.0);
+        assert_eq!(
+            scaled.components[1].roll.as_ref().unwrap().to_string(),
+            "2d6"
+        );
+        assert!(float_eq(scaled.components[1].average_value, 7.0));
+
+        // Flat conditional damage scales by the same ratio.
+        let damage = damage::parse_damage("2d8+10 slashing plus 2 precision").unwrap();
+        let levels = Levels::new(4, 5).unwrap();
+        let scaled = scale_damage_components(levels, &damage, 30.0);
+        assert!(float_eq(scaled.components[1].average_value, 4.0));
+
+        assert_eq!(conditional_damage_dice(-1), 1);
+        assert_eq!(conditional_damage_dice(4), 1);
+        assert_eq!(conditional_damage_dice(11), 3);
+        assert_eq!(conditional_damage_dice(20), 4);
+    }
+
     #[test]
     fn armor_class_exact_scale() {
         let levels = Levels::new(4, 17).unwrap();
@@ -377,6 +1018,301 @@ mod tests {
         assert_eq!(result.method, ScaleMethod::Extrapolated);
     }
 
+    #[test]
+    fn armor_class_snap() {
+        let levels = Levels::new(11, 2).unwrap();
+        let table = tables::get_table_for_statistic(&StatType::ArmorClass);
+
+        // Between High 31 and Extreme 34 at level 11, closer to High.
+        let result = snap_by_table(levels, &Statistic::new(StatType::ArmorClass, 32.0), &table);
+        assert!(float_eq(result.stat.value, 18.0));
+        assert_eq!(result.proficiency, Proficiency::High);
+        assert_eq!(result.method, ScaleMethod::Snapped);
+
+        // Closer to Extreme.
+        let result = snap_by_table(levels, &Statistic::new(StatType::ArmorClass, 33.0), &table);
+        assert!(float_eq(result.stat.value, 21.0));
+        assert_eq!(result.proficiency, Proficiency::Extreme);
+
+        // Exactly on a column.
+        let result = snap_by_table(levels, &Statistic::new(StatType::ArmorClass, 30.0), &table);
+        assert!(float_eq(result.stat.value, 17.0));
+        assert_eq!(result.proficiency, Proficiency::Moderate);
+
+        // Outside the row snaps to the edge.
+        let result = snap_by_table(levels, &Statistic::new(StatType::ArmorClass, 40.0), &table);
+        assert!(float_eq(result.stat.value, 21.0));
+        assert_eq!(result.proficiency, Proficiency::Extreme);
+        let result = snap_by_table(levels, &Statistic::new(StatType::ArmorClass, 10.0), &table);
+        assert!(float_eq(result.stat.value, 15.0));
+        assert_eq!(result.proficiency, Proficiency::Low);
+    }
+
+    #[test]
+    fn snap_tie_prefers_lower_column() {
+        let levels = Levels::new(5, 5).unwrap();
+        let table = tables::get_table_for_statistic(&StatType::StrikeDamage);
+
+        // Strike damage at level 5 is 11, 13, 16, 20: 18 is halfway between High and Extreme.
+        let result = snap_statistic(
+            levels,
+            Statistic::new(StatType::StrikeDamage, 18.0),
+            &table,
+            Extrapolation::Additive,
+        );
+        assert!(float_eq(result.faithful.stat.value, 18.0));
+        assert!(float_eq(result.movement(), -2.0));
+        let result = result.snapped;
+        assert!(float_eq(result.stat.value, 16.0));
+        assert_eq!(result.proficiency, Proficiency::High);
+    }
+
+    #[test]
+    fn snap_between_equal_columns() {
+        let levels = Levels::new(-1, 5).unwrap();
+        let table = tables::get_table_for_statistic(&StatType::StrikeDamage);
+
+        // Strike damage at level -1 is 2, 3, 3, 4: 3.5 lies between High and Extreme.
+        let result = snap_statistic(
+            levels,
+            Statistic::new(StatType::StrikeDamage, 3.5),
+            &table,
+            Extrapolation::Additive,
+        );
+        assert!(float_eq(result.snapped.stat.value, 16.0));
+        assert_eq!(result.snapped.proficiency, Proficiency::High);
+
+        // An exact match snaps to the same column as exact scaling.
+        let result = snap_statistic(
+            levels,
+            Statistic::new(StatType::StrikeDamage, 3.0),
+            &table,
+            Extrapolation::Additive,
+        );
+        assert_eq!(result.snapped.proficiency, result.faithful.proficiency);
+        assert!(float_eq(result.movement(), 0.0));
+    }
+
+    #[test]
+    fn armor_class_synthesised() {
+        // AC at level 24 is 48, 50, 51, 54 and grows by 1.5 per level at the top.
+        let levels = Levels::new(24, 26).unwrap();
+        let stat = Statistic::new(StatType::ArmorClass, 51.0);
+
+        let result = scale_statistic(levels, stat);
+
+        assert!(float_eq(result.stat.value, 54.0));
+        assert_eq!(result.proficiency, Proficiency::High);
+        assert_eq!(result.method, ScaleMethod::Synthesised);
+
+        let levels = Levels::new(-2, 3).unwrap();
+        let table = tables::get_table_for_statistic(&StatType::ArmorClass);
+        let stat = Statistic::new(StatType::ArmorClass, 14.0);
+
+        let result = scale_statistic(levels, stat.clone());
+        assert_eq!(result.method, ScaleMethod::Synthesised);
+
+        let result = snap_statistic(levels, stat, &table, Extrapolation::Additive);
+        assert_eq!(result.snapped.method, ScaleMethod::Synthesised);
+    }
+
+    #[test]
+    fn amounts_stay_positive_down_to_level_minus_2() {
+        // HP 5 is far below the level 1 row, and would be extrapolated to -8.
+        let levels = Levels::new(1, -2).unwrap();
+        let result = scale_statistic(levels, Statistic::new(StatType::HitPoints, 5.0));
+        assert!(float_eq(result.stat.value, 1.0));
+        assert_eq!(result.method, ScaleMethod::Synthesised);
+
+        for kind in [
+            StatType::StrikeDamage,
+            StatType::UnlimitedAreaDamage,
+            StatType::LimitedAreaDamage,
+            StatType::Resistance,
+        ] {
+            let result = scale_statistic(levels, Statistic::new(kind, 1.0));
+            assert!(result.stat.value >= 1.0);
+        }
+
+        // Statistics that aren't amounts may still go below 1.
+        let result = scale_statistic(levels, Statistic::new(StatType::Perception, 0.0));
+        assert!(result.stat.value < 1.0);
+
+        let levels = Levels::new(5, -2).unwrap();
+        let damage = damage::parse_damage("2d6 fire plus 1d6 precision").unwrap();
+        let stat = Statistic::new(StatType::StrikeDamage, damage.total_average_value());
+        let total = scale_statistic(levels, stat).stat.value;
+        let scaled = scale_damage_components(levels, &damage, total);
+        assert!(scaled.components.iter().all(|c| c.average_value > 0.0));
+    }
+
+    #[test]
+    fn accept_valid_proficiency_shift() {
+        assert_eq!(
+            parse_proficiency_shift("+1"),
+            Some(ProficiencyShift::Columns(1))
+        );
+        assert_eq!(
+            parse_proficiency_shift("-2"),
+            Some(ProficiencyShift::Columns(-2))
+        );
+        assert_eq!(
+            parse_proficiency_shift("high"),
+            Some(ProficiencyShift::To(Proficiency::High))
+        );
+        assert_eq!(
+            parse_proficiency_shift("Extreme"),
+            Some(ProficiencyShift::To(Proficiency::Extreme))
+        );
+    }
+
+    #[test]
+    fn reject_invalid_proficiency_shift() {
+        assert_eq!(parse_proficiency_shift(""), None);
+        assert_eq!(parse_proficiency_shift("1"), None);
+        assert_eq!(parse_proficiency_shift("+x"), None);
+        assert_eq!(parse_proficiency_shift("+1.5"), None);
+        assert_eq!(parse_proficiency_shift("legendary"), None);
+    }
+
+    #[test]
+    fn test_row_position() {
+        let row = [10.0, 12.0, 12.0, 16.0];
+        assert!(float_eq(row_position(&row, 10.0), 0.0));
+        assert!(float_eq(row_position(&row, 11.0), 0.5));
+        assert!(float_eq(row_position(&row, 12.0), 1.0));
+        assert!(float_eq(row_position(&row, 15.0), 2.75));
+        assert!(float_eq(row_position(&row, 20.0), 3.0));
+        assert!(float_eq(row_position(&row, 5.0), 0.0));
+
+        assert!(float_eq(value_at_position(&row, 2.75), 15.0));
+        assert!(float_eq(value_at_position(&row, 3.0), 16.0));
+    }
+
+    #[test]
+    fn armor_class_shift() {
+        // AC at level 2 is 15, 17, 18, 21.
+        let levels = Levels::new(11, 2).unwrap();
+        let table = tables::get_table_for_statistic(&StatType::ArmorClass);
+
+        let result = scale_statistic(levels, Statistic::new(StatType::ArmorClass, 30.0));
+        assert!(float_eq(result.stat.value, 17.0));
+
+        let shift = shift_proficiency(levels, result.clone(), &table, ProficiencyShift::Columns(1));
+        assert!(float_eq(shift.shifted.stat.value, 18.0));
+        assert_eq!(shift.shifted.proficiency, Proficiency::High);
+        assert_eq!(shift.shifted.method, ScaleMethod::Exact);
+        assert!(!shift.clamped);
+
+        let shift = shift_proficiency(
+            levels,
+            result.clone(),
+            &table,
+            ProficiencyShift::Columns(-3),
+        );
+        assert!(float_eq(shift.shifted.stat.value, 15.0));
+        assert_eq!(shift.shifted.proficiency, Proficiency::Low);
+        assert!(shift.clamped);
+
+        let shift = shift_proficiency(
+            levels,
+            result.clone(),
+            &table,
+            ProficiencyShift::To(Proficiency::Extreme),
+        );
+        assert!(float_eq(shift.shifted.stat.value, 21.0));
+        assert!(!shift.clamped);
+
+        // AC has no terrible column.
+        let shift = shift_proficiency(
+            levels,
+            result.clone(),
+            &table,
+            ProficiencyShift::To(Proficiency::Terrible),
+        );
+        assert!(float_eq(shift.shifted.stat.value, 15.0));
+        assert!(shift.clamped);
+
+        // Interpolated values keep their position between the columns.
+        let result = scale_statistic(levels, Statistic::new(StatType::ArmorClass, 32.0));
+        let shift = shift_proficiency(
+            levels,
+            result.clone(),
+            &table,
+            ProficiencyShift::Columns(-1),
+        );
+        assert!(float_eq(shift.shifted.stat.value, 17.0 + 1.0 / 3.0));
+        assert_eq!(shift.shifted.proficiency, Proficiency::Moderate);
+        assert_eq!(shift.shifted.method, ScaleMethod::Interpolated);
+    }
+
+    #[test]
+    fn extrapolated_shift() {
+        let levels = Levels::new(5, 9).unwrap();
+        let table = tables::get_table_for_statistic(&StatType::ArmorClass);
+
+        // 23 at level 9, below the Low column 25.
+        let result = scale_statistic(levels, Statistic::new(StatType::ArmorClass, 17.0));
+
+        let shift = shift_proficiency(
+            levels,
+            result.clone(),
+            &table,
+            ProficiencyShift::Columns(-1),
+        );
+        assert!(float_eq(shift.shifted.stat.value, 23.0));
+        assert_eq!(shift.shifted.method, ScaleMethod::Extrapolated);
+        assert!(shift.clamped);
+
+        let shift = shift_proficiency(levels, result.clone(), &table, ProficiencyShift::Columns(1));
+        assert!(float_eq(shift.shifted.stat.value, 27.0));
+        assert_eq!(shift.shifted.proficiency, Proficiency::Moderate);
+        assert_eq!(shift.shifted.method, ScaleMethod::Exact);
+    }
+
+    #[test]
+    fn hold_shift_to_level_direction() {
+        let table = tables::get_table_for_statistic(&StatType::Resistance);
+
+        // A resistance at the top of the level 5 row scales to 13 at level 8,
+        // and the low end there would take it below the 10 it started at.
+        let levels = Levels::new(5, 8).unwrap();
+        let result = scale_statistic(levels, Statistic::new(StatType::Resistance, 10.0));
+        let mut shift = shift_proficiency(
+            levels,
+            result,
+            &table,
+            ProficiencyShift::To(Proficiency::Low),
+        );
+        assert!(shift.shifted.stat.value < 10.0);
+        assert!(hold_level_direction(levels, 10.0, &mut shift));
+        assert!(float_eq(shift.shifted.stat.value, 10.0));
+
+        // Shifts that keep the direction are left alone.
+        let result = scale_statistic(levels, Statistic::new(StatType::Resistance, 5.0));
+        let mut shift = shift_proficiency(
+            levels,
+            result,
+            &table,
+            ProficiencyShift::To(Proficiency::Low),
+        );
+        let value = shift.shifted.stat.value;
+        assert!(!hold_level_direction(levels, 5.0, &mut shift));
+        assert!(float_eq(shift.shifted.stat.value, value));
+
+        // Scaling down, a shift can't raise the value past where it started.
+        let levels = Levels::new(8, 5).unwrap();
+        let result = scale_statistic(levels, Statistic::new(StatType::Resistance, 6.0));
+        let mut shift = shift_proficiency(
+            levels,
+            result,
+            &table,
+            ProficiencyShift::To(Proficiency::High),
+        );
+        assert!(hold_level_direction(levels, 6.0, &mut shift));
+        assert!(float_eq(shift.shifted.stat.value, 6.0));
+    }
+
     #[test]
     fn strike_damage_exact_scale() {
         let levels = Levels::new(8, 13).unwrap();
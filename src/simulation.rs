@@ -0,0 +1,222 @@
+// Monte Carlo simulation of a creature's Strikes against a PC. Average damage
+// hides the variance of the dice, so we roll every attack and damage die and
+// look at the spread of damage per round instead.
+
+use crate::combat::{self, StrikeStats};
+use crate::damage::DamageRoll;
+use crate::rng::Rng;
+use crate::strike;
+
+// Strikes per simulated round, the second with the multiple attack penalty.
+const STRIKES_PER_ROUND: usize = 2;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulationReport {
+    pub trials: u32,
+    // Damage of every simulated round, sorted from lowest to highest.
+    pub damage_per_round: Vec<f64>,
+    // Chances that the first round, or the first two rounds, reduce the PC to 0 HP.
+    pub downs_in_one_round: f64,
+    pub downs_in_two_rounds: f64,
+    // Fraction of Strikes that were critical hits.
+    pub critical_hits: f64,
+}
+
+impl SimulationReport {
+    // The damage per round below which the given fraction of rounds fall,
+    // using the nearest rank.
+    pub fn percentile(&self, fraction: f64) -> f64 {
+        let n = self.damage_per_round.len();
+        if n == 0 {
+            return 0.0;
+        }
+
+        let rank = (fraction * n as f64).ceil() as usize;
+        self.damage_per_round[rank.clamp(1, n) - 1]
+    }
+
+    pub fn mean(&self) -> f64 {
+        let n = self.damage_per_round.len();
+        if n == 0 {
+            return 0.0;
+        }
+
+        self.damage_per_round.iter().sum::<f64>() / n as f64
+    }
+}
+
+fn roll_damage(rolls: &[DamageRoll], rng: &mut Rng) -> f64 {
+    let mut total = 0.0;
+
+    for roll in rolls {
+        for dice in &roll.dice {
+            for _ in 0..dice.count {
+                total += f64::from(rng.roll(dice.size));
+            }
+        }
+        total += roll.modifier;
+    }
+
+    total
+}
+
+// Roll one round of Strikes. Returns the damage and the number of critical hits.
+fn simulate_round(strike: &StrikeStats, ac: f64, rng: &mut Rng) -> (f64, u32) {
+    let mut damage = 0.0;
+    let mut critical_hits = 0;
+
+    for i in 0..STRIKES_PER_ROUND {
+        let attack_bonus = if i == 0 {
+            strike.attack_bonus
+        } else {
+            strike.attack_bonus - strike.multiple_attack_penalty()
+        };
+
+        match combat::degree_of_success(rng.roll(20), attack_bonus, ac) {
+            3 => {
+                let (doubled, extra) =
+                    strike::critical_rolls(&strike.rolls, &strike.critical_traits);
+                damage += 2.0 * roll_damage(&doubled, rng) + roll_damage(&[extra], rng);
+                critical_hits += 1;
+            }
+            2 => damage += roll_damage(&strike.rolls, rng),
+            _ => (),
+        }
+    }
+
+    (damage, critical_hits)
+}
+
+// Simulate the given number of two-round fights of a Strike against a PC with
+// the given AC and HP. The same seed always gives the same report.
+pub fn simulate_strike(
+    strike: &StrikeStats,
+    ac: f64,
+    hp: f64,
+    trials: u32,
+    seed: u64,
+) -> SimulationReport {
+    let mut rng = Rng::new(seed);
+    let mut damage_per_round: Vec<f64> = Vec::new();
+    let mut downs_in_one_round = 0;
+    let mut downs_in_two_rounds = 0;
+    let mut critical_hits = 0;
+
+    for _ in 0..trials {
+        let (first, first_crits) = simulate_round(strike, ac, &mut rng);
+        let (second, second_crits) = simulate_round(strike, ac, &mut rng);
+
+        if first >= hp {
+            downs_in_one_round += 1;
+        }
+        if first + second >= hp {
+            downs_in_two_rounds += 1;
+        }
+
+        critical_hits += first_crits + second_crits;
+        damage_per_round.push(first);
+        damage_per_round.push(second);
+    }
+
+    damage_per_round.sort_by(f64::total_cmp);
+
+    let fraction = |count: u32, total: u32| {
+        if total > 0 {
+            f64::from(count) / f64::from(total)
+        } else {
+            0.0
+        }
+    };
+    let strikes = trials * 2 * STRIKES_PER_ROUND as u32;
+
+    SimulationReport {
+        trials,
+        damage_per_round,
+        downs_in_one_round: fraction(downs_in_one_round, trials),
+        downs_in_two_rounds: fraction(downs_in_two_rounds, trials),
+        critical_hits: fraction(critical_hits, strikes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::damage;
+    use crate::utils::float_eq;
+
+    fn strike(attack_bonus: f64, damage: &str) -> StrikeStats {
+        let roll = damage::parse_damage_roll(damage).unwrap();
+
+        StrikeStats {
+            name: String::from("jaws"),
+            attack_bonus,
+            damage: roll.average_value(),
+            rolls: vec![roll],
+            agile: false,
+            critical_traits: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn simulation_is_reproducible() {
+        let jaws = strike(16.0, "2d10+9");
+
+        let a = simulate_strike(&jaws, 23.0, 68.0, 500, 7);
+        let b = simulate_strike(&jaws, 23.0, 68.0, 500, 7);
+        let c = simulate_strike(&jaws, 23.0, 68.0, 500, 8);
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(a.damage_per_round.len(), 1000);
+    }
+
+    #[test]
+    fn simulation_matches_expected_damage() {
+        let jaws = strike(16.0, "2d10+9");
+        let report = simulate_strike(&jaws, 23.0, 68.0, 20000, 1);
+
+        // 20 average damage, 0.9 + 0.5 expected hits per round.
+        assert!((report.mean() - 28.0).abs() < 0.5);
+
+        // Critical hits are 20% of the first Strikes and 5% of the second.
+        assert!((report.critical_hits - 0.125).abs() < 0.01);
+
+        // Two critical hits deal at most 2 * 29 + 2 * 29 damage.
+        assert!(report.percentile(1.0) <= 116.0);
+        assert!(float_eq(report.percentile(0.0), 0.0));
+        assert!(report.downs_in_two_rounds >= report.downs_in_one_round);
+    }
+
+    #[test]
+    fn simulate_deadly_critical_hits() {
+        let mut jaws = strike(16.0, "2d10+9");
+        jaws.critical_traits = vec![strike::CriticalTrait::Deadly(damage::Dice {
+            count: 1,
+            size: 10,
+        })];
+        let report = simulate_strike(&jaws, 23.0, 68.0, 20000, 1);
+
+        // 20 average damage on a hit and 45.5 on a critical hit.
+        assert!((report.mean() - (20.0 * 0.9 + 45.5 * 0.25)).abs() < 0.5);
+        assert!(report.percentile(1.0) <= 2.0 * (58.0 + 10.0));
+    }
+
+    #[test]
+    fn flat_damage_rounds() {
+        // Each Strike deals 0, 10 or 20 damage.
+        let slam = strike(30.0, "10");
+        let report = simulate_strike(&slam, 20.0, 40.0, 100, 1);
+
+        for damage in &report.damage_per_round {
+            assert!(float_eq(damage % 10.0, 0.0));
+            assert!(*damage <= 40.0);
+        }
+        assert!(report.critical_hits > 0.8);
+
+        // Can't hit at all.
+        let report = simulate_strike(&slam, 100.0, 40.0, 100, 1);
+        assert!(float_eq(report.mean(), 0.0));
+        assert!(float_eq(report.critical_hits, 0.0));
+        assert!(float_eq(report.downs_in_one_round, 0.0));
+    }
+}
@@ -0,0 +1,408 @@
+use crate::logging::{self, LogLevel};
+use std::fmt;
+
+// Spell lists grouped by rank, from the highest rank down:
+//
+// spells 4th: fireball, haste; 3rd: slow; cantrips (4th): shield; focus (4th): ...
+//
+// The top rank of a spellcaster is half its level, rounded up. Scaling a spell
+// list moves every group by the change in the top rank, so the creature keeps
+// the shape of its spell list.
+
+pub const MAX_SPELL_RANK: i32 = 10;
+
+pub fn top_spell_rank(level: i32) -> i32 {
+    let rank = if level > 0 { (level + 1) / 2 } else { 1 };
+
+    rank.clamp(1, MAX_SPELL_RANK)
+}
+
+pub fn format_rank(rank: i32) -> String {
+    let suffix = match (rank % 10, rank % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+
+    format!("{}{}", rank, suffix)
+}
+
+pub fn parse_rank(rank: &str) -> Option<i32> {
+    let rank = rank.trim().to_lowercase();
+    let digits = rank.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+
+    match digits.parse::<i32>() {
+        Ok(r) if (1..=MAX_SPELL_RANK).contains(&r) && format_rank(r) == rank => Some(r),
+        _ => {
+            logging::log(LogLevel::Error, format!("invalid spell rank: {}", rank));
+            None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SpellGroupKind {
+    Rank(i32),
+    // Cantrips and focus spells are heightened to the given rank.
+    Cantrips(i32),
+    Focus(i32),
+}
+
+impl fmt::Display for SpellGroupKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpellGroupKind::Rank(r) => write!(f, "{}", format_rank(*r)),
+            SpellGroupKind::Cantrips(r) => write!(f, "cantrips ({})", format_rank(*r)),
+            SpellGroupKind::Focus(r) => write!(f, "focus ({})", format_rank(*r)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpellEntry {
+    pub name: String,
+    // The spell was moved to a lower rank than it was cast at, which it may
+    // not be available at.
+    pub needs_repick: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpellGroup {
+    pub kind: SpellGroupKind,
+    pub spells: Vec<SpellEntry>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpellList {
+    pub groups: Vec<SpellGroup>,
+}
+
+impl fmt::Display for SpellList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let groups: Vec<String> = self
+            .groups
+            .iter()
+            .map(|g| {
+                let spells: Vec<String> = g
+                    .spells
+                    .iter()
+                    .map(|s| {
+                        if s.needs_repick {
+                            format!("{} (re-pick)", s.name)
+                        } else {
+                            s.name.clone()
+                        }
+                    })
+                    .collect();
+
+                if spells.is_empty() {
+                    format!("{}: (pick spells)", g.kind)
+                } else {
+                    format!("{}: {}", g.kind, spells.join(", "))
+                }
+            })
+            .collect();
+
+        write!(f, "spells {}", groups.join("; "))
+    }
+}
+
+const KEYWORD: &str = "spells ";
+
+pub fn is_spell_list(line: &str) -> bool {
+    line.trim_start()
+        .get(..KEYWORD.len())
+        .is_some_and(|k| k.eq_ignore_ascii_case(KEYWORD))
+}
+
+fn log_invalid_group(group: &str) {
+    logging::log(
+        LogLevel::Error,
+        format!("invalid spell group: {}", group.trim()),
+    );
+    logging::log(
+        LogLevel::Info,
+        "usage: <rank>: <spell>, ... or cantrips (<rank>): <spell>, ...",
+    );
+}
+
+// Parse a "cantrips (4th)" style group name, which heightens to the given rank.
+fn parse_heightened(name: &str, keyword: &str) -> Option<Option<i32>> {
+    let rest = match name.strip_prefix(keyword) {
+        Some(r) => r.trim(),
+        None => return Some(None),
+    };
+
+    match rest.strip_prefix('(').and_then(|r| r.strip_suffix(')')) {
+        Some(rank) => Some(Some(parse_rank(rank)?)),
+        None => {
+            log_invalid_group(name);
+            None
+        }
+    }
+}
+
+fn parse_group(group: &str, top_rank: i32) -> Option<SpellGroup> {
+    let (name, spells) = match group.split_once(':') {
+        Some(g) => g,
+        None => {
+            log_invalid_group(group);
+            return None;
+        }
+    };
+    let name = name.trim().to_lowercase();
+
+    let kind = if name == "cantrips" {
+        SpellGroupKind::Cantrips(top_rank)
+    } else if name == "focus" {
+        SpellGroupKind::Focus(top_rank)
+    } else if let Some(rank) = parse_heightened(&name, "cantrips")? {
+        SpellGroupKind::Cantrips(rank)
+    } else if let Some(rank) = parse_heightened(&name, "focus")? {
+        SpellGroupKind::Focus(rank)
+    } else {
+        SpellGroupKind::Rank(parse_rank(&name)?)
+    };
+
+    let spells: Vec<SpellEntry> = spells
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| SpellEntry {
+            name: String::from(s),
+            needs_repick: false,
+        })
+        .collect();
+
+    if spells.is_empty() {
+        log_invalid_group(group);
+        return None;
+    }
+
+    Some(SpellGroup { kind, spells })
+}
+
+// Parse a spell list line. Cantrips and focus spells without a rank are
+// heightened to the top rank of the given level. Spell names keep their case.
+pub fn parse_spell_list(line: &str, level: i32) -> Option<SpellList> {
+    if !is_spell_list(line) {
+        logging::log(
+            LogLevel::Error,
+            format!("invalid spell list: {}", line.trim()),
+        );
+        return None;
+    }
+    let rest = &line.trim_start()[KEYWORD.len()..];
+
+    let mut groups: Vec<SpellGroup> = Vec::new();
+    for group in rest.split(';') {
+        groups.push(parse_group(group, top_spell_rank(level))?);
+    }
+
+    Some(SpellList { groups })
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpellShift {
+    pub list: SpellList,
+    pub old_top_rank: i32,
+    pub new_top_rank: i32,
+    // Spells whose rank fell below 1st.
+    pub removed: Vec<String>,
+}
+
+// Move every rank group by the change in the top rank. Spells moved down are
+// flagged to be re-picked, and ranks left empty at the bottom are added as
+// empty groups to fill in. Cantrips and focus spells heighten to the new top rank.
+pub fn shift_spell_list(list: &SpellList, current_level: i32, target_level: i32) -> SpellShift {
+    let old_top_rank = top_spell_rank(current_level);
+    let new_top_rank = top_spell_rank(target_level);
+    let delta = new_top_rank - old_top_rank;
+
+    let mut ranks: Vec<SpellGroup> = Vec::new();
+    let mut others: Vec<SpellGroup> = Vec::new();
+    let mut removed: Vec<String> = Vec::new();
+
+    for group in &list.groups {
+        match group.kind {
+            SpellGroupKind::Rank(rank) => {
+                let rank = rank + delta;
+                if rank < 1 {
+                    removed.extend(group.spells.iter().map(|s| s.name.clone()));
+                    continue;
+                }
+
+                let spells = group
+                    .spells
+                    .iter()
+                    .map(|s| SpellEntry {
+                        name: s.name.clone(),
+                        needs_repick: s.needs_repick || delta < 0,
+                    })
+                    .collect();
+
+                ranks.push(SpellGroup {
+                    kind: SpellGroupKind::Rank(rank.min(MAX_SPELL_RANK)),
+                    spells,
+                });
+            }
+            SpellGroupKind::Cantrips(_) => others.push(SpellGroup {
+                kind: SpellGroupKind::Cantrips(new_top_rank),
+                spells: group.spells.clone(),
+            }),
+            SpellGroupKind::Focus(_) => others.push(SpellGroup {
+                kind: SpellGroupKind::Focus(new_top_rank),
+                spells: group.spells.clone(),
+            }),
+        }
+    }
+
+    // The ranks the list used to cover at the bottom are now empty.
+    let lowest = list
+        .groups
+        .iter()
+        .filter_map(|g| match g.kind {
+            SpellGroupKind::Rank(r) => Some(r),
+            _ => None,
+        })
+        .min();
+
+    if let Some(lowest) = lowest {
+        for rank in (lowest..lowest + delta).rev() {
+            if !ranks.iter().any(|g| g.kind == SpellGroupKind::Rank(rank)) {
+                ranks.push(SpellGroup {
+                    kind: SpellGroupKind::Rank(rank),
+                    spells: Vec::new(),
+                });
+            }
+        }
+    }
+
+    ranks.append(&mut others);
+
+    SpellShift {
+        list: SpellList { groups: ranks },
+        old_top_rank,
+        new_top_rank,
+        removed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(group: &SpellGroup) -> Vec<&str> {
+        group.spells.iter().map(|s| s.name.as_str()).collect()
+    }
+
+    #[test]
+    fn compute_top_spell_rank() {
+        assert_eq!(top_spell_rank(-1), 1);
+        assert_eq!(top_spell_rank(0), 1);
+        assert_eq!(top_spell_rank(1), 1);
+        assert_eq!(top_spell_rank(2), 1);
+        assert_eq!(top_spell_rank(3), 2);
+        assert_eq!(top_spell_rank(7), 4);
+        assert_eq!(top_spell_rank(8), 4);
+        assert_eq!(top_spell_rank(19), 10);
+        assert_eq!(top_spell_rank(30), 10);
+    }
+
+    #[test]
+    fn rank_round_trip() {
+        for rank in 1..=MAX_SPELL_RANK {
+            assert_eq!(parse_rank(&format_rank(rank)), Some(rank));
+        }
+
+        assert_eq!(format_rank(1), "1st");
+        assert_eq!(format_rank(2), "2nd");
+        assert_eq!(format_rank(3), "3rd");
+        assert_eq!(format_rank(4), "4th");
+        assert_eq!(parse_rank("3RD"), Some(3));
+        assert_eq!(parse_rank("3th"), None);
+        assert_eq!(parse_rank("11th"), None);
+        assert_eq!(parse_rank("0th"), None);
+        assert_eq!(parse_rank("fourth"), None);
+    }
+
+    #[test]
+    fn accept_valid_spell_list() {
+        let list = parse_spell_list(
+            "spells 4th: Fireball, haste; 3rd: slow; cantrips (4th): shield, detect magic",
+            7,
+        )
+        .unwrap();
+
+        assert_eq!(list.groups.len(), 3);
+        assert_eq!(list.groups[0].kind, SpellGroupKind::Rank(4));
+        assert_eq!(names(&list.groups[0]), vec!["Fireball", "haste"]);
+        assert_eq!(list.groups[2].kind, SpellGroupKind::Cantrips(4));
+        assert_eq!(names(&list.groups[2]), vec!["shield", "detect magic"]);
+
+        // Cantrips and focus spells default to the top rank.
+        let list = parse_spell_list("Spells cantrips: shield; focus: fire ray", 5).unwrap();
+        assert_eq!(list.groups[0].kind, SpellGroupKind::Cantrips(3));
+        assert_eq!(list.groups[1].kind, SpellGroupKind::Focus(3));
+    }
+
+    #[test]
+    fn reject_invalid_spell_list() {
+        assert_eq!(parse_spell_list("spells", 5), None);
+        assert_eq!(parse_spell_list("spells fireball", 5), None);
+        assert_eq!(parse_spell_list("spells 4th:", 5), None);
+        assert_eq!(parse_spell_list("spells 4th: haste; 3rd", 5), None);
+        assert_eq!(parse_spell_list("spells 12th: wish", 5), None);
+        assert_eq!(parse_spell_list("spells cantrips (x): shield", 5), None);
+        assert_eq!(parse_spell_list("spells cantrips 4th: shield", 5), None);
+    }
+
+    #[test]
+    fn shift_spell_list_up() {
+        let list = parse_spell_list(
+            "spells 2nd: invisibility; 1st: fear, sleep; cantrips (2nd): shield",
+            3,
+        )
+        .unwrap();
+
+        let shift = shift_spell_list(&list, 3, 7);
+        assert_eq!(shift.old_top_rank, 2);
+        assert_eq!(shift.new_top_rank, 4);
+        assert!(shift.removed.is_empty());
+
+        assert_eq!(
+            shift.list.to_string(),
+            "spells 4th: invisibility; 3rd: fear, sleep; 2nd: (pick spells); 1st: (pick spells); cantrips (4th): shield"
+        );
+    }
+
+    #[test]
+    fn shift_spell_list_down() {
+        let list = parse_spell_list(
+            "spells 3rd: fireball; 2nd: invisibility; 1st: fear; focus (3rd): fire ray",
+            5,
+        )
+        .unwrap();
+
+        let shift = shift_spell_list(&list, 5, 2);
+        assert_eq!(shift.new_top_rank, 1);
+        assert_eq!(shift.removed, vec!["invisibility", "fear"]);
+
+        assert_eq!(
+            shift.list.to_string(),
+            "spells 1st: fireball (re-pick); focus (1st): fire ray"
+        );
+    }
+
+    #[test]
+    fn shift_spell_list_same_rank() {
+        let line = "spells 4th: haste; 3rd: slow; cantrips (4th): shield";
+        let list = parse_spell_list(line, 7).unwrap();
+
+        let shift = shift_spell_list(&list, 7, 8);
+        assert_eq!(shift.list, list);
+        assert_eq!(shift.list.to_string(), line);
+    }
+}
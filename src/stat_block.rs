@@ -0,0 +1,1040 @@
+use crate::damage::{self, Damage};
+use crate::damage_types;
+use crate::logging::{self, LogLevel};
+use crate::statistic::{self, SavingThrowType, SkillType, StatType};
+use crate::strike::{self, CriticalTrait, Strike};
+use crate::suggest;
+use std::fmt;
+
+// Lines pasted from official stat blocks, which put several statistics on
+// one line:
+//
+// AC 27; Fort +21, Ref +15, Will +18
+// Skills Acrobatics +14, Athletics +18, Stealth +14
+// Melee [one-action] jaws +19 (reach 10 feet), Damage 2d12+17 piercing plus Grab
+// Venom (poison) Saving Throw DC 22 Fortitude; Onset 1 round; Stage 1 1d6 poison (1 round)
+//
+// Afflictions may also go on over the next lines, like:
+//
+// Venom (poison) Saving Throw DC 22 Fortitude; Onset 1 round
+// Stage 1 1d6 poison (1 round); Stage 2 2d6 poison (1 round)
+//
+// A line is split into the values in it and the text around them, so the
+// scaled values can be put back into the same layout.
+
+// Keywords that start a stat block line. AC, Perception and HP lines that
+// have only one statistic are read as prompts instead.
+const LIST_KEYWORDS: &[&str] = &["AC", "Perception", "HP"];
+const STRIKE_KEYWORDS: &[&str] = &["Melee", "Ranged"];
+const SKILLS_KEYWORD: &str = "Skills";
+// Afflictions start with their name, so they are found by these instead.
+const AFFLICTION_DC: &str = "Saving Throw DC ";
+const AFFLICTION_STAGE: &str = "Stage ";
+// The entries that an affliction going on from the line before may start with.
+const AFFLICTION_ENTRIES: &[&str] = &["Onset ", "Maximum Duration ", AFFLICTION_STAGE];
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Part {
+    // Text that is kept as it is.
+    Text(String),
+    // The label tells apart statistics of the same kind, like the damage
+    // type of a resistance or the name of a Strike.
+    Value {
+        kind: StatType,
+        label: Option<String>,
+        value: f64,
+    },
+    Damage {
+        kind: StatType,
+        label: Option<String>,
+        damage: Damage,
+    },
+    // The attack bonuses of further Strikes in a turn, like [+15/+11]. They
+    // change with the attack bonus before them.
+    MultipleAttacks(Vec<f64>),
+    // Deadly and fatal traits, which change with the weapon damage dice.
+    CriticalTrait(CriticalTrait),
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Part::Text(text) => write!(f, "{}", text),
+            Part::Value { kind, value, .. } => write!(f, "{}", format_value(kind, *value)),
+            Part::Damage { damage, .. } => write!(f, "{}", format_damage(damage)),
+            Part::MultipleAttacks(values) => {
+                let values: Vec<String> = values.iter().map(|v| format!("{:+}", v)).collect();
+                write!(f, "[{}]", values.join("/"))
+            }
+            Part::CriticalTrait(critical) => write!(f, "{}", critical),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StatBlockLine {
+    pub parts: Vec<Part>,
+    // The Strike of a Melee or Ranged line with damage.
+    pub strike: Option<Strike>,
+}
+
+impl fmt::Display for StatBlockLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for part in &self.parts {
+            write!(f, "{}", part)?;
+        }
+
+        Ok(())
+    }
+}
+
+// Bonuses are written with their sign, other values without.
+pub fn format_value(kind: &StatType, value: f64) -> String {
+    if statistic::is_bonus(kind) {
+        format!("{:+}", value)
+    } else {
+        format!("{}", value)
+    }
+}
+
+fn format_damage(damage: &Damage) -> String {
+    damage
+        .components
+        .iter()
+        .map(|c| match &c.roll {
+            Some(r) => format!("{} {}", r, c.description()),
+            None => format!("{} {}", c.average_value, c.description()),
+        })
+        .collect::<Vec<String>>()
+        .join(" plus ")
+}
+
+fn first_word(line: &str) -> &str {
+    line.split_whitespace().next().unwrap_or("")
+}
+
+// Stat block keywords are matched with the case of the book, so that prompts
+// like "ac 15" are never mistaken for them.
+pub fn is_stat_block_line(line: &str) -> bool {
+    let keyword = first_word(line);
+
+    if keyword == SKILLS_KEYWORD || STRIKE_KEYWORDS.contains(&keyword) {
+        return true;
+    }
+
+    (LIST_KEYWORDS.contains(&keyword) && line.contains([';', ',']))
+        || is_affliction(line)
+        || is_affliction_entries(line)
+}
+
+// The first line of an affliction, whose stages may be on the same line or
+// on the next ones.
+pub fn is_affliction(line: &str) -> bool {
+    line.contains(AFFLICTION_DC)
+}
+
+// A line that goes on with the entries of the affliction before it, like
+// "Stage 2 2d6 poison (1 round)".
+fn is_affliction_entries(line: &str) -> bool {
+    let line = line.trim_start();
+
+    match line.strip_prefix(AFFLICTION_STAGE) {
+        Some(rest) => is_number(first_word(rest)),
+        None => AFFLICTION_ENTRIES.iter().any(|e| line.starts_with(e)),
+    }
+}
+
+fn is_number(token: &str) -> bool {
+    let digits = token
+        .strip_prefix('+')
+        .or(token.strip_prefix('-'))
+        .unwrap_or(token);
+
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+// The whitespace separated tokens of a text with their byte offsets.
+fn tokens(text: &str) -> Vec<(usize, &str)> {
+    let mut result = Vec::new();
+    let mut start = None;
+
+    for (i, c) in text.char_indices() {
+        match (c.is_whitespace(), start) {
+            (true, Some(s)) => {
+                result.push((s, &text[s..i]));
+                start = None;
+            }
+            (false, None) => start = Some(i),
+            _ => (),
+        }
+    }
+
+    if let Some(s) = start {
+        result.push((s, &text[s..]));
+    }
+
+    result
+}
+
+// Split a text on a separator outside of parentheses, so that traits like
+// (agile, reach 10 feet) stay together.
+fn split_top_level(text: &str, separator: char) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            _ if c == separator && depth <= 0 => {
+                pieces.push(&text[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => (),
+        }
+    }
+
+    pieces.push(&text[start..]);
+    pieces
+}
+
+fn push_text(parts: &mut Vec<Part>, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+
+    match parts.last_mut() {
+        Some(Part::Text(t)) => t.push_str(text),
+        _ => parts.push(Part::Text(String::from(text))),
+    }
+}
+
+// A trait of a Strike's trait list, split out of the text if it's deadly or fatal.
+fn push_trait(parts: &mut Vec<Part>, text: &str) {
+    let trimmed = text.trim();
+
+    match strike::parse_critical_trait_at(trimmed, 0) {
+        Some(Ok(critical)) => {
+            let start = text.len() - text.trim_start().len();
+            push_text(parts, &text[..start]);
+            parts.push(Part::CriticalTrait(critical));
+            push_text(parts, &text[start + trimmed.len()..]);
+        }
+        _ => push_text(parts, text),
+    }
+}
+
+// Text with a trait list, like " (deadly d10, reach 10 feet), ".
+fn push_traits_text(parts: &mut Vec<Part>, text: &str) {
+    let mut start = 0;
+
+    for (i, c) in text.char_indices() {
+        if matches!(c, '(' | ',' | ')') {
+            push_trait(parts, &text[start..i]);
+            push_text(parts, &text[i..i + 1]);
+            start = i + 1;
+        }
+    }
+    push_trait(parts, &text[start..]);
+}
+
+const SKILL_NAMES: [&str; 16] = [
+    "acrobatics",
+    "arcana",
+    "athletics",
+    "crafting",
+    "deception",
+    "diplomacy",
+    "intimidation",
+    "medicine",
+    "nature",
+    "occultism",
+    "performance",
+    "religion",
+    "society",
+    "stealth",
+    "survival",
+    "thievery",
+];
+
+fn skill_kind(name: &str) -> Option<SkillType> {
+    match name {
+        "acrobatics" => Some(SkillType::Acrobatics),
+        "arcana" => Some(SkillType::Arcana),
+        "athletics" => Some(SkillType::Athletics),
+        "crafting" => Some(SkillType::Crafting),
+        "deception" => Some(SkillType::Deception),
+        "diplomacy" => Some(SkillType::Diplomacy),
+        "intimidation" => Some(SkillType::Intimidation),
+        "medicine" => Some(SkillType::Medicine),
+        "nature" => Some(SkillType::Nature),
+        "occultism" => Some(SkillType::Occultism),
+        "performance" => Some(SkillType::Performance),
+        "religion" => Some(SkillType::Religion),
+        "society" => Some(SkillType::Society),
+        "stealth" => Some(SkillType::Stealth),
+        "survival" => Some(SkillType::Survival),
+        "thievery" => Some(SkillType::Thievery),
+        _ => None,
+    }
+}
+
+// The subject of a Lore skill, written "Warfare Lore" or "Lore (warfare)".
+pub fn lore_subject(name: &str) -> Option<&str> {
+    let subject = match name.strip_suffix(" lore") {
+        Some(s) => s,
+        None => name
+            .strip_prefix("lore (")
+            .and_then(|s| s.strip_suffix(')'))?,
+    };
+
+    Some(subject.trim())
+}
+
+// A skill name that is close to a skill of the core rules, like "acrobatcs",
+// is taken for a typo rather than a custom skill.
+fn misspelled_skill(name: &str) -> Option<&'static str> {
+    let name = name.to_lowercase();
+    if skill_kind(&name).is_some() || lore_subject(&name).is_some() || name == "lore" {
+        return None;
+    }
+
+    suggest::suggest(&name, &SKILL_NAMES)
+}
+
+// The statistic that a name in a list stands for. Lists of resistances and
+// weaknesses are headed by a keyword, and their items are damage types.
+// Skills that aren't in the core rules are custom skills.
+fn list_item_kind(header: Option<&str>, name: &str) -> Option<(StatType, Option<String>)> {
+    let name = name.to_lowercase();
+
+    match header {
+        Some("skills") => match (lore_subject(&name), skill_kind(&name)) {
+            (Some(subject), _) => Some((
+                StatType::Skill(SkillType::Lore(String::from(subject))),
+                None,
+            )),
+            (None, Some(skill)) => Some((StatType::Skill(skill), None)),
+            (None, None) if name == "lore" => {
+                Some((StatType::Skill(SkillType::Lore(String::new())), None))
+            }
+            (None, None) if misspelled_skill(&name).is_some() => None,
+            (None, None) if !name.is_empty() => {
+                Some((StatType::Skill(SkillType::Custom(name)), None))
+            }
+            (None, None) => None,
+        },
+        Some("resistances") if damage_types::is_broad(&name) => {
+            Some((StatType::BroadResistance, Some(name)))
+        }
+        Some("resistances") if !name.is_empty() => Some((StatType::Resistance, Some(name))),
+        Some("weaknesses") if !name.is_empty() => Some((StatType::Weakness, Some(name))),
+        Some(_) => None,
+        None => match name.as_str() {
+            "ac" => Some((StatType::ArmorClass, None)),
+            "fort" | "fortitude" => Some((StatType::SavingThrow(SavingThrowType::Fortitude), None)),
+            "ref" | "reflex" => Some((StatType::SavingThrow(SavingThrowType::Reflex), None)),
+            "will" => Some((StatType::SavingThrow(SavingThrowType::Will), None)),
+            "perception" => Some((StatType::Perception, None)),
+            "hp" => Some((StatType::HitPoints, None)),
+            _ => None,
+        },
+    }
+}
+
+// An item of a list is a name followed by a value, like "Fort +21" or
+// "cold 10". Any text after the value is kept, and items that aren't
+// statistics are kept as text. Returns the name of an item that has a value
+// but isn't a statistic.
+fn parse_list_item<'a>(
+    parts: &mut Vec<Part>,
+    header: Option<&str>,
+    item: &'a str,
+) -> Option<&'a str> {
+    let item_tokens = tokens(item);
+    let value_index = item_tokens
+        .iter()
+        .skip(1)
+        .position(|(_, t)| is_number(t))
+        .map(|i| i + 1);
+
+    let (start, text) = match value_index {
+        Some(i) => item_tokens[i],
+        None => {
+            push_text(parts, item);
+            return None;
+        }
+    };
+
+    let name = item[..start].trim();
+    let (kind, label) = match list_item_kind(header, name) {
+        Some(k) => k,
+        None => {
+            push_text(parts, item);
+            return Some(name);
+        }
+    };
+    let value: f64 = match text.parse::<i32>() {
+        Ok(v) => v.into(),
+        Err(_) => {
+            push_text(parts, item);
+            return None;
+        }
+    };
+
+    push_text(parts, &item[..start]);
+    parts.push(Part::Value { kind, label, value });
+    push_text(parts, &item[start + text.len()..]);
+    None
+}
+
+// Lines of statistics separated by semicolons and commas, such as saving
+// throws, skills, and HP with resistances and weaknesses. Never logs, since
+// anything that isn't a statistic is kept as text.
+pub fn parse_list_line(line: &str) -> Vec<Part> {
+    parse_list_parts(line, &mut Vec::new())
+}
+
+// Like parse_list_line, also collecting the skills that look misspelled with
+// the skill they are close to.
+fn parse_list_parts<'a>(line: &'a str, misspelled: &mut Vec<(&'a str, &'static str)>) -> Vec<Part> {
+    let mut parts = Vec::new();
+
+    for (i, segment) in split_top_level(line, ';').into_iter().enumerate() {
+        if i > 0 {
+            push_text(&mut parts, ";");
+        }
+
+        // The keyword that heads a list, like "Skills" or "Resistances".
+        let header_word = first_word(segment).to_lowercase();
+        let header = match header_word.as_str() {
+            "skills" | "resistances" | "weaknesses" => Some(header_word.as_str()),
+            _ => None,
+        };
+
+        let mut segment = segment;
+        if header.is_some() {
+            let end = segment.find(first_word(segment)).unwrap_or(0) + header_word.len();
+            push_text(&mut parts, &segment[..end]);
+            segment = &segment[end..];
+        }
+
+        for (j, item) in split_top_level(segment, ',').into_iter().enumerate() {
+            if j > 0 {
+                push_text(&mut parts, ",");
+            }
+
+            let name = parse_list_item(&mut parts, header, item);
+            if let (Some("skills"), Some(name)) = (header, name) {
+                if let Some(skill) = misspelled_skill(name) {
+                    misspelled.push((name, skill));
+                }
+            }
+        }
+    }
+
+    parts
+}
+
+// Find " plus " separators case-insensitively, returning the pieces between them.
+fn split_plus(text: &str) -> Vec<&str> {
+    let lowercase = text.to_ascii_lowercase();
+    let mut pieces = Vec::new();
+    let mut start = 0;
+
+    for (i, separator) in lowercase.match_indices(" plus ") {
+        pieces.push(&text[start..i]);
+        start = i + separator.len();
+    }
+
+    pieces.push(&text[start..]);
+    pieces
+}
+
+// The damage at the start of a Damage entry, and the length of the text it
+// was read from. Effects after the damage, like "plus Grab", aren't damage.
+fn parse_leading_damage(text: &str) -> (Damage, usize) {
+    let mut damage = Damage::new();
+    let mut end = 0;
+    let mut offset = 0;
+
+    for (i, piece) in split_plus(text).into_iter().enumerate() {
+        let starts_with_digit = piece.trim_start().starts_with(|c: char| c.is_ascii_digit());
+        let component = match damage::parse_damage_at(piece, 0) {
+            Ok(d) if starts_with_digit => d,
+            _ => break,
+        };
+
+        damage.components.extend(component.components);
+        if i > 0 {
+            offset += " plus ".len();
+        }
+        offset += piece.len();
+        end = offset;
+    }
+
+    (damage, end)
+}
+
+fn log_invalid_strike(line: &str) {
+    logging::log(
+        LogLevel::Error,
+        format!("invalid stat block Strike: {}", line.trim()),
+    );
+    logging::log(
+        LogLevel::Info,
+        "usage: Melee [one-action] <name> +<bonus> [(<trait>, ...)], Damage <damage>",
+    );
+}
+
+// Melee and Ranged lines. The action icon is pasted as text like
+// [one-action] or as a symbol, and is kept as it is.
+fn parse_strike_line(line: &str) -> Option<StatBlockLine> {
+    let line_tokens = tokens(line);
+    let bonus_index = match line_tokens.iter().position(|(_, t)| is_number(t)) {
+        Some(i) if i > 1 => i,
+        _ => {
+            log_invalid_strike(line);
+            return None;
+        }
+    };
+
+    let name: Vec<&str> = line_tokens[1..bonus_index]
+        .iter()
+        .map(|(_, t)| *t)
+        .skip_while(|t| t.starts_with('[') || !t.starts_with(char::is_alphanumeric))
+        .collect();
+    if name.is_empty() {
+        log_invalid_strike(line);
+        return None;
+    }
+    let name = name.join(" ");
+
+    let (bonus_start, bonus) = line_tokens[bonus_index];
+    let attack_bonus: f64 = match bonus.parse::<i32>() {
+        Ok(b) => b.into(),
+        Err(_) => {
+            log_invalid_strike(line);
+            return None;
+        }
+    };
+
+    let mut parts = Vec::new();
+    push_text(&mut parts, &line[..bonus_start]);
+    parts.push(Part::Value {
+        kind: StatType::StrikeAttackBonus,
+        label: Some(name.clone()),
+        value: attack_bonus,
+    });
+    let mut rest = &line[bonus_start + bonus.len()..];
+
+    // Attack bonuses with the multiple attack penalty, like [+15/+11].
+    let trimmed = rest.trim_start();
+    if let Some((inner, after)) = trimmed.strip_prefix('[').and_then(|t| t.split_once(']')) {
+        let values: Vec<&str> = inner.split('/').map(|v| v.trim()).collect();
+        if values.iter().all(|v| is_number(v)) {
+            push_text(&mut parts, &rest[..rest.len() - trimmed.len()]);
+            parts.push(Part::MultipleAttacks(
+                values
+                    .iter()
+                    .filter_map(|v| v.parse::<f64>().ok())
+                    .collect(),
+            ));
+            rest = after;
+        }
+    }
+
+    let traits = match rest.trim_start().strip_prefix('(') {
+        Some(t) => match t.split_once(')') {
+            Some((traits, _)) => traits
+                .split(',')
+                .map(|t| t.trim())
+                .filter(|t| !t.is_empty())
+                .map(String::from)
+                .collect(),
+            None => Vec::new(),
+        },
+        None => Vec::new(),
+    };
+
+    let damage_start = rest
+        .to_ascii_lowercase()
+        .find("damage ")
+        .map(|i| i + "damage ".len());
+    let (damage, damage_end) = match damage_start {
+        Some(start) => {
+            let (damage, length) = parse_leading_damage(&rest[start..]);
+            (damage, start + length)
+        }
+        None => (Damage::new(), 0),
+    };
+
+    if damage.components.is_empty() {
+        push_text(&mut parts, rest);
+        return Some(StatBlockLine {
+            parts,
+            strike: None,
+        });
+    }
+
+    let damage_start = damage_start.expect("damage should follow the Damage keyword");
+    push_traits_text(&mut parts, &rest[..damage_start]);
+    parts.push(Part::Damage {
+        kind: StatType::StrikeDamage,
+        label: Some(name.clone()),
+        damage: damage.clone(),
+    });
+    push_text(&mut parts, &rest[damage_end..]);
+
+    Some(StatBlockLine {
+        parts,
+        strike: Some(Strike {
+            name,
+            attack_bonus,
+            traits,
+            damage,
+        }),
+    })
+}
+
+fn log_invalid_affliction(line: &str) {
+    logging::log(
+        LogLevel::Error,
+        format!("invalid affliction: {}", line.trim()),
+    );
+    logging::log(
+        LogLevel::Info,
+        "usage: <name> [(<trait>, ...)] Saving Throw DC <dc> <save>; [Onset <time>;] [Maximum Duration <time>;] Stage 1 <effects> (<time>); ...",
+    );
+}
+
+// The damage at the start of the effects of a stage. Conditions after the
+// damage, like "and enfeebled 1", are kept as they are. Stages on a line of
+// their own don't know the name of their affliction.
+//
+// A stage hurts a single creature, usually one hit by a Strike, so its damage
+// scales like a Strike's rather than like area damage.
+fn push_stage(parts: &mut Vec<Part>, entry: &str, name: Option<&str>) {
+    let trimmed = entry.trim_start();
+    let after_keyword = &trimmed[AFFLICTION_STAGE.len()..];
+    let stage = after_keyword.split_whitespace().next().unwrap_or("");
+    let effects_start =
+        entry.len() - after_keyword.len() + after_keyword.find(stage).unwrap_or(0) + stage.len();
+    let effects = &entry[effects_start..];
+
+    let damage_end = [" and ", ",", " ("]
+        .iter()
+        .filter_map(|s| effects.find(s))
+        .min()
+        .unwrap_or(effects.len());
+    let (damage, length) = parse_leading_damage(&effects[..damage_end]);
+
+    if !is_number(stage) || damage.components.is_empty() {
+        push_text(parts, entry);
+        return;
+    }
+
+    let damage_start = effects.len() - effects.trim_start().len();
+    push_text(parts, &entry[..effects_start + damage_start]);
+    parts.push(Part::Damage {
+        kind: StatType::StrikeDamage,
+        label: Some(match name {
+            Some(name) => format!("{} stage {}", name, stage),
+            None => format!("stage {}", stage),
+        }),
+        damage,
+    });
+    push_text(parts, &effects[length..]);
+}
+
+// Poisons, diseases and curses. The DC scales like an ability DC and the
+// damage of each stage like Strike damage. Onsets, durations and conditions
+// don't change.
+fn parse_affliction_line(line: &str) -> Option<StatBlockLine> {
+    let dc_keyword = line.find(AFFLICTION_DC)?;
+    let name = line[..dc_keyword]
+        .split('(')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .trim_matches('*')
+        .trim();
+
+    let dc_start = dc_keyword + AFFLICTION_DC.len();
+    let dc = line[dc_start..].split_whitespace().next().unwrap_or("");
+    let dc_start = dc_start + line[dc_start..].find(dc).unwrap_or(0);
+    let value: f64 = match dc.trim_end_matches([',', ';']).parse::<i32>() {
+        Ok(v) if !name.is_empty() => v.into(),
+        _ => {
+            log_invalid_affliction(line);
+            return None;
+        }
+    };
+    let dc_end = dc_start + dc.trim_end_matches([',', ';']).len();
+
+    let mut parts = Vec::new();
+    push_text(&mut parts, &line[..dc_start]);
+    parts.push(Part::Value {
+        kind: StatType::AbilityDC,
+        label: Some(String::from(name)),
+        value,
+    });
+    push_affliction_entries(&mut parts, &line[dc_end..], Some(name));
+
+    Some(StatBlockLine {
+        parts,
+        strike: None,
+    })
+}
+
+fn push_affliction_entries(parts: &mut Vec<Part>, entries: &str, name: Option<&str>) {
+    for (i, entry) in split_top_level(entries, ';').into_iter().enumerate() {
+        if i > 0 {
+            push_text(parts, ";");
+        }
+
+        if entry.trim_start().starts_with(AFFLICTION_STAGE) {
+            push_stage(parts, entry, name);
+        } else {
+            push_text(parts, entry);
+        }
+    }
+}
+
+pub fn parse_stat_block_line(line: &str) -> Option<StatBlockLine> {
+    if STRIKE_KEYWORDS.contains(&first_word(line)) {
+        return parse_strike_line(line);
+    }
+
+    if is_affliction(line) {
+        return parse_affliction_line(line);
+    }
+
+    if is_affliction_entries(line) {
+        let mut parts = Vec::new();
+        push_affliction_entries(&mut parts, line, None);
+        return Some(StatBlockLine {
+            parts,
+            strike: None,
+        });
+    }
+
+    let mut misspelled = Vec::new();
+    let parts = parse_list_parts(line, &mut misspelled);
+    for (name, skill) in misspelled {
+        logging::log(
+            LogLevel::Warning,
+            format!("unknown skill, kept as it is: {}", name),
+        );
+        logging::log(LogLevel::Info, format!("did you mean {}?", skill));
+    }
+
+    for part in &parts {
+        if let Part::Value {
+            kind: StatType::Resistance | StatType::BroadResistance | StatType::Weakness,
+            label: Some(label),
+            ..
+        } = part
+        {
+            damage::check_resistance_type(label);
+        }
+    }
+
+    if !parts.iter().any(|p| !matches!(p, Part::Text(_))) {
+        logging::log(
+            LogLevel::Error,
+            format!("no statistics found in stat block line: {}", line.trim()),
+        );
+        return None;
+    }
+
+    Some(StatBlockLine {
+        parts,
+        strike: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::float_eq;
+
+    fn values(line: &StatBlockLine) -> Vec<(StatType, Option<String>, f64)> {
+        line.parts
+            .iter()
+            .filter_map(|p| match p {
+                Part::Value { kind, label, value } => Some((kind.clone(), label.clone(), *value)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn detect_stat_block_lines() {
+        assert!(is_stat_block_line("AC 27; Fort +21, Ref +15, Will +18"));
+        assert!(is_stat_block_line("Skills Acrobatics +14"));
+        assert!(is_stat_block_line(
+            "Melee [one-action] jaws +19, Damage 2d12+17 piercing"
+        ));
+        assert!(is_stat_block_line(
+            "Perception +20; darkvision, scent (imprecise) 30 feet"
+        ));
+
+        // Single statistics and lowercase keywords are prompts.
+        assert!(!is_stat_block_line("AC 27"));
+        assert!(!is_stat_block_line("ac 27; fort +21"));
+        assert!(!is_stat_block_line("strike jaws +19 2d12+17 piercing"));
+    }
+
+    #[test]
+    fn parse_defenses() {
+        let line = "AC 27; Fort +21, Ref +15, Will +18; +1 status to all saves vs. magic";
+        let parsed = parse_stat_block_line(line).unwrap();
+
+        assert_eq!(
+            values(&parsed),
+            vec![
+                (StatType::ArmorClass, None, 27.0),
+                (
+                    StatType::SavingThrow(SavingThrowType::Fortitude),
+                    None,
+                    21.0
+                ),
+                (StatType::SavingThrow(SavingThrowType::Reflex), None, 15.0),
+                (StatType::SavingThrow(SavingThrowType::Will), None, 18.0),
+            ]
+        );
+        assert_eq!(parsed.to_string(), line);
+        assert_eq!(parsed.strike, None);
+
+        let line = "HP 190; Immunities fire; Resistances cold 10, physical 5 (except silver); Weaknesses good 10";
+        let parsed = parse_stat_block_line(line).unwrap();
+
+        assert_eq!(
+            values(&parsed),
+            vec![
+                (StatType::HitPoints, None, 190.0),
+                (StatType::Resistance, Some(String::from("cold")), 10.0),
+                (
+                    StatType::BroadResistance,
+                    Some(String::from("physical")),
+                    5.0
+                ),
+                (StatType::Weakness, Some(String::from("good")), 10.0),
+            ]
+        );
+        assert_eq!(parsed.to_string(), line);
+    }
+
+    #[test]
+    fn parse_skills() {
+        let line = "Skills Acrobatics +14, Athletics +18, Forest Lore +12, Stealth +14";
+        let parsed = parse_stat_block_line(line).unwrap();
+
+        assert_eq!(
+            values(&parsed),
+            vec![
+                (StatType::Skill(SkillType::Acrobatics), None, 14.0),
+                (StatType::Skill(SkillType::Athletics), None, 18.0),
+                (
+                    StatType::Skill(SkillType::Lore(String::from("forest"))),
+                    None,
+                    12.0
+                ),
+                (StatType::Skill(SkillType::Stealth), None, 14.0),
+            ]
+        );
+        assert_eq!(parsed.to_string(), line);
+
+        let line = "Skills Lore (warfare) +12, Pathfinding +10";
+        let parsed = parse_stat_block_line(line).unwrap();
+        assert_eq!(
+            values(&parsed),
+            vec![
+                (
+                    StatType::Skill(SkillType::Lore(String::from("warfare"))),
+                    None,
+                    12.0
+                ),
+                (
+                    StatType::Skill(SkillType::Custom(String::from("pathfinding"))),
+                    None,
+                    10.0
+                ),
+            ]
+        );
+        assert_eq!(parsed.to_string(), line);
+
+        assert_eq!(lore_subject("warfare lore"), Some("warfare"));
+        assert_eq!(lore_subject("lore (warfare)"), Some("warfare"));
+        assert_eq!(lore_subject("lore"), None);
+        assert_eq!(lore_subject("folklore"), None);
+
+        // A misspelled skill is kept as text rather than taken for a custom skill.
+        let line = "Skills Acrobatcs +14, Athletics +18";
+        let parsed = parse_stat_block_line(line).unwrap();
+        assert_eq!(
+            values(&parsed),
+            vec![(StatType::Skill(SkillType::Athletics), None, 18.0)]
+        );
+        assert_eq!(parsed.to_string(), line);
+        assert_eq!(misspelled_skill("Acrobatcs"), Some("acrobatics"));
+        assert_eq!(misspelled_skill("Pathfinding"), None);
+
+        let line = "Perception +20; darkvision, scent (imprecise) 30 feet";
+        let parsed = parse_stat_block_line(line).unwrap();
+        assert_eq!(values(&parsed), vec![(StatType::Perception, None, 20.0)]);
+        assert_eq!(parsed.to_string(), line);
+    }
+
+    #[test]
+    fn parse_strikes() {
+        let line = "Melee [one-action] jaws +19 (reach 10 feet), Damage 2d12+17 piercing plus Grab";
+        let parsed = parse_stat_block_line(line).unwrap();
+
+        assert_eq!(
+            values(&parsed),
+            vec![(
+                StatType::StrikeAttackBonus,
+                Some(String::from("jaws")),
+                19.0
+            )]
+        );
+        assert_eq!(parsed.to_string(), line);
+
+        let strike = parsed.strike.unwrap();
+        assert_eq!(strike.name, "jaws");
+        assert_eq!(strike.traits, vec!["reach 10 feet"]);
+        assert!(float_eq(strike.damage.total_average_value(), 30.0));
+
+        let line = "Ranged ◆ tail spike +17 [+12/+7] (agile, range 60 feet), Damage 2d8+9 piercing plus 1d6 poison";
+        let parsed = parse_stat_block_line(line).unwrap();
+
+        assert_eq!(parsed.to_string(), line);
+        assert!(parsed
+            .parts
+            .contains(&Part::MultipleAttacks(vec![12.0, 7.0])));
+
+        let strike = parsed.strike.unwrap();
+        assert_eq!(strike.name, "tail spike");
+        assert!(strike.is_agile());
+        assert_eq!(strike.damage.components.len(), 2);
+
+        // A Strike without damage still has its attack bonus scaled.
+        let line = "Melee [one-action] tongue +15 (reach 20 feet), Effect Grab";
+        let parsed = parse_stat_block_line(line).unwrap();
+        assert_eq!(parsed.to_string(), line);
+        assert_eq!(parsed.strike, None);
+        assert_eq!(values(&parsed).len(), 1);
+
+        // Deadly and fatal traits are parts of their own.
+        let line = "Melee [one-action] pick +22 (deadly d10, fatal d12), Damage 3d6+12 piercing";
+        let parsed = parse_stat_block_line(line).unwrap();
+        assert_eq!(parsed.to_string(), line);
+        assert!(parsed
+            .parts
+            .contains(&Part::CriticalTrait(CriticalTrait::Deadly(damage::Dice {
+                count: 1,
+                size: 10
+            }))));
+        assert!(parsed
+            .parts
+            .contains(&Part::CriticalTrait(CriticalTrait::Fatal(12))));
+        assert_eq!(parsed.strike.unwrap().critical_traits().len(), 2);
+    }
+
+    #[test]
+    fn parse_afflictions() {
+        let line = "Giant Centipede Venom (poison) Saving Throw DC 14 Fortitude; Maximum Duration 6 rounds; Stage 1 1d8 poison (1 round); Stage 2 1d8 poison and off-guard (1 round); Stage 3 off-guard and clumsy 1 (1 round)";
+        assert!(is_stat_block_line(line));
+        let parsed = parse_stat_block_line(line).unwrap();
+        assert_eq!(parsed.to_string(), line);
+        assert_eq!(
+            values(&parsed),
+            vec![(
+                StatType::AbilityDC,
+                Some(String::from("Giant Centipede Venom")),
+                14.0
+            )]
+        );
+
+        // Stages without damage stay text.
+        let stages: Vec<(StatType, Option<String>, f64)> = parsed
+            .parts
+            .iter()
+            .filter_map(|p| match p {
+                Part::Damage {
+                    kind,
+                    label,
+                    damage,
+                } => Some((kind.clone(), label.clone(), damage.total_average_value())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            stages,
+            vec![
+                (
+                    StatType::StrikeDamage,
+                    Some(String::from("Giant Centipede Venom stage 1")),
+                    4.5
+                ),
+                (
+                    StatType::StrikeDamage,
+                    Some(String::from("Giant Centipede Venom stage 2")),
+                    4.5
+                ),
+            ]
+        );
+
+        let line = "Filth Fever (disease) Saving Throw DC 15 Fortitude; Onset 1d4 days; Stage 1 1d6 bludgeoning plus 1d6 void, enfeebled 1 (1 day)";
+        let parsed = parse_stat_block_line(line).unwrap();
+        assert_eq!(parsed.to_string(), line);
+
+        assert!(!is_stat_block_line("ability-dc venom 14"));
+        assert!(!is_stat_block_line("stage 1 1d8 poison"));
+        assert_eq!(
+            parse_stat_block_line("Venom Saving Throw DC Fortitude; Stage 1 1d8 poison"),
+            None
+        );
+
+        // An affliction may go on over the next lines.
+        let line = "Venom (poison) Saving Throw DC 22 Fortitude; Onset 1 round";
+        assert!(is_stat_block_line(line));
+        let parsed = parse_stat_block_line(line).unwrap();
+        assert_eq!(parsed.to_string(), line);
+        assert_eq!(
+            values(&parsed),
+            vec![(StatType::AbilityDC, Some(String::from("Venom")), 22.0)]
+        );
+
+        let line = "Stage 1 1d6 poison (1 round); Stage 2 off-guard (1 round)";
+        assert!(is_stat_block_line(line));
+        let parsed = parse_stat_block_line(line).unwrap();
+        assert_eq!(parsed.to_string(), line);
+        assert!(parsed.parts.contains(&Part::Damage {
+            kind: StatType::StrikeDamage,
+            label: Some(String::from("stage 1")),
+            damage: damage::parse_damage("1d6 poison").unwrap(),
+        }));
+
+        let line = "Maximum Duration 6 rounds; Stage 1 1d8 poison (1 round)";
+        assert!(is_stat_block_line(line));
+        assert_eq!(parse_stat_block_line(line).unwrap().to_string(), line);
+        assert_eq!(
+            parse_stat_block_line("Saving Throw DC 14 Fortitude; Stage 1 1d8 poison"),
+            None
+        );
+    }
+
+    #[test]
+    fn reject_invalid_stat_block_lines() {
+        assert_eq!(parse_stat_block_line("Skills none"), None);
+        assert_eq!(parse_stat_block_line("Melee [one-action] jaws"), None);
+        assert_eq!(
+            parse_stat_block_line("Melee +19, Damage 2d12 piercing"),
+            None
+        );
+    }
+}
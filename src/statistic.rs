@@ -7,7 +7,7 @@ pub enum SavingThrowType {
     Will,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum SkillType {
     Acrobatics,
     Arcana,
@@ -16,7 +16,8 @@ pub enum SkillType {
     Deception,
     Diplomacy,
     Intimidation,
-    Lore,
+    // The subject of the Lore, like "warfare", or empty for Lore in general.
+    Lore(String),
     Medicine,
     Nature,
     Occultism,
@@ -26,9 +27,12 @@ pub enum SkillType {
     Stealth,
     Survival,
     Thievery,
+    // A skill from third-party content, with its name, or empty for custom
+    // skills in general. Scales on the skills table like any other skill.
+    Custom(String),
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum StatType {
     Perception,
     Skill(SkillType),
@@ -36,10 +40,15 @@ pub enum StatType {
     SavingThrow(SavingThrowType),
     HitPoints,
     Resistance,
+    // Resistance to all damage or to a broad category like physical damage,
+    // which is lower than resistance to a single damage type.
+    BroadResistance,
     Weakness,
     StrikeAttackBonus,
     StrikeDamage,
     SpellDC,
+    // A non-spell DC, such as a breath weapon or poison DC. Uses the spell DC table.
+    AbilityDC,
     SpellAttackBonus,
     UnlimitedAreaDamage,
     LimitedAreaDamage,
@@ -47,6 +56,16 @@ pub enum StatType {
 
 impl fmt::Display for StatType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatType::Skill(SkillType::Lore(subject)) if !subject.is_empty() => {
+                return write!(f, "lore {}", subject);
+            }
+            StatType::Skill(SkillType::Custom(name)) if !name.is_empty() => {
+                return write!(f, "skill {}", name);
+            }
+            _ => {}
+        }
+
         let s = match self {
             StatType::Perception => "perception",
             StatType::Skill(kind) => match kind {
@@ -57,7 +76,7 @@ impl fmt::Display for StatType {
                 SkillType::Deception => "deception",
                 SkillType::Diplomacy => "diplomacy",
                 SkillType::Intimidation => "intimidation",
-                SkillType::Lore => "lore",
+                SkillType::Lore(_) => "lore",
                 SkillType::Medicine => "medicine",
                 SkillType::Nature => "nature",
                 SkillType::Occultism => "occultism",
@@ -67,6 +86,7 @@ impl fmt::Display for StatType {
                 SkillType::Stealth => "stealth",
                 SkillType::Survival => "survival",
                 SkillType::Thievery => "thievery",
+                SkillType::Custom(_) => "skill",
             },
             StatType::ArmorClass => "AC",
             StatType::SavingThrow(kind) => match kind {
@@ -76,10 +96,13 @@ impl fmt::Display for StatType {
             },
             StatType::HitPoints => "HP",
             StatType::Resistance => "resistance",
+            // Printed with the keyword it's written with in stat blocks.
+            StatType::BroadResistance => "resistance",
             StatType::Weakness => "weakness",
             StatType::StrikeAttackBonus => "strike-attack",
             StatType::StrikeDamage => "strike-damage",
             StatType::SpellDC => "spell-DC",
+            StatType::AbilityDC => "ability-DC",
             StatType::SpellAttackBonus => "spell-attack",
             StatType::UnlimitedAreaDamage => "unlimited-area-damage",
             StatType::LimitedAreaDamage => "limited-area-damage",
@@ -89,8 +112,23 @@ impl fmt::Display for StatType {
     }
 }
 
+impl StatType {
+    // Whether options given for this statistic apply to another one. Lore
+    // without a subject stands for the Lore of every subject, and a custom
+    // skill without a name for every custom skill.
+    pub fn covers(&self, other: &StatType) -> bool {
+        match (self, other) {
+            (StatType::Skill(SkillType::Lore(s)), StatType::Skill(SkillType::Lore(_)))
+            | (StatType::Skill(SkillType::Custom(s)), StatType::Skill(SkillType::Custom(_))) => {
+                s.is_empty() || self == other
+            }
+            _ => self == other,
+        }
+    }
+}
+
 #[rustfmt::skip]
-pub fn is_bonus(stat: StatType) -> bool {
+pub fn is_bonus(stat: &StatType) -> bool {
     matches!(stat, StatType::Perception
         | StatType::Skill(_)
         | StatType::SavingThrow(_)
@@ -98,7 +136,7 @@ pub fn is_bonus(stat: StatType) -> bool {
         | StatType::SpellAttackBonus)
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub struct Statistic {
     pub kind: StatType,
     pub value: f64,
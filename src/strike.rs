@@ -0,0 +1,417 @@
+use crate::damage::{self, Damage, DamageRoll, Dice};
+use crate::diagnostic::{self, Diagnostic, Span};
+use crate::logging::{self, LogLevel};
+use std::fmt;
+
+// A named Strike with its attack bonus, traits and damage on a single line:
+//
+// strike claw +17 (agile, reach 10 feet) 2d8+9 slashing
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Strike {
+    pub name: String,
+    pub attack_bonus: f64,
+    pub traits: Vec<String>,
+    pub damage: Damage,
+}
+
+impl Strike {
+    pub fn has_trait(&self, name: &str) -> bool {
+        self.traits.iter().any(|t| t.eq_ignore_ascii_case(name))
+    }
+
+    pub fn is_agile(&self) -> bool {
+        self.has_trait("agile")
+    }
+
+    pub fn critical_traits(&self) -> Vec<CriticalTrait> {
+        self.traits
+            .iter()
+            .filter_map(|t| parse_critical_trait_at(t, 0).and_then(Result::ok))
+            .collect()
+    }
+}
+
+// Traits that change the damage of a critical hit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CriticalTrait {
+    // Extra dice that aren't doubled, like deadly d10.
+    Deadly(Dice),
+    // The weapon damage dice become this size, plus one more die of it.
+    Fatal(i32),
+}
+
+impl CriticalTrait {
+    // Deadly gets a second die with three weapon damage dice and a third with
+    // four, like a weapon with striking runes. Fatal doesn't change.
+    pub fn scaled(self, weapon_dice: i32) -> Self {
+        match self {
+            CriticalTrait::Deadly(dice) => CriticalTrait::Deadly(Dice {
+                count: deadly_dice_count(weapon_dice),
+                size: dice.size,
+            }),
+            CriticalTrait::Fatal(_) => self,
+        }
+    }
+}
+
+impl fmt::Display for CriticalTrait {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CriticalTrait::Deadly(dice) if dice.count == 1 => write!(f, "deadly d{}", dice.size),
+            CriticalTrait::Deadly(dice) => write!(f, "deadly {}d{}", dice.count, dice.size),
+            CriticalTrait::Fatal(size) => write!(f, "fatal d{}", size),
+        }
+    }
+}
+
+fn deadly_dice_count(weapon_dice: i32) -> i32 {
+    match weapon_dice {
+        ..=2 => 1,
+        3 => 2,
+        _ => 3,
+    }
+}
+
+// Parse a deadly or fatal trait that starts at the given offset in a line.
+// Returns None for other traits.
+pub fn parse_critical_trait_at(
+    text: &str,
+    start: usize,
+) -> Option<Result<CriticalTrait, Diagnostic>> {
+    let lowercase = text.to_ascii_lowercase();
+    let (name, dice) = lowercase
+        .split_once(char::is_whitespace)
+        .unwrap_or((&lowercase, ""));
+    if name != "deadly" && name != "fatal" {
+        return None;
+    }
+
+    let dice = dice.trim();
+    let dice_start = start + text.len() - dice.len();
+    let error = || {
+        Diagnostic::new(
+            format!("invalid {} trait: {}", name, text),
+            Span::at(dice_start, dice),
+        )
+        .with_help(format!(
+            "write the trait as {} d<size>, such as {} d10",
+            name, name
+        ))
+    };
+
+    let (count, size) = match dice.split_once('d') {
+        Some(d) => d,
+        None => return Some(Err(error())),
+    };
+    // Only deadly can have more than one die.
+    let count = match count {
+        "" => Some(1),
+        c if name == "deadly" => c.parse().ok().filter(|c| *c > 0),
+        _ => None,
+    };
+    let size = match size {
+        "4" | "6" | "8" | "10" | "12" => size.parse().ok(),
+        _ => None,
+    };
+    let (count, size) = match (count, size) {
+        (Some(c), Some(s)) => (c, s),
+        _ => return Some(Err(error())),
+    };
+
+    Some(Ok(if name == "deadly" {
+        CriticalTrait::Deadly(Dice { count, size })
+    } else {
+        CriticalTrait::Fatal(size)
+    }))
+}
+
+// Rewrite deadly and fatal traits for a weapon with the given number of damage dice.
+pub fn scale_traits(traits: &[String], weapon_dice: i32) -> Vec<String> {
+    traits
+        .iter()
+        .map(|t| match parse_critical_trait_at(t, 0) {
+            Some(Ok(critical)) => critical.scaled(weapon_dice).to_string(),
+            _ => t.clone(),
+        })
+        .collect()
+}
+
+// The number of damage dice of the weapon, the first damage roll of a Strike.
+pub fn weapon_dice(rolls: &[DamageRoll]) -> i32 {
+    rolls
+        .first()
+        .map_or(0, |r| r.dice.iter().map(|d| d.count).sum())
+}
+
+// The damage rolls of a critical hit, which are doubled, and the extra dice of
+// deadly and fatal, which aren't. Fatal changes the dice of the weapon, the
+// first damage roll.
+pub fn critical_rolls(
+    rolls: &[DamageRoll],
+    traits: &[CriticalTrait],
+) -> (Vec<DamageRoll>, DamageRoll) {
+    let mut doubled = rolls.to_vec();
+    let mut extra = DamageRoll::default();
+
+    for critical in traits {
+        match critical {
+            CriticalTrait::Deadly(dice) => extra.dice.push(*dice),
+            CriticalTrait::Fatal(size) => {
+                if let Some(weapon) = doubled.first_mut() {
+                    for dice in &mut weapon.dice {
+                        dice.size = *size;
+                    }
+                }
+                extra.dice.push(Dice {
+                    count: 1,
+                    size: *size,
+                });
+            }
+        }
+    }
+
+    (doubled, extra)
+}
+
+const KEYWORD: &str = "strike ";
+
+pub fn is_strike(line: &str) -> bool {
+    let line = line.trim_start();
+
+    line.get(..KEYWORD.len())
+        .is_some_and(|k| k.eq_ignore_ascii_case(KEYWORD))
+}
+
+fn log_invalid_strike(line: &str) {
+    logging::log(LogLevel::Error, format!("invalid strike: {}", line.trim()));
+    logging::log(
+        LogLevel::Info,
+        "usage: strike <name> +<bonus> [(<trait>, ...)] <damage>",
+    );
+}
+
+fn is_attack_bonus(token: &str) -> bool {
+    let digits = match token.strip_prefix('+').or(token.strip_prefix('-')) {
+        Some(d) => d,
+        None => return false,
+    };
+
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+// Split an optional parenthesised trait list off the front of the rest of the line.
+fn split_traits(rest: &str) -> Option<(Vec<&str>, &str)> {
+    let rest = rest.trim_start();
+
+    let inner = match rest.strip_prefix('(') {
+        Some(i) => i,
+        None => return Some((Vec::new(), rest)),
+    };
+
+    let (traits, rest) = inner.split_once(')')?;
+    let traits = traits
+        .split(',')
+        .map(|t| t.trim())
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    Some((traits, rest))
+}
+
+// Parse a strike line. The name and traits keep their original case.
+pub fn parse_strike(line: &str) -> Option<Strike> {
+    if !is_strike(line) {
+        log_invalid_strike(line);
+        return None;
+    }
+    let rest = &line.trim_start()[KEYWORD.len()..];
+
+    let tokens: Vec<&str> = rest.split_whitespace().collect();
+    let bonus_index = match tokens.iter().position(|t| is_attack_bonus(t)) {
+        Some(i) if i > 0 => i,
+        _ => {
+            log_invalid_strike(line);
+            return None;
+        }
+    };
+
+    let name = tokens[..bonus_index].join(" ");
+    let attack_bonus: i32 = match tokens[bonus_index].parse() {
+        Ok(b) => b,
+        Err(_) => {
+            logging::log(
+                LogLevel::Error,
+                format!(
+                    "strike attack bonus is not a valid integer: {}",
+                    tokens[bonus_index]
+                ),
+            );
+            return None;
+        }
+    };
+
+    // Everything after the bonus, with its original spacing.
+    let mut end = 0;
+    for token in &tokens[..=bonus_index] {
+        end += rest[end..]
+            .find(token)
+            .expect("token should be in the line")
+            + token.len();
+    }
+    let after_bonus = &rest[end..];
+
+    let (traits, damage) = match split_traits(after_bonus) {
+        Some(t) => t,
+        None => {
+            log_invalid_strike(line);
+            return None;
+        }
+    };
+
+    if damage.trim().is_empty() {
+        log_invalid_strike(line);
+        return None;
+    }
+
+    for t in &traits {
+        let offset = diagnostic::offset_in(line, t).unwrap_or(0);
+        if let Some(Err(d)) = parse_critical_trait_at(t, offset) {
+            d.log(line);
+            return None;
+        }
+    }
+
+    let offset = diagnostic::offset_in(line, damage).unwrap_or(0);
+    let damage = match damage::parse_damage_at(damage, offset) {
+        Ok(d) => d,
+        Err(d) => {
+            d.log(line);
+            return None;
+        }
+    };
+
+    Some(Strike {
+        name,
+        attack_bonus: attack_bonus.into(),
+        traits: traits.into_iter().map(String::from).collect(),
+        damage,
+    })
+}
+
+pub fn format_traits(traits: &[String]) -> String {
+    if traits.is_empty() {
+        String::new()
+    } else {
+        format!("({}) ", traits.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::float_eq;
+
+    #[test]
+    fn accept_valid_strike() {
+        let strike = parse_strike("strike jaws +15 2d10+7 piercing").unwrap();
+        assert_eq!(strike.name, "jaws");
+        assert!(float_eq(strike.attack_bonus, 15.0));
+        assert!(strike.traits.is_empty());
+        assert!(float_eq(strike.damage.total_average_value(), 18.0));
+
+        let strike =
+            parse_strike("Strike Tail Lash +13 (agile, reach 10 feet) 2d6+7 bludgeoning").unwrap();
+        assert_eq!(strike.name, "Tail Lash");
+        assert_eq!(strike.traits, vec!["agile", "reach 10 feet"]);
+        assert!(strike.is_agile());
+        assert!(float_eq(strike.damage.total_average_value(), 14.0));
+
+        let strike = parse_strike("strike claw -1 (Agile) 1d4 slashing plus 1d4 fire").unwrap();
+        assert!(float_eq(strike.attack_bonus, -1.0));
+        assert!(strike.is_agile());
+        assert_eq!(strike.damage.components.len(), 2);
+    }
+
+    #[test]
+    fn reject_invalid_strike() {
+        assert_eq!(parse_strike("strike"), None);
+        assert_eq!(parse_strike("strike jaws"), None);
+        assert_eq!(parse_strike("strike +15 2d10+7 piercing"), None);
+        assert_eq!(parse_strike("strike jaws 15 2d10+7 piercing"), None);
+        assert_eq!(parse_strike("strike jaws +15"), None);
+        assert_eq!(parse_strike("strike jaws +15 (agile"), None);
+        assert_eq!(parse_strike("strike jaws +15 (agile)"), None);
+        assert_eq!(parse_strike("strike jaws +15 2d7 piercing"), None);
+        assert_eq!(parse_strike("strike-attack +15"), None);
+    }
+
+    #[test]
+    fn parse_critical_traits() {
+        let strike =
+            parse_strike("strike pick +15 (Deadly 2d10, fatal d12) 2d6+7 piercing").unwrap();
+        assert_eq!(
+            strike.critical_traits(),
+            vec![
+                CriticalTrait::Deadly(Dice { count: 2, size: 10 }),
+                CriticalTrait::Fatal(12)
+            ]
+        );
+        assert_eq!(strike.traits, vec!["Deadly 2d10", "fatal d12"]);
+
+        assert_eq!(parse_critical_trait_at("agile", 0), None);
+        assert_eq!(parse_critical_trait_at("reach 10 feet", 0), None);
+
+        assert_eq!(
+            parse_strike("strike pick +15 (deadly d7) 2d6+7 piercing"),
+            None
+        );
+        assert_eq!(
+            parse_strike("strike pick +15 (deadly 0d10) 2d6+7 piercing"),
+            None
+        );
+        assert_eq!(
+            parse_strike("strike pick +15 (fatal 2d12) 2d6+7 piercing"),
+            None
+        );
+        assert_eq!(parse_strike("strike pick +15 (fatal) 2d6+7 piercing"), None);
+        assert!(parse_critical_trait_at("deadly ten", 0).unwrap().is_err());
+    }
+
+    #[test]
+    fn scale_critical_traits() {
+        let traits = vec![String::from("deadly d10"), String::from("fatal d12")];
+        assert_eq!(scale_traits(&traits, 2), vec!["deadly d10", "fatal d12"]);
+        assert_eq!(scale_traits(&traits, 3), vec!["deadly 2d10", "fatal d12"]);
+        assert_eq!(scale_traits(&traits, 4), vec!["deadly 3d10", "fatal d12"]);
+        assert_eq!(
+            scale_traits(&[String::from("deadly 3d8")], 1),
+            vec!["deadly d8"]
+        );
+
+        let rolls = vec![damage::parse_damage_roll("3d6+8").unwrap()];
+        assert_eq!(weapon_dice(&rolls), 3);
+        assert_eq!(weapon_dice(&[]), 0);
+
+        let (doubled, extra) = critical_rolls(&rolls, &[CriticalTrait::Fatal(10)]);
+        assert_eq!(doubled[0].to_string(), "3d10+8");
+        assert_eq!(extra.to_string(), "1d10");
+    }
+
+    #[test]
+    fn detect_strike_lines() {
+        assert!(is_strike("strike jaws +15 2d10+7 piercing"));
+        assert!(is_strike("  STRIKE jaws +15 2d10+7 piercing"));
+        assert!(!is_strike("strike-attack +15"));
+        assert!(!is_strike("strike"));
+    }
+
+    #[test]
+    fn format_strike_traits() {
+        assert_eq!(format_traits(&[]), "");
+        assert_eq!(
+            format_traits(&[String::from("agile"), String::from("reach 10 feet")]),
+            "(agile, reach 10 feet) "
+        );
+    }
+}
@@ -0,0 +1,104 @@
+// Suggestions for mistyped words, such as "perceptoin" for "perception".
+
+// Number of single-character insertions, deletions, substitutions and
+// transpositions of adjacent characters needed to turn one word into another.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // distances[i][j] is the distance between the first i characters of a and
+    // the first j characters of b.
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
+
+// The closest candidate, if it is close enough to be a likely typo. Longer
+// words may have more typos.
+pub fn suggest<'a>(word: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let word = word.to_lowercase();
+    let max_distance = (word.chars().count() / 4).max(1);
+
+    candidates
+        .iter()
+        .map(|c| (edit_distance(&word, c), *c))
+        .filter(|(d, _)| *d > 0 && *d <= max_distance)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, c)| c)
+}
+
+// The candidates that start with the prefix, in their original order.
+pub fn complete<'a>(prefix: &str, candidates: &[&'a str]) -> Vec<&'a str> {
+    let prefix = prefix.to_lowercase();
+
+    candidates
+        .iter()
+        .filter(|c| c.starts_with(&prefix))
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compute_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("reflex", "reflex"), 0);
+        assert_eq!(edit_distance("", "ac"), 2);
+        assert_eq!(edit_distance("acrobatic", "acrobatics"), 1);
+        assert_eq!(edit_distance("strke-damage", "strike-damage"), 1);
+        assert_eq!(edit_distance("relfex", "reflex"), 1);
+        assert_eq!(edit_distance("perceptoin", "perception"), 1);
+        assert_eq!(edit_distance("fire", "cold"), 4);
+    }
+
+    #[test]
+    fn suggest_close_words() {
+        let candidates = ["perception", "reflex", "ref", "strike-damage", "ac", "hp"];
+
+        assert_eq!(suggest("perceptoin", &candidates), Some("perception"));
+        assert_eq!(suggest("Reflx", &candidates), Some("reflex"));
+        assert_eq!(suggest("strke-damage", &candidates), Some("strike-damage"));
+
+        // Exact matches and distant words have no suggestion.
+        assert_eq!(suggest("reflex", &candidates), None);
+        assert_eq!(suggest("will", &candidates), None);
+        assert_eq!(suggest("xy", &candidates), None);
+    }
+
+    #[test]
+    fn complete_prefixes() {
+        let candidates = ["strike-attack", "strike-damage", "spell-dc"];
+
+        assert_eq!(
+            complete("str", &candidates),
+            vec!["strike-attack", "strike-damage"]
+        );
+        assert_eq!(complete("S", &candidates).len(), 3);
+        assert!(complete("x", &candidates).is_empty());
+    }
+}
@@ -0,0 +1,303 @@
+use crate::levels::MIN_LEVEL;
+use crate::logging::{self, LogLevel};
+use crate::tables::{self, Proficiency, StatTable, TableKind, TableSet, TABLE_KINDS};
+use std::fs;
+
+// A table file overrides some or all of the built-in tables. Each table starts
+// with a [<table>] header, followed by its proficiency columns and one row of
+// values per level:
+//
+// [hp]
+// proficiencies: low low moderate moderate high high
+// -1: 5 6 7 8 9 9
+// 0: 11 13 14 16 17 20
+// ...
+
+struct PendingTable {
+    kind: TableKind,
+    proficiencies: Vec<Proficiency>,
+    values: Vec<Vec<f64>>,
+}
+
+fn log_line_error<T: Into<String> + std::fmt::Display>(line_number: usize, msg: T) {
+    logging::log(
+        LogLevel::Error,
+        format!("table file line {}: {}", line_number, msg),
+    );
+}
+
+fn parse_table_kind(name: &str) -> Option<TableKind> {
+    let name = name.trim().to_lowercase();
+
+    TABLE_KINDS.into_iter().find(|k| k.to_string() == name)
+}
+
+fn parse_header(line_number: usize, line: &str) -> Option<TableKind> {
+    let name = line
+        .strip_prefix('[')
+        .and_then(|l| l.strip_suffix(']'))
+        .unwrap_or_default();
+
+    match parse_table_kind(name) {
+        Some(k) => Some(k),
+        None => {
+            log_line_error(line_number, format!("unknown table: {}", line));
+            let names: Vec<String> = TABLE_KINDS.iter().map(|k| k.to_string()).collect();
+            logging::log(LogLevel::Info, format!("tables: {}", names.join(", ")));
+            None
+        }
+    }
+}
+
+fn parse_proficiencies(line_number: usize, proficiencies: &str) -> Option<Vec<Proficiency>> {
+    let mut result: Vec<Proficiency> = Vec::new();
+
+    for p in proficiencies.split_whitespace() {
+        match tables::parse_proficiency(p) {
+            Some(p) => result.push(p),
+            None => {
+                log_line_error(line_number, format!("unknown proficiency: {}", p));
+                return None;
+            }
+        }
+    }
+
+    Some(result)
+}
+
+fn parse_row(line_number: usize, expected_level: i32, line: &str) -> Option<Vec<f64>> {
+    let (level, values) = match line.split_once(':') {
+        Some(r) => r,
+        None => {
+            log_line_error(
+                line_number,
+                format!("expected <level>: <values>, got {}", line),
+            );
+            return None;
+        }
+    };
+
+    match level.trim().parse::<i32>() {
+        Ok(l) if l == expected_level => (),
+        _ => {
+            log_line_error(
+                line_number,
+                format!(
+                    "expected the row for level {}, got {}",
+                    expected_level,
+                    level.trim()
+                ),
+            );
+            return None;
+        }
+    }
+
+    let mut row: Vec<f64> = Vec::new();
+
+    for value in values.split_whitespace() {
+        match value.parse::<f64>() {
+            Ok(v) if v.is_finite() => row.push(v),
+            _ => {
+                log_line_error(
+                    line_number,
+                    format!("value is not a valid number: {}", value),
+                );
+                return None;
+            }
+        }
+    }
+
+    Some(row)
+}
+
+fn finish_table(pending: PendingTable, tables: &mut TableSet) -> bool {
+    let table = StatTable {
+        values: pending.values,
+        proficiencies: pending.proficiencies,
+    };
+
+    if !tables::check_table(pending.kind, &table) {
+        return false;
+    }
+
+    tables.set(pending.kind, table);
+    true
+}
+
+pub fn parse_table_file(contents: &str) -> Option<TableSet> {
+    let mut tables = TableSet::new();
+    let mut seen: Vec<TableKind> = Vec::new();
+    let mut pending: Option<PendingTable> = None;
+
+    for (i, line) in contents.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
+            continue;
+        }
+
+        if line.starts_with('[') {
+            if let Some(p) = pending.take() {
+                if !finish_table(p, &mut tables) {
+                    return None;
+                }
+            }
+
+            let kind = parse_header(line_number, line)?;
+            if seen.contains(&kind) {
+                log_line_error(line_number, format!("duplicate table: {}", kind));
+                return None;
+            }
+            seen.push(kind);
+
+            pending = Some(PendingTable {
+                kind,
+                proficiencies: Vec::new(),
+                values: Vec::new(),
+            });
+            continue;
+        }
+
+        let table = match pending.as_mut() {
+            Some(t) => t,
+            None => {
+                log_line_error(line_number, "expected a [<table>] header");
+                return None;
+            }
+        };
+
+        let lowercase = line.to_lowercase();
+        if let Some(proficiencies) = lowercase.strip_prefix("proficiencies:") {
+            table.proficiencies = parse_proficiencies(line_number, proficiencies)?;
+            continue;
+        }
+
+        let expected_level = MIN_LEVEL + table.values.len() as i32;
+        let row = parse_row(line_number, expected_level, line)?;
+        table.values.push(row);
+    }
+
+    if let Some(p) = pending.take() {
+        if !finish_table(p, &mut tables) {
+            return None;
+        }
+    }
+
+    Some(tables)
+}
+
+pub fn load_table_file(path: &str) -> Option<TableSet> {
+    let contents = match fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(_) => {
+            logging::log(
+                LogLevel::Error,
+                format!("failed to read table file: {}", path),
+            );
+            return None;
+        }
+    };
+
+    parse_table_file(&contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::levels::MAX_LEVEL;
+
+    fn format_table(kind: TableKind, table: &StatTable) -> String {
+        let proficiencies: Vec<String> =
+            table.proficiencies.iter().map(|p| p.to_string()).collect();
+
+        let mut contents = format!("[{}]\nproficiencies: {}\n", kind, proficiencies.join(" "));
+
+        for (i, row) in table.values.iter().enumerate() {
+            let values: Vec<String> = row.iter().map(|v| v.to_string()).collect();
+            let level = MIN_LEVEL + i as i32;
+            contents += &format!("{}: {}\n", level, values.join(" "));
+        }
+
+        contents
+    }
+
+    fn doubled_hit_points() -> StatTable {
+        let mut table = tables::builtin_table(TableKind::HitPoints);
+        for row in table.values.iter_mut() {
+            for v in row.iter_mut() {
+                *v *= 2.0;
+            }
+        }
+        table
+    }
+
+    #[test]
+    fn accept_valid_table_file() {
+        let table = doubled_hit_points();
+        let contents = format!(
+            "# homebrew\n\n{}",
+            format_table(TableKind::HitPoints, &table)
+        );
+
+        let tables = parse_table_file(&contents).unwrap();
+        assert!(tables.is_custom(TableKind::HitPoints));
+        assert!(!tables.is_custom(TableKind::ArmorClass));
+        assert_eq!(tables.get(TableKind::HitPoints), table);
+
+        let ac = tables::builtin_table(TableKind::ArmorClass);
+        let contents = format!(
+            "{}\n{}",
+            format_table(TableKind::HitPoints, &table),
+            format_table(TableKind::ArmorClass, &ac)
+        );
+
+        let tables = parse_table_file(&contents).unwrap();
+        assert!(tables.is_custom(TableKind::HitPoints));
+        assert!(tables.is_custom(TableKind::ArmorClass));
+    }
+
+    #[test]
+    fn accept_every_builtin_table() {
+        let mut contents = String::new();
+        for kind in TABLE_KINDS {
+            contents += &format_table(kind, &tables::builtin_table(kind));
+        }
+
+        let tables = parse_table_file(&contents).unwrap();
+        for kind in TABLE_KINDS {
+            assert_eq!(tables.get(kind), tables::builtin_table(kind));
+        }
+    }
+
+    #[test]
+    fn reject_invalid_table_file() {
+        let table = doubled_hit_points();
+        let valid = format_table(TableKind::HitPoints, &table);
+
+        // Rows before any header.
+        assert!(parse_table_file("-1: 1 2 3").is_none());
+
+        // Unknown table and proficiency.
+        assert!(parse_table_file(&valid.replace("[hp]", "[mana]")).is_none());
+        assert!(parse_table_file(&valid.replace("Moderate", "Mediocre")).is_none());
+
+        // Missing, misnumbered and malformed rows.
+        let missing_row = valid.replace(&format!("{}: ", MAX_LEVEL), "# ");
+        assert!(parse_table_file(&missing_row).is_none());
+        assert!(parse_table_file(&valid.replace("\n0: ", "\n1: ")).is_none());
+        assert!(parse_table_file(&valid.replace("\n0: ", "\n0 ")).is_none());
+        assert!(parse_table_file(&valid.replace("\n0: 22", "\n0: x")).is_none());
+
+        // Values decreasing with proficiency.
+        assert!(parse_table_file(&valid.replace("\n0: 22 26", "\n0: 26 22")).is_none());
+
+        // Duplicate tables.
+        assert!(parse_table_file(&format!("{}{}", valid, valid)).is_none());
+    }
+
+    #[test]
+    fn reject_nonexistent_table_file() {
+        assert!(load_table_file("testdata/nonexistent_table_file.txt").is_none());
+    }
+}
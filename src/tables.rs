@@ -1,6 +1,12 @@
+use crate::levels::{self, MAX_LEVEL, MIN_LEVEL};
+use crate::logging::{self, LogLevel};
 use crate::statistic::StatType;
 use std::fmt;
 
+// Number of rows at each end of a table used to fit the trend of each column
+// when synthesising rows for levels outside the official range.
+const TREND_ROWS: usize = 3;
+
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
 pub enum Proficiency {
     Terrible,
@@ -24,29 +30,314 @@ impl fmt::Display for Proficiency {
     }
 }
 
+pub fn parse_proficiency(proficiency: &str) -> Option<Proficiency> {
+    match proficiency.trim().to_lowercase().as_str() {
+        "terrible" => Some(Proficiency::Terrible),
+        "low" => Some(Proficiency::Low),
+        "moderate" => Some(Proficiency::Moderate),
+        "high" => Some(Proficiency::High),
+        "extreme" => Some(Proficiency::Extreme),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct StatTable {
     pub values: Vec<Vec<f64>>,
     pub proficiencies: Vec<Proficiency>,
 }
 
-pub fn get_table_for_statistic(stat: StatType) -> StatTable {
+// Least squares slope of values at consecutive levels.
+fn fit_slope(values: &[f64]) -> f64 {
+    let n = values.len() as f64;
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = values.iter().sum::<f64>() / n;
+
+    let mut covariance = 0.0;
+    let mut variance = 0.0;
+
+    for (x, y) in values.iter().enumerate() {
+        let dx = x as f64 - mean_x;
+        covariance += dx * (y - mean_y);
+        variance += dx * dx;
+    }
+
+    covariance / variance
+}
+
+impl StatTable {
+    fn column(&self, column: usize, rows: std::ops::Range<usize>) -> Vec<f64> {
+        self.values[rows].iter().map(|row| row[column]).collect()
+    }
+
+    // Continue each column from the nearest official row along the trend of the
+    // rows at that end of the table. Values are rounded like the official ones,
+    // and kept non-decreasing across columns.
+    fn synthesise_row(&self, level: i32) -> Vec<f64> {
+        let n = self.values.len();
+
+        let (edge_row, trend_rows, distance) = if level > MAX_LEVEL {
+            (n - 1, n - TREND_ROWS..n, level - MAX_LEVEL)
+        } else {
+            (0, 0..TREND_ROWS, level - MIN_LEVEL)
+        };
+        let distance: f64 = distance.into();
+
+        let mut row: Vec<f64> = Vec::new();
+
+        for (i, edge) in self.values[edge_row].iter().enumerate() {
+            let slope = fit_slope(&self.column(i, trend_rows.clone()));
+            let value = (edge + slope * distance).round();
+
+            let previous = row.last().copied().unwrap_or(f64::MIN);
+            row.push(value.max(previous));
+        }
+
+        row
+    }
+
+    pub fn row(&self, level: i32) -> Vec<f64> {
+        if !levels::is_official(level) {
+            return self.synthesise_row(level);
+        }
+
+        let i: usize = (level - MIN_LEVEL)
+            .try_into()
+            .expect("level should be in range");
+
+        self.values[i].clone()
+    }
+
+    // The lowest and highest values of the columns with the given proficiency at
+    // a level. Some tables have more than one column per proficiency, like HP.
+    pub fn proficiency_range(&self, level: i32, proficiency: Proficiency) -> Option<(f64, f64)> {
+        let row = self.row(level);
+        let values: Vec<f64> = self
+            .proficiencies
+            .iter()
+            .zip(row)
+            .filter(|(p, _)| **p == proficiency)
+            .map(|(_, v)| v)
+            .collect();
+
+        Some((*values.first()?, *values.last()?))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TableKind {
+    Perception,
+    Skills,
+    ArmorClass,
+    SavingThrows,
+    HitPoints,
+    ResistanceWeakness,
+    StrikeAttackBonus,
+    StrikeDamage,
+    SpellDC,
+    SpellAttackBonus,
+    UnlimitedAreaDamage,
+    LimitedAreaDamage,
+}
+
+pub const TABLE_KINDS: [TableKind; 12] = [
+    TableKind::Perception,
+    TableKind::Skills,
+    TableKind::ArmorClass,
+    TableKind::SavingThrows,
+    TableKind::HitPoints,
+    TableKind::ResistanceWeakness,
+    TableKind::StrikeAttackBonus,
+    TableKind::StrikeDamage,
+    TableKind::SpellDC,
+    TableKind::SpellAttackBonus,
+    TableKind::UnlimitedAreaDamage,
+    TableKind::LimitedAreaDamage,
+];
+
+impl fmt::Display for TableKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            TableKind::Perception => "perception",
+            TableKind::Skills => "skills",
+            TableKind::ArmorClass => "ac",
+            TableKind::SavingThrows => "saving-throws",
+            TableKind::HitPoints => "hp",
+            TableKind::ResistanceWeakness => "resistance-weakness",
+            TableKind::StrikeAttackBonus => "strike-attack",
+            TableKind::StrikeDamage => "strike-damage",
+            TableKind::SpellDC => "spell-dc",
+            TableKind::SpellAttackBonus => "spell-attack",
+            TableKind::UnlimitedAreaDamage => "unlimited-area-damage",
+            TableKind::LimitedAreaDamage => "limited-area-damage",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+impl TableKind {
+    // Damage tables are used to build damage expressions, which need
+    // positive values and a proficiency with preferred dice.
+    pub fn is_damage(&self) -> bool {
+        matches!(
+            self,
+            TableKind::StrikeDamage | TableKind::UnlimitedAreaDamage | TableKind::LimitedAreaDamage
+        )
+    }
+
+    // Amounts like HP and damage can't drop to zero or below.
+    pub fn is_amount(&self) -> bool {
+        self.is_damage() || matches!(self, TableKind::HitPoints | TableKind::ResistanceWeakness)
+    }
+}
+
+pub fn table_kind_for_statistic(stat: &StatType) -> TableKind {
     match stat {
-        StatType::Perception => perception(),
-        StatType::Skill(_) => skills(),
-        StatType::ArmorClass => armor_class(),
-        StatType::SavingThrow(_) => saving_throws(),
-        StatType::HitPoints => hit_points(),
-        StatType::Resistance => resistance_weakness(),
-        StatType::Weakness => resistance_weakness(),
-        StatType::StrikeAttackBonus => strike_attack_bonus(),
-        StatType::StrikeDamage => strike_damage(),
-        StatType::SpellDC => spell_dc(),
-        StatType::SpellAttackBonus => spell_attack_bonus(),
-        StatType::UnlimitedAreaDamage => unlimited_area_damage(),
-        StatType::LimitedAreaDamage => limited_area_damage(),
+        StatType::Perception => TableKind::Perception,
+        StatType::Skill(_) => TableKind::Skills,
+        StatType::ArmorClass => TableKind::ArmorClass,
+        StatType::SavingThrow(_) => TableKind::SavingThrows,
+        StatType::HitPoints => TableKind::HitPoints,
+        StatType::Resistance => TableKind::ResistanceWeakness,
+        StatType::BroadResistance => TableKind::ResistanceWeakness,
+        StatType::Weakness => TableKind::ResistanceWeakness,
+        StatType::StrikeAttackBonus => TableKind::StrikeAttackBonus,
+        StatType::StrikeDamage => TableKind::StrikeDamage,
+        StatType::SpellDC => TableKind::SpellDC,
+        StatType::AbilityDC => TableKind::SpellDC,
+        StatType::SpellAttackBonus => TableKind::SpellAttackBonus,
+        StatType::UnlimitedAreaDamage => TableKind::UnlimitedAreaDamage,
+        StatType::LimitedAreaDamage => TableKind::LimitedAreaDamage,
+    }
+}
+
+pub fn builtin_table(kind: TableKind) -> StatTable {
+    match kind {
+        TableKind::Perception => perception(),
+        TableKind::Skills => skills(),
+        TableKind::ArmorClass => armor_class(),
+        TableKind::SavingThrows => saving_throws(),
+        TableKind::HitPoints => hit_points(),
+        TableKind::ResistanceWeakness => resistance_weakness(),
+        TableKind::StrikeAttackBonus => strike_attack_bonus(),
+        TableKind::StrikeDamage => strike_damage(),
+        TableKind::SpellDC => spell_dc(),
+        TableKind::SpellAttackBonus => spell_attack_bonus(),
+        TableKind::UnlimitedAreaDamage => unlimited_area_damage(),
+        TableKind::LimitedAreaDamage => limited_area_damage(),
+    }
+}
+
+pub fn get_table_for_statistic(stat: &StatType) -> StatTable {
+    builtin_table(table_kind_for_statistic(stat))
+}
+
+// The tables to scale with: the built-in ones, overridden by any custom tables.
+#[derive(Clone, Default)]
+pub struct TableSet {
+    custom: Vec<(TableKind, StatTable)>,
+}
+
+impl TableSet {
+    pub fn new() -> Self {
+        Self { custom: Vec::new() }
+    }
+
+    // Replace the table of the given kind. A later override wins.
+    pub fn set(&mut self, kind: TableKind, table: StatTable) {
+        self.custom.retain(|(k, _)| *k != kind);
+        self.custom.push((kind, table));
+    }
+
+    pub fn is_custom(&self, kind: TableKind) -> bool {
+        self.custom.iter().any(|(k, _)| *k == kind)
+    }
+
+    pub fn get(&self, kind: TableKind) -> StatTable {
+        match self.custom.iter().find(|(k, _)| *k == kind) {
+            Some((_, table)) => table.clone(),
+            None => builtin_table(kind),
+        }
+    }
+
+    pub fn get_for_statistic(&self, stat: &StatType) -> StatTable {
+        self.get(table_kind_for_statistic(stat))
     }
 }
 
+// Check the invariants every table must hold, logging each violation.
+pub fn check_table(kind: TableKind, table: &StatTable) -> bool {
+    let mut errors: Vec<String> = Vec::new();
+
+    if table.values.len() != levels::num_levels() {
+        errors.push(format!(
+            "expected {} rows for levels {} to {}, got {}",
+            levels::num_levels(),
+            MIN_LEVEL,
+            MAX_LEVEL,
+            table.values.len()
+        ));
+    }
+
+    let columns = table.proficiencies.len();
+    if columns == 0 {
+        errors.push(String::from("no proficiency columns"));
+    }
+
+    for (i, row) in table.values.iter().enumerate() {
+        let level = MIN_LEVEL + i as i32;
+
+        if row.len() != columns {
+            errors.push(format!(
+                "level {}: expected {} columns, got {}",
+                level,
+                columns,
+                row.len()
+            ));
+            continue;
+        }
+
+        if row.windows(2).any(|w| w[0] > w[1]) {
+            errors.push(format!("level {}: values decrease with proficiency", level));
+        }
+
+        if let Some(next) = table.values.get(i + 1) {
+            if next.len() == columns && row.iter().zip(next).any(|(a, b)| a > b) {
+                errors.push(format!(
+                    "level {}: values decrease at the next level",
+                    level
+                ));
+            }
+        }
+
+        if kind.is_damage() && row.iter().any(|v| *v <= 0.0) {
+            errors.push(format!("level {}: damage values must be positive", level));
+        }
+    }
+
+    if table.proficiencies.windows(2).any(|w| w[0] > w[1]) {
+        errors.push(String::from(
+            "proficiencies are not in non-descending order",
+        ));
+    }
+
+    if kind.is_damage() && table.proficiencies.contains(&Proficiency::Terrible) {
+        errors.push(String::from(
+            "damage tables can't have a terrible proficiency",
+        ));
+    }
+
+    for error in &errors {
+        logging::log(
+            LogLevel::Error,
+            format!("invalid {} table: {}", kind, error),
+        );
+    }
+
+    errors.is_empty()
+}
+
 fn to_float(table: Vec<Vec<i32>>) -> Vec<Vec<f64>> {
     let mut v: Vec<Vec<f64>> = Vec::new();
 
@@ -610,6 +901,136 @@ mod tests {
         }
     }
 
+    fn validate_synthesised_rows(table: StatTable) {
+        let mut rows: Vec<Vec<f64>> = Vec::new();
+        for level in levels::MIN_EXTENDED_LEVEL..=levels::MAX_EXTENDED_LEVEL {
+            rows.push(table.row(level));
+        }
+
+        // Synthesised rows keep the invariants of the official ones.
+        for i in 0..rows.len() - 1 {
+            for j in 0..rows[i].len() {
+                assert!(rows[i][j] <= rows[i + 1][j]);
+                if j < rows[i].len() - 1 {
+                    assert!(rows[i][j] <= rows[i][j + 1]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn builtin_tables_pass_check() {
+        for kind in TABLE_KINDS {
+            assert!(check_table(kind, &builtin_table(kind)));
+        }
+    }
+
+    #[test]
+    fn check_rejects_invalid_tables() {
+        let mut table = hit_points();
+        table.values.pop();
+        assert!(!check_table(TableKind::HitPoints, &table));
+
+        let mut table = hit_points();
+        table.values[3].swap(0, 1);
+        assert!(!check_table(TableKind::HitPoints, &table));
+
+        let mut table = hit_points();
+        table.values[3][0] = 1000.0;
+        assert!(!check_table(TableKind::HitPoints, &table));
+
+        let mut table = hit_points();
+        table.values[3].push(1000.0);
+        assert!(!check_table(TableKind::HitPoints, &table));
+
+        let mut table = hit_points();
+        table.proficiencies.reverse();
+        assert!(!check_table(TableKind::HitPoints, &table));
+
+        let mut table = saving_throws();
+        assert!(check_table(TableKind::SavingThrows, &table));
+        table.values[0][0] = -1.0;
+        assert!(check_table(TableKind::SavingThrows, &table));
+        assert!(!check_table(TableKind::StrikeDamage, &table));
+    }
+
+    #[test]
+    fn override_tables() {
+        let mut tables = TableSet::new();
+        assert_eq!(tables.get(TableKind::HitPoints), hit_points());
+
+        let mut custom = hit_points();
+        custom.values[0][0] = 1.0;
+        tables.set(TableKind::HitPoints, custom.clone());
+
+        assert!(tables.is_custom(TableKind::HitPoints));
+        assert_eq!(tables.get_for_statistic(&StatType::HitPoints), custom);
+        assert_eq!(tables.get(TableKind::ArmorClass), armor_class());
+    }
+
+    #[test]
+    fn test_fit_slope() {
+        use crate::utils::float_eq;
+
+        assert!(float_eq(fit_slope(&[1.0, 2.0, 3.0]), 1.0));
+        assert!(float_eq(fit_slope(&[5.0, 5.0, 5.0]), 0.0));
+        assert!(float_eq(fit_slope(&[5.0, 11.0, 14.0]), 4.5));
+    }
+
+    #[test]
+    fn synthesise_rows() {
+        let table = hit_points();
+
+        assert_eq!(table.row(MIN_LEVEL), table.values[0]);
+        assert_eq!(table.row(MAX_LEVEL), table.values[table.values.len() - 1]);
+
+        // The top three rows of the Low column are 317, 339 and 367.
+        assert_eq!(table.row(MAX_LEVEL + 1)[0], 392.0);
+        assert_eq!(table.row(MAX_LEVEL + 2)[0], 417.0);
+    }
+
+    #[test]
+    fn proficiency_ranges() {
+        assert_eq!(
+            hit_points().proficiency_range(5, Proficiency::High),
+            Some((91.0, 97.0))
+        );
+        assert_eq!(
+            armor_class().proficiency_range(5, Proficiency::Extreme),
+            Some((25.0, 25.0))
+        );
+        assert_eq!(
+            armor_class().proficiency_range(5, Proficiency::Terrible),
+            None
+        );
+    }
+
+    #[test]
+    fn validate_synthesised_table_rows() {
+        for table in [
+            perception(),
+            skills(),
+            armor_class(),
+            saving_throws(),
+            hit_points(),
+            resistance_weakness(),
+            strike_attack_bonus(),
+            strike_damage(),
+            spell_dc(),
+            spell_attack_bonus(),
+            unlimited_area_damage(),
+            limited_area_damage(),
+        ] {
+            validate_synthesised_rows(table);
+        }
+
+        // Amounts like HP and damage stay positive on the lowest synthesised row.
+        for kind in TABLE_KINDS.into_iter().filter(TableKind::is_amount) {
+            let row = builtin_table(kind).row(levels::MIN_EXTENDED_LEVEL);
+            assert!(row.iter().all(|v| *v >= 1.0), "{}", kind);
+        }
+    }
+
     #[test]
     fn validate_perception_table() {
         let table = perception();